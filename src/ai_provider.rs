@@ -1,14 +1,7 @@
+use crate::backend::{Anthropic, ChatCompletions, Completion, Gemini, TranslationBackend};
+use crate::error::TranslatorError;
+use crate::retry::RetryPolicy;
 use anyhow::Result;
-use async_openai::{Client as OpenAIClient, types::{CreateChatCompletionRequestArgs, ChatCompletionRequestMessage, Role, ChatCompletionRequestSystemMessage, ChatCompletionRequestUserMessage, ChatCompletionRequestUserMessageContent}};
-use anthropic::{client::Client as AnthropicClient};
-use google_generative_ai_rs::v1::{
-    api::Client as GeminiClient,
-    gemini::{
-        request::{Request as GeminiRequest, GenerationConfig, SafetySettings},
-        safety::{HarmCategory, HarmBlockThreshold},
-        Content, Part, Role as GeminiRole, Model,
-    },
-};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -24,6 +17,17 @@ pub enum AIError {
     ModelNotAvailable,
 }
 
+impl From<TranslatorError> for AIError {
+    fn from(error: TranslatorError) -> Self {
+        match error {
+            TranslatorError::Unauthorized { .. } => AIError::InvalidAPIKey,
+            TranslatorError::RateLimited { .. } => AIError::RateLimitExceeded,
+            TranslatorError::ApiError { status: 404, .. } => AIError::ModelNotAvailable,
+            error => AIError::RequestFailed(error.to_string()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AIProvider {
     OpenAI {
@@ -38,127 +42,81 @@ pub enum AIProvider {
         api_key: String,
         model: String,
     },
+    OpenRouter {
+        api_key: String,
+        model: String,
+    },
 }
 
 impl AIProvider {
-    pub async fn generate(&self, system_prompt: &str, text: &str) -> Result<String> {
+    pub fn name(&self) -> &'static str {
         match self {
-            AIProvider::OpenAI { api_key, model } => {
-                let config = async_openai::config::OpenAIConfig::new().with_api_key(api_key.clone());
-                let client = OpenAIClient::with_config(config);
-                let request = CreateChatCompletionRequestArgs::default()
-                    .model(model)
-                    .messages([
-                        ChatCompletionRequestMessage::System(
-                            ChatCompletionRequestSystemMessage {
-                                content: system_prompt.to_string(),
-                                name: None,
-                                role: Role::System,
-                            }
-                        ),
-                        ChatCompletionRequestMessage::User(
-                            ChatCompletionRequestUserMessage {
-                                content: ChatCompletionRequestUserMessageContent::Text(text.to_string()),
-                                name: None,
-                                role: Role::User,
-                            }
-                        ),
-                    ])
-                    .build()?;
-
-                let response = client.chat().create(request).await?;
-                Ok(response.choices[0].message.content.clone().unwrap_or_default())
-            }
-            AIProvider::Claude { api_key, model } => {
-                let client = AnthropicClient {
-                    api_key: api_key.clone(),
-                    ..Default::default()
-                };
-
-                let request = anthropic::types::MessagesRequest {
-                    model: model.to_string(),
-                    max_tokens: 1024,
-                    system: system_prompt.to_string(),
-                    messages: vec![anthropic::types::Message {
-                        role: anthropic::types::Role::User,
-                        content: vec![anthropic::types::ContentBlock::Text { text: text.to_string() }],
-                    }],
-                    ..Default::default()
-                };
-
-                let response = client.messages(request).await?;
-                if let Some(content) = response.content.first() {
-                    match content {
-                        anthropic::types::ContentBlock::Text { text } => Ok(text.clone()),
-                        _ => Err(AIError::RequestFailed("Unexpected response content type".to_string()).into()),
-                    }
-                } else {
-                    Err(AIError::RequestFailed("No response content received".to_string()).into())
-                }
-            }
-            AIProvider::Gemini { api_key, model } => {
-                // Map the provided model string onto the crate's `Model` enum. For unknown strings we use `Custom`, available under the `beta` feature.
-                let model_enum = match model.as_str() {
-                    "gemini-1.0-pro" => Model::Gemini1_0Pro,
-                    #[cfg(feature = "beta")]
-                    _ => Model::Custom(model.clone()),
-                    #[cfg(not(feature = "beta"))]
-                    _ => Model::Gemini1_0Pro,
-                };
+            AIProvider::OpenAI { .. } => "OpenAI",
+            AIProvider::Claude { .. } => "Claude",
+            AIProvider::Gemini { .. } => "Google Gemini",
+            AIProvider::OpenRouter { .. } => "OpenRouter",
+        }
+    }
 
-                let client = GeminiClient::new_from_model(model_enum, api_key.clone());
+    pub fn api_key(&self) -> &str {
+        match self {
+            AIProvider::OpenAI { api_key, .. }
+            | AIProvider::Claude { api_key, .. }
+            | AIProvider::Gemini { api_key, .. }
+            | AIProvider::OpenRouter { api_key, .. } => api_key,
+        }
+    }
 
-                // Build the request payload expected by the new SDK.
-                let request = GeminiRequest {
-                    contents: vec![Content {
-                        role: GeminiRole::User,
-                        parts: vec![Part {
-                            text: Some(format!("{}\n{}", system_prompt, text)),
-                            inline_data: None,
-                            file_data: None,
-                            video_metadata: None,
-                        }],
-                    }],
-                    tools: vec![],
-                    safety_settings: vec![SafetySettings {
-                        category: HarmCategory::HarmCategoryHarassment,
-                        threshold: HarmBlockThreshold::BlockNone,
-                    }],
-                    generation_config: Some(GenerationConfig {
-                        temperature: Some(0.7),
-                        top_p: Some(0.8),
-                        top_k: Some(40),
-                        candidate_count: Some(1),
-                        max_output_tokens: Some(1024),
-                        stop_sequences: None,
-                        response_mime_type: None,
-                        response_schema: None,
-                    }),
-                    system_instruction: None,
-                };
+    pub fn model(&self) -> &str {
+        match self {
+            AIProvider::OpenAI { model, .. }
+            | AIProvider::Claude { model, .. }
+            | AIProvider::Gemini { model, .. }
+            | AIProvider::OpenRouter { model, .. } => model,
+        }
+    }
 
-                // Execute the request (30-second timeout).
-                let post_result = client
-                    .post(30, &request)
-                    .await
-                    .map_err(|e| AIError::RequestFailed(e.to_string()))?;
+    /// Environment variable read when no API key is configured
+    pub fn api_key_variable(&self) -> &'static str {
+        match self {
+            AIProvider::OpenAI { .. } => "OPENAI_API_KEY",
+            AIProvider::Claude { .. } => "ANTHROPIC_API_KEY",
+            AIProvider::Gemini { .. } => "GEMINI_API_KEY",
+            AIProvider::OpenRouter { .. } => "OPENROUTER_API_KEY",
+        }
+    }
 
-                // Extract the plain (non-streamed) response text.
-                if let Some(rest) = post_result.rest() {
-                    if let Some(candidate) = rest.candidates.first() {
-                        if let Some(part) = candidate.content.parts.first() {
-                            if let Some(text) = &part.text {
-                                return Ok(text.clone());
-                            }
-                        }
-                    }
-                }
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            AIProvider::OpenAI { .. } => "https://api.openai.com/v1",
+            AIProvider::Claude { .. } => "https://api.anthropic.com",
+            AIProvider::Gemini { .. } => "https://generativelanguage.googleapis.com/v1beta",
+            AIProvider::OpenRouter { .. } => "https://openrouter.ai/api/v1",
+        }
+    }
 
-                Err(AIError::RequestFailed("No valid response received".to_string()).into())
+    /// The backend that talks to this provider, at `base_url` or the provider's own endpoint
+    pub fn backend(&self, base_url: Option<&str>) -> Box<dyn TranslationBackend> {
+        let base_url = base_url.unwrap_or(self.default_base_url()).to_string();
+        let (api_key, model) = (self.api_key().to_string(), self.model().to_string());
+        match self {
+            AIProvider::OpenAI { .. } | AIProvider::OpenRouter { .. } => {
+                Box::new(ChatCompletions::new(self.name(), api_key, base_url, model))
             }
+            AIProvider::Claude { .. } => Box::new(Anthropic::new(api_key, base_url, model)),
+            AIProvider::Gemini { .. } => Box::new(Gemini::new(api_key, base_url, model)),
         }
     }
 
+    pub async fn generate(&self, system_prompt: &str, text: &str) -> Result<String> {
+        let completion = Completion::new(text.to_string(), 1024, 0.7).with_system(system_prompt.to_string());
+        let backend = self.backend(None);
+        RetryPolicy::default()
+            .run(|| backend.complete(&completion))
+            .await
+            .map_err(|e| AIError::from(e).into())
+    }
+
     pub fn available_models(&self) -> Vec<String> {
         match self {
             AIProvider::OpenAI { .. } => vec![
@@ -198,6 +156,11 @@ impl AIProvider {
                 // 1.0
                 "gemini-1.0-pro".to_string(),
             ],
+            AIProvider::OpenRouter { .. } => vec![
+                "anthropic/claude-3.5-sonnet".to_string(),
+                "openai/gpt-4o".to_string(),
+                "google/gemini-2.5-flash".to_string(),
+            ],
         }
     }
 
@@ -241,6 +204,20 @@ impl AIProvider {
                 api_key: api_key.clone(),
                 model,
             },
+            AIProvider::OpenRouter { api_key, .. } => AIProvider::OpenRouter {
+                api_key: api_key.clone(),
+                model,
+            },
+        }
+    }
+
+    /// The same provider and model with another API key
+    pub fn with_api_key(&self, api_key: String) -> Self {
+        match self {
+            AIProvider::OpenAI { model, .. } => AIProvider::OpenAI { api_key, model: model.clone() },
+            AIProvider::Claude { model, .. } => AIProvider::Claude { api_key, model: model.clone() },
+            AIProvider::Gemini { model, .. } => AIProvider::Gemini { api_key, model: model.clone() },
+            AIProvider::OpenRouter { model, .. } => AIProvider::OpenRouter { api_key, model: model.clone() },
         }
     }
 }
@@ -0,0 +1,611 @@
+use crate::error::{Result, TranslatorError};
+use crate::format_specifiers::{FormatArguments, LENGTH_MODIFIERS};
+use crate::plural_rules::PLURAL_CATEGORIES;
+use crate::xcode_json::compare_keys;
+use crate::xcstrings::{TranslationValue, UnitNode, UnitState, XCStringsFile};
+use crate::xml::attribute;
+use quick_xml::escape::unescape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const STRINGS_FILE: &str = "strings.xml";
+
+/// Value of a `<string>` or `<plurals>` resource
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AndroidValue {
+    Text(String),
+    /// Items keyed by `quantity`, which uses the CLDR category names
+    Plural(BTreeMap<String, String>),
+}
+
+/// One resource of a `strings.xml` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AndroidString {
+    pub name: String,
+    pub value: AndroidValue,
+    pub comment: Option<String>,
+    pub translatable: bool,
+}
+
+/// Files written by `export`, and keys Android has no equivalent for
+#[derive(Debug, Default)]
+pub struct AndroidExport {
+    pub files: Vec<PathBuf>,
+    pub skipped: Vec<String>,
+}
+
+/// Android-only keys added by `import`, and the languages they came with
+#[derive(Debug, Default)]
+pub struct AndroidImport {
+    pub keys: Vec<String>,
+    pub languages: Vec<String>,
+}
+
+/// Resource name for every key: lowercase letters, digits and underscores, unique across the catalog
+pub fn resource_names(keys: &[String]) -> HashMap<String, String> {
+    let mut sorted: Vec<&String> = keys.iter().collect();
+    sorted.sort_by(|a, b| compare_keys(a, b));
+
+    let mut used = HashSet::new();
+    let mut names = HashMap::new();
+    for key in sorted {
+        let mut base = String::new();
+        for c in key.chars() {
+            if c.is_ascii_alphanumeric() {
+                base.push(c.to_ascii_lowercase());
+            } else if !base.ends_with('_') {
+                base.push('_');
+            }
+        }
+        let mut base = base.trim_matches('_').to_string();
+        if base.is_empty() || base.starts_with(|c: char| c.is_ascii_digit()) {
+            base = format!("string_{}", base);
+        }
+
+        let mut name = base.clone();
+        let mut counter = 2;
+        while !used.insert(name.clone()) {
+            name = format!("{}_{}", base, counter);
+            counter += 1;
+        }
+        names.insert(key.clone(), name);
+    }
+    names
+}
+
+/// Convert Apple format specifiers to Java ones: `%@` becomes `%s`, `%1$@` becomes `%1$s`,
+/// `%u` and `%i` become `%d`, and the length modifiers Java does not know are dropped. aapt2
+/// rejects several non-positional specifiers in one string, so those are numbered: `%@ has
+/// %lld` becomes `%1$s has %2$d`.
+pub fn to_android_format(text: &str) -> String {
+    convert_specifiers(text, true, |conversion| {
+        let conversion = conversion.trim_start_matches(LENGTH_MODIFIERS);
+        match conversion {
+            "@" | "S" => "s",
+            "i" | "u" | "D" | "U" => "d",
+            "O" => "o",
+            "C" => "c",
+            other => other,
+        }
+        .to_string()
+    })
+}
+
+/// Convert Java format specifiers back to Apple ones: `%s` becomes `%@`
+pub fn from_android_format(text: &str) -> String {
+    convert_specifiers(text, false, |conversion| match conversion {
+        "s" | "S" => "@".to_string(),
+        other => other.to_string(),
+    })
+}
+
+/// Rewrite the length modifier and conversion of every specifier, such as the `lld` of
+/// `%5lld`. With `number`, a string with several specifiers gets an `n$` on each.
+fn convert_specifiers(text: &str, number: bool, convert: impl Fn(&str) -> String) -> String {
+    let arguments = FormatArguments::parse(text);
+    let number = number && arguments.specifiers.len() > 1;
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    // `*` widths have no text of their own
+    for specifier in arguments.specifiers.iter().filter(|s| !s.range.is_empty()) {
+        out.push_str(&text[last..specifier.range.start]);
+        out.push('%');
+        if specifier.has_position() || number {
+            out.push_str(&format!("{}$", specifier.position));
+        }
+        let options = specifier.options();
+        out.push_str(options);
+        out.push_str(&convert(&specifier.format()[options.len()..]));
+        last = specifier.range.end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Resource directory for a language: `values` for the source, `values-ja`, `values-pt-rBR`,
+/// or the BCP 47 form `values-b+zh+Hans` for scripts
+pub fn values_dir(language: &str, source_language: &str) -> String {
+    if language == source_language {
+        return "values".to_string();
+    }
+    let parts: Vec<&str> = language.split(['-', '_']).collect();
+    match parts.as_slice() {
+        [language] => format!("values-{}", language),
+        [language, region] if region.len() == 2 && region.chars().all(|c| c.is_ascii_alphabetic()) => {
+            format!("values-{}-r{}", language, region.to_ascii_uppercase())
+        }
+        _ => format!("values-b+{}", parts.join("+")),
+    }
+}
+
+/// Language of a `values-*` directory, or None for other qualifiers such as `values-night`
+fn language_for_values_dir(name: &str) -> Option<String> {
+    let qualifier = name.strip_prefix("values-")?;
+    if let Some(bcp47) = qualifier.strip_prefix("b+") {
+        return Some(bcp47.replace('+', "-"));
+    }
+
+    let mut parts = qualifier.split('-');
+    let language = parts.next()?;
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    match parts.next() {
+        None => Some(language.to_string()),
+        Some(region) if region.len() == 3 && region.starts_with('r') => {
+            Some(format!("{}-{}", language, &region[1..]))
+        }
+        Some(_) => None,
+    }
+}
+
+/// Escape text for a string resource: XML entities, quotes, backslashes and leading `@`/`?`.
+/// Text whose spacing Android would collapse is wrapped in double quotes.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\'' => out.push_str("\\'"),
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '@' | '?' if i == 0 => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+
+    let collapses = text.starts_with(' ') || text.ends_with(' ') || text.contains("  ");
+    if collapses {
+        format!("\"{}\"", out)
+    } else {
+        out
+    }
+}
+
+/// Undo `escape` on XML-unescaped resource text, collapsing whitespace outside double quotes
+fn unescape_resource(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.trim().chars().peekable();
+    let mut quoted = false;
+    let mut last_space = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                last_space = false;
+                match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| chars.next()).collect();
+                        if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                            out.push(c);
+                        }
+                    }
+                    Some(c) => out.push(c),
+                    None => {}
+                }
+            }
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !last_space {
+                    out.push(' ');
+                    last_space = true;
+                }
+            }
+            c => {
+                out.push(c);
+                last_space = false;
+            }
+        }
+    }
+    out
+}
+
+fn write_comment(out: &mut String, comment: &Option<String>) {
+    if let Some(comment) = comment {
+        out.push_str(&format!("    <!-- {} -->\n", comment.replace("--", "- -")));
+    }
+}
+
+/// Write a `strings.xml` file in Android Studio's layout
+pub fn to_xml(strings: &[AndroidString]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<resources>\n");
+    for string in strings {
+        write_comment(&mut out, &string.comment);
+        let translatable = if string.translatable { "" } else { " translatable=\"false\"" };
+        match &string.value {
+            AndroidValue::Text(text) => out.push_str(&format!(
+                "    <string name=\"{}\"{}>{}</string>\n",
+                string.name,
+                translatable,
+                escape(text)
+            )),
+            AndroidValue::Plural(items) => {
+                out.push_str(&format!("    <plurals name=\"{}\"{}>\n", string.name, translatable));
+                for category in PLURAL_CATEGORIES {
+                    if let Some(item) = items.get(category) {
+                        out.push_str(&format!(
+                            "        <item quantity=\"{}\">{}</item>\n",
+                            category,
+                            escape(item)
+                        ));
+                    }
+                }
+                out.push_str("    </plurals>\n");
+            }
+        }
+    }
+    out.push_str("</resources>\n");
+    out
+}
+
+fn xml_error(e: impl std::fmt::Display) -> TranslatorError {
+    TranslatorError::FileFormatError(format!("Invalid strings.xml: {}", e))
+}
+
+fn read_resource_text(reader: &mut Reader<&[u8]>, element: &BytesStart) -> Result<String> {
+    let end = element.to_end().into_owned();
+    let raw = reader.read_text(end.name()).map_err(xml_error)?;
+    let text = unescape(&raw.xml10_content()).map_err(xml_error)?.into_owned();
+    Ok(unescape_resource(&text))
+}
+
+/// Parse the `<string>` and `<plurals>` resources of a `strings.xml` file
+pub fn parse(content: &str) -> Result<Vec<AndroidString>> {
+    let mut reader = Reader::from_str(content);
+    let mut strings = Vec::new();
+    let mut comment: Option<String> = None;
+    let mut plural: Option<AndroidString> = None;
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Comment(text) => {
+                comment = Some(text.xml10_content().trim().to_string()).filter(|c| !c.is_empty());
+            }
+            Event::Start(element) => match element.local_name().as_ref() {
+                "string" => {
+                    let name = attribute(&element, "name")?.unwrap_or_default();
+                    let translatable = attribute(&element, "translatable")?.as_deref() != Some("false");
+                    let text = read_resource_text(&mut reader, &element)?;
+                    strings.push(AndroidString {
+                        name,
+                        value: AndroidValue::Text(text),
+                        comment: comment.take(),
+                        translatable,
+                    });
+                }
+                "plurals" => {
+                    plural = Some(AndroidString {
+                        name: attribute(&element, "name")?.unwrap_or_default(),
+                        value: AndroidValue::Plural(BTreeMap::new()),
+                        comment: comment.take(),
+                        translatable: attribute(&element, "translatable")?.as_deref() != Some("false"),
+                    });
+                }
+                "item" => {
+                    let quantity = attribute(&element, "quantity")?;
+                    let text = read_resource_text(&mut reader, &element)?;
+                    if let (Some(quantity), Some(AndroidString { value: AndroidValue::Plural(items), .. })) =
+                        (quantity, plural.as_mut())
+                    {
+                        items.insert(quantity, text);
+                    }
+                }
+                "resources" => {}
+                // string-array and other resources have no catalog equivalent
+                _ => {
+                    let end = element.to_end().into_owned();
+                    reader.read_to_end(end.name()).map_err(xml_error)?;
+                    comment = None;
+                }
+            },
+            Event::Empty(element) if element.local_name().as_ref() == "string" => {
+                strings.push(AndroidString {
+                    name: attribute(&element, "name")?.unwrap_or_default(),
+                    value: AndroidValue::Text(String::new()),
+                    comment: comment.take(),
+                    translatable: attribute(&element, "translatable")?.as_deref() != Some("false"),
+                });
+            }
+            Event::End(element) if element.local_name().as_ref() == "plurals" => {
+                strings.extend(plural.take());
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(strings)
+}
+
+/// Android value of a catalog value. Device variations collapse to one device;
+/// substitutions have no Android equivalent.
+fn android_value(value: TranslationValue) -> Option<AndroidValue> {
+    match value.collapse_devices() {
+        TranslationValue::Text(text) => Some(AndroidValue::Text(to_android_format(&text))),
+        TranslationValue::Plural(forms) => Some(AndroidValue::Plural(
+            forms
+                .into_iter()
+                .map(|(category, form)| (category, to_android_format(&form)))
+                .collect(),
+        )),
+        TranslationValue::Device(_) | TranslationValue::Substitutions { .. } => None,
+    }
+}
+
+/// Write `res/values*/strings.xml` for the source language and each of `languages`
+pub fn export(file: &XCStringsFile, languages: &[String], res_dir: &Path) -> Result<AndroidExport> {
+    let data = &file.data;
+    let keys: Vec<String> = data
+        .strings
+        .iter()
+        .filter(|(key, entry)| !key.trim().is_empty() && !entry.is_stale())
+        .map(|(key, _)| key.clone())
+        .collect();
+    let names = resource_names(&keys);
+    let translatable: HashSet<String> = file.get_translatable_keys().into_iter().collect();
+
+    let mut sorted = keys;
+    sorted.sort_by(|a, b| compare_keys(&names[a], &names[b]));
+
+    let mut summary = AndroidExport::default();
+    let mut all_languages = vec![data.source_language.clone()];
+    all_languages.extend(languages.iter().filter(|l| **l != data.source_language).cloned());
+
+    for language in &all_languages {
+        let is_source = *language == data.source_language;
+        let mut strings = Vec::new();
+
+        for key in &sorted {
+            let entry = &data.strings[key];
+            let is_translatable = translatable.contains(key);
+            // Strings that must not be translated only live in the default resources
+            if !is_source && !is_translatable {
+                continue;
+            }
+
+            let value = match entry.localizations.get(language).and_then(|loc| loc.value()) {
+                Some(value) => value,
+                None if is_source => TranslationValue::Text(key.clone()),
+                None => continue,
+            };
+            match android_value(value) {
+                Some(value) => strings.push(AndroidString {
+                    name: names[key].clone(),
+                    value,
+                    comment: entry.comment.clone().filter(|_| is_source),
+                    translatable: is_translatable,
+                }),
+                None if is_source => summary.skipped.push(key.clone()),
+                None => {}
+            }
+        }
+
+        if strings.is_empty() {
+            continue;
+        }
+        let dir = res_dir.join(values_dir(language, &data.source_language));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(STRINGS_FILE);
+        fs::write(&path, to_xml(&strings))?;
+        summary.files.push(path);
+    }
+
+    Ok(summary)
+}
+
+/// Add the strings of an Android `res` directory that the catalog does not have yet,
+/// with their translations from every `values-*` directory
+pub fn import(file: &mut XCStringsFile, res_dir: &Path, state: UnitState) -> Result<AndroidImport> {
+    let source_path = res_dir.join("values").join(STRINGS_FILE);
+    let source = parse(&fs::read_to_string(&source_path).map_err(|e| {
+        TranslatorError::FileFormatError(format!("{}: {}", source_path.display(), e))
+    })?)?;
+
+    // Names the catalog's own keys export to are not Android-only
+    let existing: HashSet<String> = resource_names(&file.get_keys()).into_values().collect();
+    let source_language = file.data.source_language.clone();
+    let mut summary = AndroidImport::default();
+
+    for string in &source {
+        if existing.contains(&string.name) || !file.add_manual_key(&string.name, string.comment.clone()) {
+            continue;
+        }
+        if !string.translatable {
+            file.mark_as_no_translate(&string.name)?;
+        }
+        file.add_translation_value(
+            &string.name,
+            &source_language,
+            &catalog_value(&string.value),
+            UnitState::Translated,
+        )?;
+        summary.keys.push(string.name.clone());
+    }
+
+    let added: HashSet<&String> = summary.keys.iter().collect();
+    let mut dirs: Vec<PathBuf> = fs::read_dir(res_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    dirs.sort();
+
+    for dir in dirs {
+        let Some(language) = dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(language_for_values_dir)
+        else {
+            continue;
+        };
+        let path = dir.join(STRINGS_FILE);
+        if !path.is_file() || language == source_language {
+            continue;
+        }
+
+        let mut found = false;
+        for string in parse(&fs::read_to_string(&path)?)? {
+            if added.contains(&string.name) {
+                file.add_translation_value(&string.name, &language, &catalog_value(&string.value), state)?;
+                found = true;
+            }
+        }
+        if found {
+            summary.languages.push(language);
+        }
+    }
+
+    Ok(summary)
+}
+
+fn catalog_value(value: &AndroidValue) -> TranslationValue {
+    match value {
+        AndroidValue::Text(text) => TranslationValue::Text(from_android_format(text)),
+        AndroidValue::Plural(items) => TranslationValue::Plural(
+            items
+                .iter()
+                .map(|(category, item)| (category.clone(), from_android_format(item)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_format_conversion() {
+        assert_eq!(to_android_format("%@ has %lld new %1$@ (100%%)"), "%1$s has %2$d new %1$s (100%%)");
+        assert_eq!(to_android_format("%@ has %lld"), "%1$s has %2$d");
+        assert_eq!(to_android_format("%2$@ sent %1$lld"), "%2$s sent %1$d");
+        assert_eq!(to_android_format("%lld items (100%%)"), "%d items (100%%)");
+        assert_eq!(to_android_format("%u left"), "%d left");
+        assert_eq!(to_android_format("%lu left"), "%d left");
+        assert_eq!(to_android_format("%zu of %qd, %hhx"), "%1$d of %2$d, %3$x");
+        assert_eq!(to_android_format("%-5.2Lf %i"), "%1$-5.2f %2$d");
+        assert_eq!(from_android_format("%1$s sent %2$d"), "%1$@ sent %2$d");
+        assert_eq!(values_dir("zh-Hans", "en"), "values-b+zh+Hans");
+        assert_eq!(values_dir("pt-BR", "en"), "values-pt-rBR");
+        assert_eq!(language_for_values_dir("values-pt-rBR").as_deref(), Some("pt-BR"));
+        assert_eq!(language_for_values_dir("values-night"), None);
+    }
+
+    #[test]
+    fn test_strings_xml_round_trip() {
+        let strings = vec![
+            AndroidString {
+                name: "greeting".to_string(),
+                value: AndroidValue::Text("Don't say \"hi\" & <leave>\n@home".to_string()),
+                comment: Some("Shown -- once".to_string()),
+                translatable: true,
+            },
+            AndroidString {
+                name: "items".to_string(),
+                value: AndroidValue::Plural(
+                    [("one", "%d item"), ("other", "%d items")]
+                        .iter()
+                        .map(|(c, v)| (c.to_string(), v.to_string()))
+                        .collect(),
+                ),
+                comment: None,
+                translatable: true,
+            },
+            AndroidString {
+                name: "brand".to_string(),
+                value: AndroidValue::Text("  Rosetta ".to_string()),
+                comment: None,
+                translatable: false,
+            },
+        ];
+
+        let xml = to_xml(&strings);
+        assert!(xml.contains(r#"<string name="greeting">Don\'t say \"hi\" &amp; &lt;leave&gt;\n@home</string>"#));
+        assert!(xml.contains(r#"<item quantity="one">%d item</item>"#));
+
+        let mut expected = strings.clone();
+        expected[0].comment = Some("Shown - - once".to_string());
+        assert_eq!(parse(&xml).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("Localizable.xcstrings");
+        fs::write(
+            &path,
+            r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "Hello, %@!" : {
+      "localizations" : {
+        "ja" : { "stringUnit" : { "state" : "translated", "value" : "こんにちは、%@！" } }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#,
+        )
+        .unwrap();
+        let mut file = XCStringsFile::load(&path).unwrap();
+        let res = dir.path().join("res");
+
+        let summary = export(&file, &["ja".to_string()], &res).unwrap();
+        assert_eq!(summary.files.len(), 2);
+        let ja = fs::read_to_string(res.join("values-ja/strings.xml")).unwrap();
+        assert!(ja.contains(r#"<string name="hello">こんにちは、%s！</string>"#));
+
+        // An Android-only string appears next to the exported one
+        fs::write(
+            res.join("values/strings.xml"),
+            fs::read_to_string(res.join("values/strings.xml"))
+                .unwrap()
+                .replace("</resources>", "    <string name=\"widget_label\">Open %1$s</string>\n</resources>"),
+        )
+        .unwrap();
+        fs::write(
+            res.join("values-ja/strings.xml"),
+            ja.replace("</resources>", "    <string name=\"widget_label\">%1$sを開く</string>\n</resources>"),
+        )
+        .unwrap();
+
+        let summary = import(&mut file, &res, UnitState::Translated).unwrap();
+        assert_eq!(summary.keys, vec!["widget_label".to_string()]);
+        assert_eq!(summary.languages, vec!["ja".to_string()]);
+        let entry = &file.data.strings["widget_label"];
+        assert_eq!(entry.extraction_state.as_deref(), Some("manual"));
+        assert_eq!(entry.localizations["en"].string_unit.as_ref().unwrap().value, "Open %1$@");
+        assert_eq!(entry.localizations["ja"].string_unit.as_ref().unwrap().value, "%1$@を開く");
+    }
+}
@@ -0,0 +1,479 @@
+use crate::error::{Result, TranslatorError};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// One prompt sent to a model
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub system: Option<String>,
+    pub prompt: String,
+    pub max_tokens: u32,
+    pub temperature: f32,
+    /// Ask for a JSON object mapping each of these keys to a string, through the provider's
+    /// JSON mode or response schema where it has one
+    pub json_keys: Option<Vec<String>>,
+}
+
+impl Completion {
+    pub fn new(prompt: String, max_tokens: u32, temperature: f32) -> Self {
+        Self {
+            system: None,
+            prompt,
+            max_tokens,
+            temperature,
+            json_keys: None,
+        }
+    }
+
+    pub fn with_system(mut self, system: String) -> Self {
+        self.system = Some(system);
+        self
+    }
+
+    pub fn with_json_keys(mut self, keys: Vec<String>) -> Self {
+        self.json_keys = Some(keys);
+        self
+    }
+}
+
+/// A model API that turns a prompt into text. `Translator` builds the prompts; backends
+/// only know how to reach their provider.
+#[async_trait]
+pub trait TranslationBackend: Send + Sync {
+    /// Provider name shown to the user
+    fn name(&self) -> &str;
+
+    fn model(&self) -> &str;
+
+    async fn complete(&self, completion: &Completion) -> Result<String>;
+}
+
+fn http_client() -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .expect("Failed to create HTTP client")
+}
+
+/// Send a request and decode its JSON body, turning error statuses into typed errors
+async fn send(request: reqwest::RequestBuilder) -> Result<Value> {
+    let response = request.send().await.map_err(network_error)?;
+    if !response.status().is_success() {
+        let status = response.status().as_u16();
+        let retry_after = retry_after(response.headers());
+        let message = response.text().await.unwrap_or_default();
+        return Err(api_error(status, retry_after.or_else(|| body_retry_delay(&message)), message));
+    }
+    response.json().await.map_err(network_error)
+}
+
+fn network_error(error: reqwest::Error) -> TranslatorError {
+    if error.is_timeout() || error.is_connect() {
+        TranslatorError::Transient {
+            retry_after: None,
+            message: error.to_string(),
+        }
+    } else {
+        TranslatorError::HttpError(error)
+    }
+}
+
+fn api_error(status: u16, retry_after: Option<Duration>, message: String) -> TranslatorError {
+    let lowercase = message.to_lowercase();
+    // Running out of credits is final; a 429 that names a wait is only a rate limit
+    let out_of_credit = lowercase.contains("insufficient_quota")
+        || (retry_after.is_none() && ["quota", "billing", "credit"].iter().any(|w| lowercase.contains(w)));
+
+    match status {
+        401 | 403 => TranslatorError::Unauthorized { status, message },
+        402 => TranslatorError::QuotaExceeded { message },
+        429 if out_of_credit => TranslatorError::QuotaExceeded { message },
+        429 => TranslatorError::RateLimited { retry_after, message },
+        // 529 is Anthropic's "overloaded"
+        408 | 500 | 502 | 503 | 504 | 529 => TranslatorError::Transient {
+            retry_after,
+            message: format!("status {}: {}", status, message),
+        },
+        _ => TranslatorError::ApiError { status, message },
+    }
+}
+
+/// How long to wait according to the response headers: the standard `Retry-After` (seconds or an
+/// HTTP date), or the reset times of OpenAI, Anthropic and OpenRouter rate-limit headers
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+    let until = |time: DateTime<Utc>| (time - Utc::now()).to_std().unwrap_or_default();
+
+    if let Some(value) = header("retry-after-ms") {
+        if let Ok(ms) = value.parse::<f64>() {
+            return Some(Duration::from_secs_f64(ms.max(0.0) / 1000.0));
+        }
+    }
+    if let Some(value) = header("retry-after") {
+        if let Ok(seconds) = value.parse::<f64>() {
+            return Some(Duration::from_secs_f64(seconds.max(0.0)));
+        }
+        if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+            return Some(until(date.with_timezone(&Utc)));
+        }
+    }
+
+    // OpenAI: time until the request and token windows reset, such as "1s" or "6m0s"
+    let openai = ["x-ratelimit-reset-requests", "x-ratelimit-reset-tokens"]
+        .iter()
+        .filter_map(|name| header(name).and_then(parse_duration))
+        .max();
+    if openai.is_some() {
+        return openai;
+    }
+
+    // Anthropic: RFC 3339 time the limits reset
+    let anthropic = ["anthropic-ratelimit-requests-reset", "anthropic-ratelimit-tokens-reset"]
+        .iter()
+        .filter_map(|name| header(name).and_then(|v| DateTime::parse_from_rfc3339(v).ok()))
+        .map(|time| until(time.with_timezone(&Utc)))
+        .max();
+    if anthropic.is_some() {
+        return anthropic;
+    }
+
+    // OpenRouter: Unix time in milliseconds
+    let reset = header("x-ratelimit-reset")?.parse::<i64>().ok()?;
+    DateTime::from_timestamp_millis(reset).map(until)
+}
+
+/// Gemini puts the wait in the error body, as `RetryInfo.retryDelay`
+fn body_retry_delay(body: &str) -> Option<Duration> {
+    let body: Value = serde_json::from_str(body).ok()?;
+    body["error"]["details"]
+        .as_array()?
+        .iter()
+        .find_map(|detail| detail["retryDelay"].as_str().and_then(parse_duration))
+}
+
+/// Parse durations such as `20ms`, `1.5s`, `13s` or `6m0s`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let split = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let number: f64 = rest[..split].parse().ok()?;
+        let unit_end = rest[split..]
+            .find(|c: char| c.is_ascii_digit())
+            .map_or(rest.len(), |i| split + i);
+        total += match &rest[split..unit_end] {
+            "ms" => number / 1000.0,
+            "s" => number,
+            "m" => number * 60.0,
+            "h" => number * 3600.0,
+            _ => return None,
+        };
+        rest = &rest[unit_end..];
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+fn missing_text() -> TranslatorError {
+    TranslatorError::TranslationFailed("No text in response".to_string())
+}
+
+/// OpenAI's chat completions API, also spoken by OpenRouter and most proxies
+pub struct ChatCompletions {
+    name: String,
+    client: Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+}
+
+impl ChatCompletions {
+    pub fn new(name: &str, api_key: String, base_url: String, model: String) -> Self {
+        Self {
+            name: name.to_string(),
+            client: http_client(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model,
+        }
+    }
+
+    fn body(&self, completion: &Completion) -> Value {
+        let mut messages = Vec::new();
+        if let Some(system) = &completion.system {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.push(json!({ "role": "user", "content": completion.prompt }));
+        let mut body = json!({
+            "model": self.model,
+            "messages": messages,
+            "max_tokens": completion.max_tokens,
+            "temperature": completion.temperature,
+        });
+        // JSON mode only guarantees valid JSON; the prompt names the keys
+        if completion.json_keys.is_some() {
+            body["response_format"] = json!({ "type": "json_object" });
+        }
+        body
+    }
+
+    fn text(response: &Value) -> Result<String> {
+        response["choices"]
+            .get(0)
+            .ok_or_else(|| TranslatorError::TranslationFailed("No choices in response".to_string()))?["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(missing_text)
+    }
+}
+
+#[async_trait]
+impl TranslationBackend for ChatCompletions {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, completion: &Completion) -> Result<String> {
+        let request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&self.body(completion));
+        Self::text(&send(request).await?)
+    }
+}
+
+/// Anthropic's Messages API
+pub struct Anthropic {
+    client: Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+}
+
+impl Anthropic {
+    pub fn new(api_key: String, base_url: String, model: String) -> Self {
+        Self {
+            client: http_client(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model,
+        }
+    }
+
+    /// Anthropic has no JSON mode, so keyed requests rely on the prompt alone
+    fn body(&self, completion: &Completion) -> Value {
+        let mut body = json!({
+            "model": self.model,
+            "max_tokens": completion.max_tokens,
+            "temperature": completion.temperature,
+            "messages": [{ "role": "user", "content": completion.prompt }],
+        });
+        if let Some(system) = &completion.system {
+            body["system"] = json!(system);
+        }
+        body
+    }
+
+    fn text(response: &Value) -> Result<String> {
+        let text: String = response["content"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect();
+        Some(text).filter(|t| !t.is_empty()).ok_or_else(missing_text)
+    }
+}
+
+#[async_trait]
+impl TranslationBackend for Anthropic {
+    fn name(&self) -> &str {
+        "Claude"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, completion: &Completion) -> Result<String> {
+        let request = self
+            .client
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&self.body(completion));
+        Self::text(&send(request).await?)
+    }
+}
+
+/// Google's Gemini `generateContent` API
+pub struct Gemini {
+    client: Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+}
+
+impl Gemini {
+    pub fn new(api_key: String, base_url: String, model: String) -> Self {
+        Self {
+            client: http_client(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model,
+        }
+    }
+
+    fn body(&self, completion: &Completion) -> Value {
+        let mut body = json!({
+            "contents": [{ "role": "user", "parts": [{ "text": completion.prompt }] }],
+            "generationConfig": {
+                "maxOutputTokens": completion.max_tokens,
+                "temperature": completion.temperature,
+            },
+        });
+        if let Some(system) = &completion.system {
+            body["systemInstruction"] = json!({ "parts": [{ "text": system }] });
+        }
+        if let Some(keys) = &completion.json_keys {
+            let properties: serde_json::Map<String, Value> =
+                keys.iter().map(|key| (key.clone(), json!({ "type": "STRING" }))).collect();
+            body["generationConfig"]["responseMimeType"] = json!("application/json");
+            body["generationConfig"]["responseSchema"] = json!({
+                "type": "OBJECT",
+                "properties": properties,
+                "required": keys,
+            });
+        }
+        body
+    }
+
+    fn text(response: &Value) -> Result<String> {
+        let candidate = response["candidates"].get(0).ok_or_else(|| {
+            // Prompts blocked by safety settings come back without candidates
+            let reason = response["promptFeedback"]["blockReason"].as_str().unwrap_or("no candidates");
+            TranslatorError::TranslationFailed(format!("Gemini returned no translation: {}", reason))
+        })?;
+        let text: String = candidate["content"]["parts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|part| part["text"].as_str())
+            .collect();
+        Some(text).filter(|t| !t.is_empty()).ok_or_else(missing_text)
+    }
+}
+
+#[async_trait]
+impl TranslationBackend for Gemini {
+    fn name(&self) -> &str {
+        "Google Gemini"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, completion: &Completion) -> Result<String> {
+        let request = self
+            .client
+            .post(format!("{}/models/{}:generateContent", self.base_url, self.model))
+            .header("x-goog-api-key", &self.api_key)
+            .json(&self.body(completion));
+        Self::text(&send(request).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completion() -> Completion {
+        Completion::new("Translate".to_string(), 100, 0.2).with_system("Be brief".to_string())
+    }
+
+    #[test]
+    fn test_request_bodies() {
+        let openai = ChatCompletions::new("OpenAI", "k".into(), "https://api.openai.com/v1/".into(), "gpt-4o".into());
+        assert_eq!(openai.base_url, "https://api.openai.com/v1");
+        let body = openai.body(&completion());
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "Translate");
+
+        let anthropic = Anthropic::new("k".into(), "https://api.anthropic.com".into(), "claude".into());
+        let body = anthropic.body(&completion());
+        assert_eq!(body["system"], "Be brief");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+
+        let gemini = Gemini::new("k".into(), "https://example.com".into(), "gemini-2.5-flash".into());
+        let body = gemini.body(&completion());
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "Be brief");
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 100);
+
+        let keyed = completion().with_json_keys(vec!["greeting".to_string(), "farewell".to_string()]);
+        assert_eq!(openai.body(&keyed)["response_format"]["type"], "json_object");
+        assert!(openai.body(&completion()).get("response_format").is_none());
+        let config = &gemini.body(&keyed)["generationConfig"];
+        assert_eq!(config["responseMimeType"], "application/json");
+        assert_eq!(config["responseSchema"]["properties"]["farewell"]["type"], "STRING");
+        assert_eq!(config["responseSchema"]["required"][0], "greeting");
+    }
+
+    #[test]
+    fn test_error_classification() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", "12".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(12)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-reset-requests", "1s".parse().unwrap());
+        headers.insert("x-ratelimit-reset-tokens", "6m0s".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(360)));
+        assert_eq!(parse_duration("20ms"), Some(Duration::from_millis(20)));
+        assert_eq!(parse_duration("soon"), None);
+
+        let gemini = r#"{"error":{"code":429,"status":"RESOURCE_EXHAUSTED","message":"Quota exceeded for metric","details":[{"@type":"type.googleapis.com/google.rpc.RetryInfo","retryDelay":"13s"}]}}"#;
+        let delay = body_retry_delay(gemini);
+        assert_eq!(delay, Some(Duration::from_secs(13)));
+        assert!(matches!(
+            api_error(429, delay, gemini.to_string()),
+            TranslatorError::RateLimited { retry_after: Some(_), .. }
+        ));
+
+        let openai = r#"{"error":{"type":"insufficient_quota","message":"You exceeded your current quota"}}"#;
+        assert!(matches!(
+            api_error(429, Some(Duration::from_secs(1)), openai.to_string()),
+            TranslatorError::QuotaExceeded { .. }
+        ));
+        assert!(matches!(api_error(401, None, String::new()), TranslatorError::Unauthorized { .. }));
+        assert!(api_error(529, None, "overloaded".to_string()).is_retryable());
+        assert!(!api_error(400, None, "bad request".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn test_response_text() {
+        let response = json!({ "choices": [{ "message": { "role": "assistant", "content": "こんにちは" } }] });
+        assert_eq!(ChatCompletions::text(&response).unwrap(), "こんにちは");
+        assert!(ChatCompletions::text(&json!({ "choices": [] })).is_err());
+
+        let response = json!({ "content": [{ "type": "text", "text": "Bonjour" }] });
+        assert_eq!(Anthropic::text(&response).unwrap(), "Bonjour");
+
+        let response = json!({ "candidates": [{ "content": { "parts": [{ "text": "Hallo" }] } }] });
+        assert_eq!(Gemini::text(&response).unwrap(), "Hallo");
+        let blocked = json!({ "promptFeedback": { "blockReason": "SAFETY" } });
+        assert!(Gemini::text(&blocked).unwrap_err().to_string().contains("SAFETY"));
+    }
+}
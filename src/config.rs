@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::{fs, path::PathBuf};
 
 use crate::ai_provider::AIProvider;
+use crate::prompts::{AppProfile, PromptTemplates};
+use crate::xcstrings::UnitState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -15,6 +17,21 @@ pub struct Config {
     pub ai_provider: AIProvider,
     #[serde(default = "default_batch_size")]
     pub batch_size: usize,
+    /// Requests sent to the provider at once during batch translation
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// Plain strings packed into one request during batch translation
+    #[serde(default = "default_keys_per_request")]
+    pub keys_per_request: usize,
+    /// State written on AI translations so reviewers can find them in Xcode
+    #[serde(default = "default_ai_translation_state")]
+    pub ai_translation_state: UnitState,
+    /// The product being translated, described in every prompt
+    #[serde(default, skip_serializing_if = "AppProfile::is_empty")]
+    pub app_profile: AppProfile,
+    /// Replacements for the built-in prompts
+    #[serde(default, skip_serializing_if = "PromptTemplates::is_empty")]
+    pub prompt_templates: PromptTemplates,
 }
 
 impl Default for Config {
@@ -30,6 +47,11 @@ impl Default for Config {
                 model: String::from("gpt-3.5-turbo"),
             },
             batch_size: 30,
+            concurrency: default_concurrency(),
+            keys_per_request: default_keys_per_request(),
+            ai_translation_state: default_ai_translation_state(),
+            app_profile: AppProfile::default(),
+            prompt_templates: PromptTemplates::default(),
         }
     }
 }
@@ -66,28 +88,26 @@ impl Config {
 
     pub fn update_ai_provider(&mut self, provider: AIProvider) -> Result<()> {
         // Keep top-level api_key & model in sync for backwards-compat displays.
-        match &provider {
-            AIProvider::OpenAI { api_key, model } => {
-                self.api_key = api_key.clone();
-                self.model = model.clone();
-                self.base_url = "https://api.openai.com/v1".to_string();
-            }
-            AIProvider::Claude { api_key, model } => {
-                self.api_key = api_key.clone();
-                self.model = model.clone();
-                self.base_url = "https://api.anthropic.com".to_string();
-            }
-            AIProvider::Gemini { api_key, model } => {
-                self.api_key = api_key.clone();
-                self.model = model.clone();
-                self.base_url = "https://generativelanguage.googleapis.com/v1beta".to_string();
-            }
-        }
+        self.api_key = provider.api_key().to_string();
+        self.model = provider.model().to_string();
+        self.base_url = provider.default_base_url().to_string();
 
         self.ai_provider = provider;
         self.save()
     }
 
+    /// Provider `translate` uses. Until `rosetta setup` picks a provider, the config only holds
+    /// the top-level OpenRouter settings next to a placeholder provider without a key.
+    pub fn translation_provider(&self) -> AIProvider {
+        if self.ai_provider.api_key().is_empty() {
+            return AIProvider::OpenRouter {
+                api_key: self.api_key.clone(),
+                model: self.model.clone(),
+            };
+        }
+        self.ai_provider.clone()
+    }
+
     pub fn update_default_language(&mut self, language: String) -> Result<()> {
         self.default_language = language;
         self.save()
@@ -115,6 +135,11 @@ impl Config {
                 model: String::from("gpt-3.5-turbo"),
             },
             batch_size: 30,
+            concurrency: default_concurrency(),
+            keys_per_request: default_keys_per_request(),
+            ai_translation_state: default_ai_translation_state(),
+            app_profile: AppProfile::default(),
+            prompt_templates: PromptTemplates::default(),
         }
     }
 
@@ -129,6 +154,13 @@ impl Config {
         println!("  Base URL         : {}", self.base_url);
         println!("  Model            : {}", self.model);
         println!("  Batch size       : {}", self.batch_size);
+        println!("  Concurrency      : {}", self.concurrency);
+        println!("  Keys per request : {}", self.keys_per_request);
+        println!("  AI output state  : {}", self.ai_translation_state);
+        println!("  App              : {}", self.app_profile.name.as_deref().unwrap_or("<not set>"));
+        if !self.prompt_templates.is_empty() {
+            println!("  Prompt templates : custom");
+        }
         match &self.ai_provider {
             crate::ai_provider::AIProvider::OpenAI { model, .. } => {
                 println!("  Provider         : OpenAI ({})", model);
@@ -139,12 +171,21 @@ impl Config {
             crate::ai_provider::AIProvider::Gemini { model, .. } => {
                 println!("  Provider         : Gemini ({})", model);
             }
+            crate::ai_provider::AIProvider::OpenRouter { model, .. } => {
+                println!("  Provider         : OpenRouter ({})", model);
+            }
         }
     }
 }
 
 fn default_batch_size() -> usize { 30 }
 
+fn default_concurrency() -> usize { 4 }
+
+fn default_keys_per_request() -> usize { 10 }
+
+fn default_ai_translation_state() -> UnitState { UnitState::NeedsReview }
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,6 +198,9 @@ mod tests {
         assert_eq!(config.default_language, "en");
         assert!(config.project_path.is_none());
         assert_eq!(config.batch_size, 30);
+        assert_eq!(config.concurrency, 4);
+        assert_eq!(config.keys_per_request, 10);
+        assert_eq!(config.ai_translation_state, UnitState::NeedsReview);
         
         match config.ai_provider {
             AIProvider::OpenAI { model, .. } => {
@@ -234,4 +278,26 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_translation_provider() {
+        // A config from before providers were used for translation keeps translating via OpenRouter
+        let legacy = Config::new("or-key".to_string(), "ja".to_string(), None);
+        match legacy.translation_provider() {
+            AIProvider::OpenRouter { api_key, model } => {
+                assert_eq!(api_key, "or-key");
+                assert_eq!(model, "anthropic/claude-3.5-sonnet");
+            }
+            _ => panic!("Legacy config should translate with OpenRouter"),
+        }
+        assert_eq!(Config::default().translation_provider().name(), "OpenRouter");
+
+        let mut config = Config::new(String::new(), "ja".to_string(), None);
+        config.ai_provider = AIProvider::Claude {
+            api_key: "claude-key".to_string(),
+            model: "claude-3-haiku-20240307".to_string(),
+        };
+        assert_eq!(config.translation_provider().name(), "Claude");
+        assert_eq!(config.translation_provider().api_key(), "claude-key");
+    }
 } 
\ No newline at end of file
@@ -1,3 +1,4 @@
+use std::time::Duration;
 use thiserror::Error;
 
 /// Custom error types for the translator
@@ -6,9 +7,26 @@ pub enum TranslatorError {
     #[error("API request failed with status {status}: {message}")]
     ApiError { status: u16, message: String },
 
+    #[error("Authentication failed with status {status}: {message}")]
+    Unauthorized { status: u16, message: String },
+
+    #[error("Rate limit exceeded: {message}")]
+    RateLimited { retry_after: Option<Duration>, message: String },
+
+    #[error("Quota exceeded: {message}")]
+    QuotaExceeded { message: String },
+
+    /// Network failures, timeouts and overloaded or failing servers
+    #[error("Temporary failure: {message}")]
+    Transient { retry_after: Option<Duration>, message: String },
+
     #[error("Translation failed: {0}")]
     TranslationFailed(String),
 
+    /// The model's translation reads different format arguments than the source
+    #[error("Format specifiers don't match the source: {0}")]
+    FormatMismatch(String),
+
     #[error("File format error: {0}")]
     FileFormatError(String),
 
@@ -22,5 +40,20 @@ pub enum TranslatorError {
     HttpError(#[from] reqwest::Error),
 }
 
+impl TranslatorError {
+    /// Whether sending the same request again may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::RateLimited { .. } | Self::Transient { .. })
+    }
+
+    /// How long the provider asked clients to wait before the next request
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after, .. } | Self::Transient { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
 /// Result type alias for convenience
 pub type Result<T> = std::result::Result<T, TranslatorError>;
\ No newline at end of file
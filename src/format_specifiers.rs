@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// How a format argument is read. Specifiers that read the same width of argument share a kind,
+/// so `%d`, `%i` and `%x` are interchangeable while `%d` and `%lld` are not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgumentKind {
+    Object,
+    Int,
+    /// 64-bit integers: `l`, `ll`, `q`, `z`, `t` and `j` lengths, and `%D`, `%U`, `%O`
+    Long,
+    Double,
+    LongDouble,
+    Char,
+    WideChar,
+    CString,
+    WideString,
+    Pointer,
+}
+
+/// A printf-style specifier that reads an argument
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Specifier {
+    /// The specifier as written, such as `%1$@`
+    pub text: String,
+    /// Byte range of `text` in the format string; empty for a `*` width or precision, which
+    /// is written inside the specifier that follows it
+    pub range: Range<usize>,
+    /// 1-based argument number, explicit (`%2$d`) or by order of appearance
+    pub position: usize,
+    pub kind: ArgumentKind,
+}
+
+impl Specifier {
+    /// Whether the argument number is written out, as in `%2$d`
+    pub fn has_position(&self) -> bool {
+        self.text.contains('$')
+    }
+
+    /// The specifier without `%` and argument number, such as `lld` or `.2f`
+    pub fn format(&self) -> &str {
+        let rest = &self.text[1..];
+        rest.split_once('$').map_or(rest, |(_, format)| format)
+    }
+
+    /// Flags, width and precision, such as `-5` or `.2`
+    pub fn options(&self) -> &str {
+        let format = self.format();
+        format[..format.len() - 1].trim_end_matches(LENGTH_MODIFIERS)
+    }
+}
+
+/// Length modifiers, such as the `ll` of `%lld`
+pub const LENGTH_MODIFIERS: [char; 7] = ['h', 'l', 'q', 'L', 'z', 't', 'j'];
+
+/// The arguments a format string reads, and its `%#@name@` substitution tokens
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormatArguments {
+    pub specifiers: Vec<Specifier>,
+    /// Substitution names, plus `arg` for each `%arg` in a substitution's plural forms
+    pub tokens: Vec<String>,
+    /// Each `%#@name@` substitution with the argument it reads. Substitutions take their place
+    /// in the argument order like any specifier, so in `%@ has %#@files@` files is argument 2.
+    pub substitutions: Vec<(String, usize)>,
+}
+
+impl FormatArguments {
+    /// Parse the specifiers of a format string. `%%` and text that is not a specifier, such as
+    /// the `% ` in "50% off", read no argument.
+    pub fn parse(format: &str) -> Self {
+        let chars: Vec<char> = format.chars().collect();
+        // Byte offset of each char, and of the end
+        let offsets: Vec<usize> = format.char_indices().map(|(offset, _)| offset).chain([format.len()]).collect();
+        let mut arguments = Self::default();
+        let mut next_position = 1;
+        let mut i = 0;
+
+        while i < chars.len() {
+            if chars[i] != '%' {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            i += 1;
+            if chars.get(i) == Some(&'%') {
+                i += 1;
+                continue;
+            }
+
+            // `%arg` stands for the substitution's own argument inside its plural forms
+            let word_end = !chars.get(i + 3).is_some_and(|c| c.is_alphanumeric());
+            if chars[i..].starts_with(&['a', 'r', 'g']) && word_end {
+                arguments.tokens.push("arg".to_string());
+                i += 3;
+                continue;
+            }
+
+            let digits = count_digits(&chars[i..]);
+            let mut position = None;
+            if digits > 0 && chars.get(i + digits) == Some(&'$') {
+                position = chars[i..i + digits].iter().collect::<String>().parse::<usize>().ok();
+                i += digits + 1;
+            }
+
+            if chars[i..].starts_with(&['#', '@']) {
+                if let Some(end) = chars[i + 2..].iter().position(|&c| c == '@') {
+                    let name: String = chars[i + 2..i + 2 + end].iter().collect();
+                    let position = position.unwrap_or_else(|| {
+                        next_position += 1;
+                        next_position - 1
+                    });
+                    arguments.tokens.push(name.clone());
+                    arguments.substitutions.push((name, position));
+                    i += end + 3;
+                    continue;
+                }
+            }
+
+            while i < chars.len() && matches!(chars[i], '-' | '+' | '#' | '0') {
+                i += 1;
+            }
+
+            // A `*` width or precision reads an int argument of its own
+            let mut star_positions = Vec::new();
+            if chars.get(i) == Some(&'*') {
+                star_positions.push(i);
+                i += 1;
+            } else {
+                i += count_digits(&chars[i..]);
+            }
+            if chars.get(i) == Some(&'.') {
+                i += 1;
+                if chars.get(i) == Some(&'*') {
+                    star_positions.push(i);
+                    i += 1;
+                } else {
+                    i += count_digits(&chars[i..]);
+                }
+            }
+
+            let length_start = i;
+            while i - length_start < 2 && chars.get(i).is_some_and(|c| LENGTH_MODIFIERS.contains(c)) {
+                i += 1;
+            }
+            let length: String = chars[length_start..i].iter().collect();
+
+            let Some(kind) = chars.get(i).and_then(|&c| argument_kind(c, &length)) else {
+                // Not a specifier, or `%n`, which reads no argument
+                i = start + 1;
+                continue;
+            };
+            i += 1;
+
+            for &star in &star_positions {
+                arguments.specifiers.push(Specifier {
+                    text: "*".to_string(),
+                    range: offsets[star]..offsets[star],
+                    position: next_position,
+                    kind: ArgumentKind::Int,
+                });
+                next_position += 1;
+            }
+            let position = position.unwrap_or_else(|| {
+                next_position += 1;
+                next_position - 1
+            });
+            arguments.specifiers.push(Specifier {
+                text: chars[start..i].iter().collect(),
+                range: offsets[start]..offsets[i],
+                position,
+                kind,
+            });
+        }
+
+        arguments
+    }
+
+    /// One specifier per argument position
+    fn by_position(&self) -> BTreeMap<usize, &Specifier> {
+        let mut positions = BTreeMap::new();
+        for specifier in &self.specifiers {
+            positions.entry(specifier.position).or_insert(specifier);
+        }
+        positions
+    }
+}
+
+fn count_digits(chars: &[char]) -> usize {
+    chars.iter().take_while(|c| c.is_ascii_digit()).count()
+}
+
+fn argument_kind(conversion: char, length: &str) -> Option<ArgumentKind> {
+    let long = matches!(length, "l" | "ll" | "q" | "z" | "t" | "j");
+    Some(match conversion {
+        '@' => ArgumentKind::Object,
+        'd' | 'i' | 'u' | 'x' | 'X' | 'o' if long => ArgumentKind::Long,
+        'd' | 'i' | 'u' | 'x' | 'X' | 'o' => ArgumentKind::Int,
+        'D' | 'U' | 'O' => ArgumentKind::Long,
+        'f' | 'F' | 'e' | 'E' | 'g' | 'G' | 'a' | 'A' if length == "L" => ArgumentKind::LongDouble,
+        'f' | 'F' | 'e' | 'E' | 'g' | 'G' | 'a' | 'A' => ArgumentKind::Double,
+        'c' if length == "l" => ArgumentKind::WideChar,
+        'c' => ArgumentKind::Char,
+        'C' => ArgumentKind::WideChar,
+        's' if length == "l" => ArgumentKind::WideString,
+        's' => ArgumentKind::CString,
+        'S' => ArgumentKind::WideString,
+        'p' => ArgumentKind::Pointer,
+        _ => return None,
+    })
+}
+
+/// Check that a translation reads the same arguments as its source: the same types at the same
+/// positions, so `%1$@ %2$d` may become `%2$d %1$@` but `%@ %d` may not become `%d %@`.
+pub fn check(source: &str, translation: &str) -> Result<(), String> {
+    check_forms(&[source], translation)
+}
+
+/// Check one plural form against the source's forms. The translation may read any argument of
+/// some source form, and must read those every source form reads, so a `one` form may leave
+/// out a count that the source's `one` form also leaves out.
+pub fn check_forms(sources: &[&str], translation: &str) -> Result<(), String> {
+    let sources: Vec<FormatArguments> = sources.iter().map(|s| FormatArguments::parse(s)).collect();
+    let actual = FormatArguments::parse(translation);
+    let actual_positions = actual.by_position();
+    let mut problems = Vec::new();
+
+    let mut allowed: BTreeMap<usize, &Specifier> = BTreeMap::new();
+    for source in &sources {
+        for (position, specifier) in source.by_position() {
+            allowed.entry(position).or_insert(specifier);
+        }
+    }
+
+    for (position, expected) in &allowed {
+        let required = sources.iter().all(|s| s.by_position().contains_key(position));
+        match actual_positions.get(position) {
+            Some(found) if found.kind != expected.kind => {
+                problems.push(format!("{} in place of {}", found.text, expected.text))
+            }
+            Some(_) => {}
+            None if required => problems.push(format!("missing {}", expected.text)),
+            None => {}
+        }
+    }
+    for (position, found) in &actual_positions {
+        if !allowed.contains_key(position) {
+            problems.push(format!("unexpected {}", found.text));
+        }
+    }
+
+    // A token may appear as often as some source form has it, and must appear as often as every
+    // source form has it
+    let count = |tokens: &[String], name: &str| tokens.iter().filter(|t| *t == name).count();
+    let mut names: Vec<&String> = sources.iter().flat_map(|s| &s.tokens).chain(&actual.tokens).collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        let found = count(&actual.tokens, name);
+        let required = sources.iter().map(|s| count(&s.tokens, name)).min().unwrap_or(0);
+        let allowed = sources.iter().map(|s| count(&s.tokens, name)).max().unwrap_or(0);
+        if found < required {
+            problems.push(format!("missing {}", token_text(name)));
+        } else if found > allowed {
+            problems.push(format!("unexpected {}", token_text(name)));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join(", "))
+    }
+}
+
+fn token_text(name: &str) -> String {
+    if name == "arg" {
+        "%arg".to_string()
+    } else {
+        format!("%#@{}@", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(format: &str) -> Vec<(usize, ArgumentKind)> {
+        FormatArguments::parse(format)
+            .specifiers
+            .iter()
+            .map(|s| (s.position, s.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        use ArgumentKind::*;
+        assert_eq!(kinds("%@ has %lld items"), vec![(1, Object), (2, Long)]);
+        assert_eq!(kinds("%2$d of %1$@"), vec![(2, Int), (1, Object)]);
+        assert_eq!(kinds("100%% done, %.1f%%"), vec![(1, Double)]);
+        assert_eq!(kinds("%-5.2Lf %ls %hhd %zu %C"), vec![(1, LongDouble), (2, WideString), (3, Int), (4, Long), (5, WideChar)]);
+        assert_eq!(kinds("%*d"), vec![(1, Int), (2, Int)]);
+        assert!(kinds("50% off, 100 %").is_empty());
+
+        let arguments = FormatArguments::parse("é %2$-5.2lf");
+        let specifier = &arguments.specifiers[0];
+        assert_eq!(specifier.range, 3..12);
+        assert!(specifier.has_position());
+        assert_eq!((specifier.format(), specifier.options()), ("-5.2lf", "-5.2"));
+
+        let arguments = FormatArguments::parse("%#@files@ in %1$#@folders@, %arg");
+        assert!(arguments.specifiers.is_empty());
+        assert_eq!(arguments.tokens, vec!["files", "folders", "arg"]);
+        assert_eq!(arguments.substitutions, vec![("files".to_string(), 1), ("folders".to_string(), 1)]);
+
+        let arguments = FormatArguments::parse("%@ has %#@files@ in %lld folders");
+        assert_eq!(arguments.substitutions, vec![("files".to_string(), 2)]);
+        assert_eq!(kinds("%@ has %#@files@ in %lld folders"), vec![(1, Object), (3, Long)]);
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(check("%@ has %lld items", "%@ には %lld 個の項目があります").is_ok());
+        assert!(check("%1$@ sent %2$@", "%2$@ から %1$@ へ").is_ok());
+        assert!(check("Delete %d photos?", "%i 枚の写真を削除しますか？").is_ok());
+        assert!(check("50% off", "50%オフ").is_ok());
+
+        assert_eq!(check("%@ has %lld items", "%@ には項目があります"), Err("missing %lld".to_string()));
+        assert_eq!(check("%lld items", "%d 個"), Err("%d in place of %lld".to_string()));
+        assert_eq!(check("%@ and %d", "%d と %@"), Err("%d in place of %@, %@ in place of %d".to_string()));
+        assert_eq!(check("Done", "完了 %@"), Err("unexpected %@".to_string()));
+        assert_eq!(check("%#@count@ left", "残り"), Err("missing %#@count@".to_string()));
+    }
+
+    #[test]
+    fn test_check_forms() {
+        let sources = ["One item", "%lld items"];
+        assert!(check_forms(&sources, "%lld 個").is_ok());
+        assert!(check_forms(&sources, "項目").is_ok());
+        assert!(check_forms(&sources, "%d 個").is_err());
+        assert_eq!(check_forms(&["%arg file", "%arg files"], "ファイル"), Err("missing %arg".to_string()));
+    }
+}
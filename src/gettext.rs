@@ -0,0 +1,495 @@
+use crate::error::{Result, TranslatorError};
+use crate::plural_rules::plural_categories;
+use crate::xcstrings::{split_variation_id, variation_id, ImportSummary, UnitState, XCStringsFile};
+use crate::xliff::{self, XliffUnit};
+
+/// Flag gettext tools put on translations that need checking
+const FUZZY: &str = "fuzzy";
+
+/// One message of a PO/POT file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoEntry {
+    /// `msgctxt`: the catalog key, or the variation id of a device or substitution variation
+    pub context: Option<String>,
+    pub id: String,
+    pub id_plural: Option<String>,
+    /// `msgstr`, or `msgstr[n]` in the language's plural order
+    pub strings: Vec<String>,
+    /// `#.` extracted comments
+    pub comment: Option<String>,
+    /// `#,` flags such as `fuzzy`
+    pub flags: Vec<String>,
+}
+
+impl PoEntry {
+    pub fn is_fuzzy(&self) -> bool {
+        self.flags.iter().any(|flag| flag == FUZZY)
+    }
+}
+
+/// A PO file, or a POT template when `language` is None
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoFile {
+    pub language: Option<String>,
+    pub entries: Vec<PoEntry>,
+}
+
+/// `Plural-Forms` header for a language, with `msgstr[n]` following the CLDR category order
+/// of `plural_categories`. Fractions are left to the trailing `other` form where CLDR has one.
+fn plural_forms(language: &str) -> String {
+    let base = language.split(['-', '_']).next().unwrap_or(language).to_lowercase();
+    let expression = match base.as_str() {
+        "fr" | "pt" => "(n == 0 || n == 1) ? 0 : (n != 0 && n % 1000000 == 0) ? 1 : 2",
+        "es" | "it" | "ca" => "n == 1 ? 0 : (n != 0 && n % 1000000 == 0) ? 1 : 2",
+        "ru" | "uk" | "be" => {
+            "n % 10 == 1 && n % 100 != 11 ? 0 : n % 10 >= 2 && n % 10 <= 4 && (n % 100 < 12 || n % 100 > 14) ? 1 : 2"
+        }
+        "pl" => "n == 1 ? 0 : n % 10 >= 2 && n % 10 <= 4 && (n % 100 < 12 || n % 100 > 14) ? 1 : 2",
+        "cs" | "sk" => "n == 1 ? 0 : (n >= 2 && n <= 4) ? 1 : 3",
+        "lt" => "n % 10 == 1 && (n % 100 < 11 || n % 100 > 19) ? 0 : n % 10 >= 2 && (n % 100 < 11 || n % 100 > 19) ? 1 : 3",
+        "hr" | "sr" | "bs" => {
+            "n % 10 == 1 && n % 100 != 11 ? 0 : n % 10 >= 2 && n % 10 <= 4 && (n % 100 < 12 || n % 100 > 14) ? 1 : 2"
+        }
+        "ro" => "n == 1 ? 0 : (n == 0 || (n % 100 >= 2 && n % 100 <= 19)) ? 1 : 2",
+        "lv" => "n % 10 == 0 || (n % 100 >= 11 && n % 100 <= 19) ? 0 : n % 10 == 1 && n % 100 != 11 ? 1 : 2",
+        "he" | "iw" => "n == 1 ? 0 : n == 2 ? 1 : 2",
+        "sl" => "n % 100 == 1 ? 0 : n % 100 == 2 ? 1 : (n % 100 == 3 || n % 100 == 4) ? 2 : 3",
+        "ga" => "n == 1 ? 0 : n == 2 ? 1 : (n >= 3 && n <= 6) ? 2 : (n >= 7 && n <= 10) ? 3 : 4",
+        "mt" => {
+            "n == 1 ? 0 : n == 2 ? 1 : (n == 0 || (n % 100 >= 3 && n % 100 <= 10)) ? 2 : (n % 100 >= 11 && n % 100 <= 19) ? 3 : 4"
+        }
+        "ar" => "n == 0 ? 0 : n == 1 ? 1 : n == 2 ? 2 : (n % 100 >= 3 && n % 100 <= 10) ? 3 : n % 100 >= 11 ? 4 : 5",
+        "cy" => "n == 0 ? 0 : n == 1 ? 1 : n == 2 ? 2 : n == 3 ? 3 : n == 6 ? 4 : 5",
+        _ => match plural_categories(language).len() {
+            1 => "0",
+            _ => "n != 1",
+        },
+    };
+    format!(
+        "nplurals={}; plural=({});",
+        plural_categories(language).len(),
+        expression
+    )
+}
+
+/// The variation path prefix and category of a plural variation path such as
+/// `device.iphone.plural.one`
+fn plural_path(path: &str) -> Option<(&str, &str)> {
+    let (prefix, category) = path.rsplit_once("plural.")?;
+    if !(prefix.is_empty() || prefix.ends_with('.')) {
+        return None;
+    }
+    Some((prefix.trim_end_matches('.'), category))
+}
+
+fn join_path(prefix: &str, segment: &str) -> String {
+    if prefix.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{}.{}", prefix, segment)
+    }
+}
+
+/// Messages for every translatable key of a catalog. Without a language this is a POT template
+/// with empty translations; plurals use the source language's forms for `msgid`/`msgid_plural`.
+pub fn export(file: &XCStringsFile, language: Option<&str>) -> PoFile {
+    let source_language = &file.data.source_language;
+    let units = xliff::export_units(file, language.unwrap_or(source_language));
+    let plural_count = language.map_or(2, |l| plural_categories(l).len());
+    let mut entries: Vec<PoEntry> = Vec::new();
+
+    let mut i = 0;
+    while i < units.len() {
+        let unit = &units[i];
+        let (key, path) = split_variation_id(&unit.id);
+        let fuzzy = language.is_some()
+            && unit.target.is_some()
+            && xliff::unit_state(unit.state.as_deref()) == UnitState::NeedsReview;
+        let mut entry = PoEntry {
+            comment: unit.note.clone(),
+            flags: if fuzzy { vec![FUZZY.to_string()] } else { Vec::new() },
+            ..Default::default()
+        };
+
+        match plural_path(path) {
+            Some((prefix, _)) => {
+                // Units of one plural are consecutive, in category order
+                let group: Vec<&XliffUnit> = units[i..]
+                    .iter()
+                    .take_while(|u| {
+                        let (k, p) = split_variation_id(&u.id);
+                        k == key && plural_path(p).is_some_and(|(pr, _)| pr == prefix)
+                    })
+                    .collect();
+                let source_form = |category: &str| {
+                    group
+                        .iter()
+                        .find(|u| plural_path(split_variation_id(&u.id).1).map(|(_, c)| c) == Some(category))
+                        .map(|u| u.source.clone())
+                };
+
+                entry.context = Some(variation_id(key, prefix));
+                entry.id = source_form("one").unwrap_or_else(|| group[0].source.clone());
+                entry.id_plural = Some(source_form("other").unwrap_or_else(|| entry.id.clone()));
+                entry.strings = match language {
+                    Some(_) => group.iter().map(|u| u.target.clone().unwrap_or_default()).collect(),
+                    None => vec![String::new(); plural_count],
+                };
+                i += group.len();
+            }
+            None => {
+                entry.context = Some(unit.id.clone());
+                entry.id = unit.source.clone();
+                entry.strings = vec![language.and(unit.target.clone()).unwrap_or_default()];
+                i += 1;
+            }
+        }
+        entries.push(entry);
+    }
+
+    PoFile {
+        language: language.map(str::to_string),
+        entries,
+    }
+}
+
+/// Apply a PO file's translations to a catalog. Fuzzy messages are stored as `needs_review`;
+/// messages without `msgctxt` are matched by their `msgid`.
+pub fn import(file: &mut XCStringsFile, po: &PoFile, language: &str) -> Result<ImportSummary> {
+    let categories = plural_categories(language);
+    let mut units = Vec::new();
+
+    for entry in &po.entries {
+        let state = if entry.is_fuzzy() {
+            UnitState::NeedsReview
+        } else {
+            UnitState::Translated
+        };
+        let id = entry.context.clone().unwrap_or_else(|| entry.id.clone());
+        let (key, path) = split_variation_id(&id);
+
+        let mut unit = |id: String, target: &String| {
+            units.push(XliffUnit {
+                id,
+                source: entry.id.clone(),
+                target: Some(target.clone()),
+                state: Some(xliff::xliff_state(state).to_string()),
+                note: None,
+            });
+        };
+        if entry.id_plural.is_some() {
+            for (category, target) in categories.iter().zip(&entry.strings) {
+                let plural = join_path(path, &format!("plural.{}", category));
+                unit(variation_id(key, &plural), target);
+            }
+        } else if let Some(target) = entry.strings.first() {
+            unit(id.clone(), target);
+        }
+    }
+
+    xliff::import_units(file, &units, language)
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Write `keyword "text"`, splitting multi-line text after each `\n` the way xgettext does
+fn write_string(out: &mut String, keyword: &str, text: &str) {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    if lines.len() > 1 {
+        out.push_str(&format!("{} \"\"\n", keyword));
+        for line in lines {
+            out.push_str(&format!("\"{}\"\n", escape(line)));
+        }
+    } else {
+        out.push_str(&format!("{} \"{}\"\n", keyword, escape(text)));
+    }
+}
+
+/// Write a PO or POT file
+pub fn to_string(po: &PoFile) -> String {
+    let mut out = String::new();
+    let (language, plural_forms) = match &po.language {
+        Some(language) => (language.as_str(), plural_forms(language)),
+        None => ("", "nplurals=INTEGER; plural=EXPRESSION;".to_string()),
+    };
+    out.push_str("msgid \"\"\nmsgstr \"\"\n");
+    out.push_str("\"Content-Type: text/plain; charset=UTF-8\\n\"\n");
+    out.push_str("\"Content-Transfer-Encoding: 8bit\\n\"\n");
+    out.push_str(&format!("\"Language: {}\\n\"\n", language));
+    out.push_str(&format!("\"Plural-Forms: {}\\n\"\n", plural_forms));
+
+    for entry in &po.entries {
+        out.push('\n');
+        if let Some(comment) = &entry.comment {
+            for line in comment.lines() {
+                out.push_str(&format!("#. {}\n", line));
+            }
+        }
+        if !entry.flags.is_empty() {
+            out.push_str(&format!("#, {}\n", entry.flags.join(", ")));
+        }
+        if let Some(context) = &entry.context {
+            write_string(&mut out, "msgctxt", context);
+        }
+        write_string(&mut out, "msgid", &entry.id);
+        match &entry.id_plural {
+            Some(id_plural) => {
+                write_string(&mut out, "msgid_plural", id_plural);
+                for (n, text) in entry.strings.iter().enumerate() {
+                    write_string(&mut out, &format!("msgstr[{}]", n), text);
+                }
+            }
+            None => write_string(&mut out, "msgstr", entry.strings.first().map_or("", String::as_str)),
+        }
+    }
+    out
+}
+
+fn po_error(line: usize, message: &str) -> TranslatorError {
+    TranslatorError::FileFormatError(format!("Invalid PO file, line {}: {}", line, message))
+}
+
+/// Contents of a quoted PO string, with C escapes resolved
+fn unquote(text: &str, line: usize) -> Result<String> {
+    let inner = text
+        .trim()
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .ok_or_else(|| po_error(line, "expected a quoted string"))?;
+
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('a') => out.push('\u{7}'),
+            Some('b') => out.push('\u{8}'),
+            Some('f') => out.push('\u{c}'),
+            Some('v') => out.push('\u{b}'),
+            Some(c) => out.push(c),
+            None => return Err(po_error(line, "dangling backslash")),
+        }
+    }
+    Ok(out)
+}
+
+/// Which string of the current entry continuation lines append to
+#[derive(Clone, Copy)]
+enum Field {
+    Context,
+    Id,
+    IdPlural,
+    Str(usize),
+}
+
+/// Parse a PO or POT file. The header's `Language` becomes `PoFile::language`; obsolete
+/// (`#~`) messages are dropped.
+pub fn parse(content: &str) -> Result<PoFile> {
+    let mut po = PoFile::default();
+    let mut entries: Vec<PoEntry> = Vec::new();
+    let mut entry = PoEntry::default();
+    let mut field: Option<Field> = None;
+    let mut comments: Vec<String> = Vec::new();
+
+    let finish = |entry: &mut PoEntry, comments: &mut Vec<String>, entries: &mut Vec<PoEntry>| {
+        if !comments.is_empty() {
+            entry.comment = Some(comments.join("\n"));
+            comments.clear();
+        }
+        let entry = std::mem::take(entry);
+        if !(entry.id.is_empty() && entry.context.is_none() && entry.strings.is_empty()) {
+            entries.push(entry);
+        }
+    };
+
+    for (n, line) in content.trim_start_matches('\u{feff}').lines().enumerate() {
+        let line_number = n + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with("#~") {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('#') {
+            // A comment after a message's strings starts the next message
+            if field.is_some() {
+                finish(&mut entry, &mut comments, &mut entries);
+                field = None;
+            }
+            if let Some(comment) = rest.strip_prefix('.') {
+                comments.push(comment.trim().to_string());
+            } else if let Some(flags) = rest.strip_prefix(',') {
+                entry.flags.extend(flags.split(',').map(|f| f.trim().to_string()).filter(|f| !f.is_empty()));
+            }
+            continue;
+        }
+        if line.starts_with('"') {
+            let text = unquote(line, line_number)?;
+            match field {
+                Some(Field::Context) => entry.context.get_or_insert_with(String::new).push_str(&text),
+                Some(Field::Id) => entry.id.push_str(&text),
+                Some(Field::IdPlural) => entry.id_plural.get_or_insert_with(String::new).push_str(&text),
+                Some(Field::Str(i)) => entry.strings[i].push_str(&text),
+                None => return Err(po_error(line_number, "string outside a message")),
+            }
+            continue;
+        }
+
+        let (keyword, rest) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| po_error(line_number, "expected a keyword and a string"))?;
+        let text = unquote(rest, line_number)?;
+        // msgctxt or msgid after the strings of a message starts the next one
+        if matches!(keyword, "msgctxt" | "msgid") && matches!(field, Some(Field::Str(_))) {
+            finish(&mut entry, &mut comments, &mut entries);
+        }
+        field = Some(match keyword {
+            "msgctxt" => {
+                entry.context = Some(text);
+                Field::Context
+            }
+            "msgid" => {
+                entry.id = text;
+                Field::Id
+            }
+            "msgid_plural" => {
+                entry.id_plural = Some(text);
+                Field::IdPlural
+            }
+            "msgstr" => {
+                entry.strings = vec![text];
+                Field::Str(0)
+            }
+            _ => {
+                let index = keyword
+                    .strip_prefix("msgstr[")
+                    .and_then(|k| k.strip_suffix(']'))
+                    .and_then(|k| k.parse::<usize>().ok())
+                    .ok_or_else(|| po_error(line_number, &format!("unknown keyword '{}'", keyword)))?;
+                if entry.strings.len() <= index {
+                    entry.strings.resize(index + 1, String::new());
+                }
+                entry.strings[index] = text;
+                Field::Str(index)
+            }
+        });
+    }
+    finish(&mut entry, &mut comments, &mut entries);
+
+    // The header is the message with an empty msgid and no context
+    if let Some(position) = entries.iter().position(|e| e.id.is_empty() && e.context.is_none()) {
+        let header = entries.remove(position);
+        po.language = header
+            .strings
+            .first()
+            .into_iter()
+            .flat_map(|h| h.lines())
+            .find_map(|line| line.strip_prefix("Language:"))
+            .map(|language| language.trim().replace('_', "-"))
+            .filter(|language| !language.is_empty());
+    }
+    po.entries = entries;
+    Ok(po)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xcstrings::tests::load_catalog;
+    use crate::xcstrings::UnitNode;
+
+    const CATALOG: &str = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "%lld items" : {
+      "comment" : "Items in the cart",
+      "localizations" : {
+        "en" : {
+          "variations" : {
+            "plural" : {
+              "one" : { "stringUnit" : { "state" : "translated", "value" : "%lld item" } },
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld items" } }
+            }
+          }
+        }
+      }
+    },
+    "Say \"hi\"\nplease" : {
+      "localizations" : {
+        "ru" : { "stringUnit" : { "state" : "needs_review", "value" : "Скажи \"привет\"\nпожалуйста" } }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#;
+
+    #[test]
+    fn test_export_po() {
+        let (_dir, file) = load_catalog(CATALOG);
+        let po = to_string(&export(&file, Some("ru")));
+
+        assert!(po.contains("\"Language: ru\\n\"\n"));
+        assert!(po.contains("nplurals=4;"));
+        assert!(po.contains(
+            "#. Items in the cart\nmsgctxt \"%lld items\"\nmsgid \"%lld item\"\nmsgid_plural \"%lld items\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\nmsgstr[2] \"\"\nmsgstr[3] \"\"\n"
+        ));
+        assert!(po.contains("#, fuzzy\nmsgctxt \"\"\n\"Say \\\"hi\\\"\\n\"\n\"please\"\n"));
+
+        let pot = to_string(&export(&file, None));
+        assert!(pot.contains("\"Language: \\n\"\n"));
+        assert!(!pot.contains("fuzzy"));
+        assert!(!pot.contains("привет"));
+    }
+
+    #[test]
+    fn test_po_round_trip() {
+        let (_dir, mut file) = load_catalog(CATALOG);
+        let mut po = parse(&to_string(&export(&file, Some("ru")))).unwrap();
+        assert_eq!(po.language.as_deref(), Some("ru"));
+        assert_eq!(po, export(&file, Some("ru")));
+
+        po.entries[0].strings = ["%lld товар", "%lld товара", "%lld товаров", "%lld товара"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        po.entries[1].flags.clear();
+        let summary = import(&mut file, &po, "ru").unwrap();
+        assert_eq!(summary.keys, 2);
+
+        let items = &file.data.strings["%lld items"].localizations["ru"];
+        assert_eq!(items.plural_forms()["many"], "%lld товаров");
+        let say = &file.data.strings["Say \"hi\"\nplease"].localizations["ru"];
+        assert_eq!(say.unit_states(), vec![UnitState::Translated]);
+    }
+
+    #[test]
+    fn test_parse_fuzzy_without_context() {
+        let po = parse(
+            "# translator note\n#: src/app.js:12\n#, fuzzy, javascript-format\nmsgid \"Save\"\nmsgstr \"Sichern\"\n\n#~ msgid \"Old\"\n#~ msgstr \"Alt\"\n",
+        )
+        .unwrap();
+        assert_eq!(po.language, None);
+        assert_eq!(po.entries.len(), 1);
+        assert!(po.entries[0].is_fuzzy());
+        assert_eq!(po.entries[0].context, None);
+        assert_eq!(po.entries[0].strings, vec!["Sichern".to_string()]);
+    }
+}
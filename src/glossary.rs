@@ -0,0 +1,181 @@
+use crate::error::Result;
+use crate::translation_memory::languages_match;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Glossary file looked up at the root of the workspace
+pub const GLOSSARY_FILE: &str = "rosetta-glossary.json";
+
+/// A project's mandated translations of product terms
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Glossary {
+    /// Source term → its translation in each target language
+    #[serde(default)]
+    pub terms: BTreeMap<String, BTreeMap<String, String>>,
+    /// Terms kept as written in every language
+    #[serde(default)]
+    pub do_not_translate: Vec<String>,
+}
+
+/// A glossary term found in a source string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlossaryTerm {
+    pub source: String,
+    /// The mandated translation, or `None` when the term stays as written
+    pub target: Option<String>,
+}
+
+impl Glossary {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Number of terms, translated and kept
+    pub fn len(&self) -> usize {
+        self.terms.len() + self.do_not_translate.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Terms occurring in `source` that have a translation into `language` or stay as written.
+    /// Longer terms win, so "Focus Mode" does not also count as "Focus".
+    pub fn terms_in(&self, source: &str, language: &str) -> Vec<GlossaryTerm> {
+        let mut candidates: Vec<GlossaryTerm> = self
+            .do_not_translate
+            .iter()
+            .map(|term| GlossaryTerm {
+                source: term.clone(),
+                target: None,
+            })
+            .collect();
+        for (term, targets) in &self.terms {
+            let target = targets
+                .get(language)
+                .or_else(|| targets.iter().find(|(l, _)| languages_match(l, language)).map(|(_, t)| t));
+            if let Some(target) = target {
+                candidates.push(GlossaryTerm {
+                    source: term.clone(),
+                    target: Some(target.clone()),
+                });
+            }
+        }
+        candidates.sort_by_key(|term| std::cmp::Reverse(term.source.chars().count()));
+
+        let mut remaining = source.to_lowercase();
+        let mut found = Vec::new();
+        for term in candidates {
+            if contains_word(&remaining, &term.source) {
+                remaining = remaining.replace(&term.source.to_lowercase(), "\n");
+                found.push(term);
+            }
+        }
+        found
+    }
+
+    /// Terms of `source` the translation does not use as the glossary mandates
+    pub fn check(&self, source: &str, translation: &str, language: &str) -> Vec<String> {
+        let lowercase = translation.to_lowercase();
+        self.terms_in(source, language)
+            .into_iter()
+            .filter_map(|term| match term.target {
+                // Capitalization may change at the start of a sentence
+                Some(target) if !lowercase.contains(&target.to_lowercase()) => {
+                    Some(format!("\"{}\" should be \"{}\"", term.source, target))
+                }
+                None if !translation.contains(&term.source) => {
+                    Some(format!("\"{}\" should stay untranslated", term.source))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Case-insensitive search for `term` as a whole word. Scripts written without spaces, such as
+/// Chinese or Japanese, have no word boundaries to check.
+fn contains_word(text: &str, term: &str) -> bool {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let term: Vec<char> = term.to_lowercase().chars().collect();
+    if term.is_empty() || term.len() > text.len() {
+        return false;
+    }
+
+    let joins = |a: char, b: char| is_word_char(a) && is_word_char(b);
+    (0..=text.len() - term.len()).any(|start| {
+        let end = start + term.len();
+        text[start..end] == term[..]
+            && (start == 0 || !joins(text[start - 1], term[0]))
+            && (end == text.len() || !joins(text[end], term[term.len() - 1]))
+    })
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() && c < '\u{2E80}'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn glossary() -> Glossary {
+        let mut terms = BTreeMap::new();
+        terms.insert(
+            "Focus Mode".to_string(),
+            BTreeMap::from([("ja".to_string(), "集中モード".to_string()), ("de".to_string(), "Fokusmodus".to_string())]),
+        );
+        terms.insert(
+            "Focus".to_string(),
+            BTreeMap::from([("ja".to_string(), "フォーカス".to_string())]),
+        );
+        Glossary {
+            terms,
+            do_not_translate: vec!["Trailmate".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_terms_in() {
+        let glossary = glossary();
+        let terms = glossary.terms_in("Turn on focus mode in Trailmate", "ja-JP");
+        let sources: Vec<&str> = terms.iter().map(|t| t.source.as_str()).collect();
+        assert_eq!(sources, vec!["Focus Mode", "Trailmate"]);
+        assert_eq!(terms[0].target.as_deref(), Some("集中モード"));
+        assert_eq!(terms[1].target, None);
+
+        // Only whole words, and only terms with a translation into the language
+        assert_eq!(glossary.terms_in("Focus timer and Focus Mode", "ja").len(), 2);
+        assert!(glossary.terms_in("Refocusing", "ja").is_empty());
+        assert_eq!(glossary.terms_in("Focus", "de").len(), 0);
+        assert!(contains_word("集中モードをオン", "集中モード"));
+    }
+
+    #[test]
+    fn test_check() {
+        let glossary = glossary();
+        assert!(glossary.check("Focus Mode", "Fokusmodus aktivieren", "de").is_empty());
+        assert!(glossary.check("Focus Mode", "fokusmodus", "de").is_empty());
+        assert_eq!(
+            glossary.check("Open Trailmate", "Öffne Wanderfreund", "de"),
+            vec!["\"Trailmate\" should stay untranslated"]
+        );
+        assert_eq!(
+            glossary.check("Focus Mode is on", "Konzentrationsmodus ist an", "de"),
+            vec!["\"Focus Mode\" should be \"Fokusmodus\""]
+        );
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(GLOSSARY_FILE);
+        fs::write(&path, r#"{ "terms": { "Focus Mode": { "ja": "集中モード" } }, "do_not_translate": ["iCloud"] }"#).unwrap();
+        let glossary = Glossary::load(&path).unwrap();
+        assert_eq!(glossary.len(), 2);
+        assert_eq!(glossary.terms["Focus Mode"]["ja"], "集中モード");
+    }
+}
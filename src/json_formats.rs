@@ -0,0 +1,901 @@
+use crate::android::resource_names;
+use crate::error::{Result, TranslatorError};
+use crate::format_specifiers::{ArgumentKind, FormatArguments, Specifier};
+use crate::plural_rules::PLURAL_CATEGORIES;
+use crate::xcode_json::compare_keys;
+use crate::xcstrings::{
+    CatalogFormat, FormatExport, ImportSummary, TranslationValue, UnitNode, UnitState,
+    ValueBuilder, XCStringsFile,
+};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Specifiers written in a string; a `*` width is part of the specifier after it
+fn specifiers(text: &str) -> Vec<Specifier> {
+    FormatArguments::parse(text)
+        .specifiers
+        .into_iter()
+        .filter(|spec| !spec.range.is_empty())
+        .collect()
+}
+
+/// Names given to a key's format arguments: `count` for the plural argument, `argN` for the others
+#[derive(Debug, Default)]
+struct Placeholders {
+    formats: BTreeMap<usize, String>,
+    names: BTreeMap<usize, String>,
+    /// The source uses `%1$@`-style positions, so imports keep them
+    positional: bool,
+}
+
+impl Placeholders {
+    /// Placeholders of a source value; for plurals, the `other` form decides
+    fn new(source: &TranslationValue) -> Self {
+        let (text, plural) = match source {
+            TranslationValue::Text(text) => (text.as_str(), false),
+            TranslationValue::Plural(forms) => (
+                forms
+                    .get("other")
+                    .or_else(|| forms.values().next())
+                    .map_or("", String::as_str),
+                true,
+            ),
+            _ => ("", false),
+        };
+
+        let specs = specifiers(text);
+        let count = specs
+            .iter()
+            .find(|s| plural && matches!(s.kind, ArgumentKind::Int | ArgumentKind::Long))
+            .map(|s| s.position);
+        let mut placeholders = Placeholders {
+            positional: text.contains('$') && specs.iter().any(|s| text[s.range.clone()].contains('$')),
+            ..Default::default()
+        };
+        for spec in specs {
+            let name = if Some(spec.position) == count {
+                "count".to_string()
+            } else {
+                format!("arg{}", spec.position)
+            };
+            placeholders.names.insert(spec.position, name);
+            placeholders.formats.insert(spec.position, spec.format().to_string());
+        }
+        placeholders
+    }
+
+    fn name(&self, position: usize) -> String {
+        self.names
+            .get(&position)
+            .cloned()
+            .unwrap_or_else(|| format!("arg{}", position))
+    }
+
+    /// Name of the plural argument, if the source has one
+    fn count(&self) -> Option<&str> {
+        self.names.values().find(|n| *n == "count").map(String::as_str)
+    }
+
+    /// Replace format specifiers with `wrap(name)`, passing literal text through `escape`
+    fn to_named(&self, text: &str, wrap: impl Fn(&str) -> String, escape: impl Fn(&str) -> String) -> String {
+        let specs = specifiers(text);
+        let literal = |s: &str| {
+            if specs.is_empty() {
+                escape(s)
+            } else {
+                escape(&s.replace("%%", "%"))
+            }
+        };
+
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for spec in &specs {
+            out.push_str(&literal(&text[last..spec.range.start]));
+            out.push_str(&wrap(&self.name(spec.position)));
+            last = spec.range.end;
+        }
+        out.push_str(&literal(&text[last..]));
+        out
+    }
+
+    /// Replace `open name close` tokens of known placeholders with format specifiers, passing
+    /// literal text through `unescape`. Reordered arguments get explicit positions.
+    fn to_specifiers(&self, text: &str, open: &str, close: &str, unescape: impl Fn(&str) -> String) -> String {
+        enum Piece {
+            Literal(String),
+            Argument(usize),
+        }
+
+        let positions: HashMap<String, usize> = self
+            .names
+            .iter()
+            .map(|(position, name)| (name.to_lowercase(), *position))
+            .collect();
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find(open) {
+            let after = &rest[start + open.len()..];
+            let token = after.find(close).and_then(|end| {
+                let name = after[..end].trim();
+                let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                let position = positions.get(&name.to_lowercase()).copied().or_else(|| {
+                    name.to_lowercase().strip_prefix("arg").and_then(|n| n.parse().ok())
+                });
+                position.filter(|_| valid).map(|position| (position, end))
+            });
+            match token {
+                Some((position, end)) => {
+                    literal.push_str(&rest[..start]);
+                    pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+                    pieces.push(Piece::Argument(position));
+                    rest = &after[end + close.len()..];
+                }
+                None => {
+                    literal.push_str(&rest[..start + open.len()]);
+                    rest = after;
+                }
+            }
+        }
+        literal.push_str(rest);
+        pieces.push(Piece::Literal(literal));
+
+        let arguments: Vec<usize> = pieces
+            .iter()
+            .filter_map(|p| match p {
+                Piece::Argument(position) => Some(*position),
+                Piece::Literal(_) => None,
+            })
+            .collect();
+        let in_order = arguments.iter().enumerate().all(|(i, p)| *p == i + 1);
+        let positional = self.positional || !in_order;
+
+        let mut out = String::with_capacity(text.len());
+        for piece in pieces {
+            match piece {
+                Piece::Literal(text) if arguments.is_empty() => out.push_str(&unescape(&text)),
+                Piece::Literal(text) => out.push_str(&unescape(&text).replace('%', "%%")),
+                Piece::Argument(position) => {
+                    let format = self.formats.get(&position).map_or("@", String::as_str);
+                    if positional {
+                        out.push_str(&format!("%{}${}", position, format));
+                    } else {
+                        out.push_str(&format!("%{}", format));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// The JSON format of an imported file: `.arb` files are Flutter ARB, `messages.json`
+/// files Chrome messages, and other `.json` files i18next
+pub fn format_for_path(path: &Path) -> Option<Box<dyn CatalogFormat>> {
+    match path.extension()?.to_str()? {
+        "arb" => Some(Box::new(FlutterArb)),
+        "json" if path.file_name()? == "messages.json" => Some(Box::new(ChromeMessages)),
+        "json" => Some(Box::new(I18next)),
+        _ => None,
+    }
+}
+
+fn json_error(e: impl std::fmt::Display) -> TranslatorError {
+    TranslatorError::FileFormatError(format!("Invalid JSON: {}", e))
+}
+
+fn source_value(file: &XCStringsFile, key: &str) -> TranslationValue {
+    file.data.strings[key]
+        .localizations
+        .get(&file.data.source_language)
+        .and_then(|loc| loc.value())
+        .unwrap_or_else(|| TranslationValue::Text(key.to_string()))
+        .collapse_devices()
+}
+
+/// Keys to export for a language with their values: every key for the source language,
+/// falling back to the key itself, and translated translatable keys otherwise
+fn export_values(file: &XCStringsFile, language: &str) -> Vec<(String, TranslationValue)> {
+    let data = &file.data;
+    let is_source = language == data.source_language;
+    let translatable: HashSet<String> = file.get_translatable_keys().into_iter().collect();
+    let mut keys: Vec<&String> = data
+        .strings
+        .iter()
+        .filter(|(key, entry)| !key.trim().is_empty() && !entry.is_stale())
+        .filter(|(key, _)| is_source || translatable.contains(*key))
+        .map(|(key, _)| key)
+        .collect();
+    keys.sort_by(|a, b| compare_keys(a, b));
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let value = data.strings[key].localizations.get(language).and_then(|loc| loc.value());
+            let value = match value {
+                Some(value) => value,
+                None if is_source => TranslationValue::Text(key.clone()),
+                None => return None,
+            };
+            Some((key.clone(), value.collapse_devices()))
+        })
+        .collect()
+}
+
+/// Store imported values under their keys, reporting ids the catalog does not have
+fn apply_values(
+    file: &mut XCStringsFile,
+    language: &str,
+    values: BTreeMap<String, ValueBuilder>,
+    summary: &mut ImportSummary,
+) -> Result<()> {
+    for (key, builder) in values {
+        if let Some(value) = builder.build() {
+            file.add_translation_value(&key, language, &value, UnitState::Translated)?;
+            summary.keys += 1;
+        }
+    }
+    Ok(())
+}
+
+fn parse_object(content: &str) -> Result<Map<String, Value>> {
+    match serde_json::from_str(content).map_err(json_error)? {
+        Value::Object(map) => Ok(map),
+        _ => Err(TranslatorError::FileFormatError("The JSON root is not an object".to_string())),
+    }
+}
+
+fn to_json(value: &Value) -> Result<String> {
+    Ok(serde_json::to_string_pretty(value).map_err(json_error)? + "\n")
+}
+
+/// Language of a file stored as `<language>.json` or `<language>/<namespace>.json`
+fn language_from_path(path: &Path) -> Option<String> {
+    let looks_like_language = |s: &str| {
+        let base = s.split(['-', '_']).next().unwrap_or(s);
+        (2..=3).contains(&base.len()) && base.chars().all(|c| c.is_ascii_lowercase())
+    };
+    let stem = path.file_stem()?.to_str()?;
+    if looks_like_language(stem) {
+        return Some(stem.replace('_', "-"));
+    }
+    let parent = path.parent()?.file_name()?.to_str()?;
+    looks_like_language(parent).then(|| parent.replace('_', "-"))
+}
+
+/// i18next JSON v4: keys nested on `.`, plurals as `key_one`/`key_other`, `{{name}}` placeholders
+pub struct I18next;
+
+impl I18next {
+    /// Nest identifier-like keys such as `settings.title`; keys that are sentences or
+    /// would collide with another key stay at the top level
+    fn insert(map: &mut Map<String, Value>, key: &str, text: String) {
+        let segments: Vec<&str> = key.split('.').collect();
+        let nestable = segments.len() > 1
+            && segments
+                .iter()
+                .all(|s| !s.is_empty() && !s.contains(char::is_whitespace));
+        if nestable && Self::insert_nested(map, &segments, &text) {
+            return;
+        }
+        map.insert(key.to_string(), Value::String(text));
+    }
+
+    fn insert_nested(map: &mut Map<String, Value>, segments: &[&str], text: &str) -> bool {
+        match segments {
+            [] => false,
+            [leaf] if map.contains_key(*leaf) => false,
+            [leaf] => {
+                map.insert(leaf.to_string(), Value::String(text.to_string()));
+                true
+            }
+            [segment, rest @ ..] => match map
+                .entry(segment.to_string())
+                .or_insert_with(|| Value::Object(Map::new()))
+            {
+                Value::Object(object) => Self::insert_nested(object, rest, text),
+                _ => false,
+            },
+        }
+    }
+
+    fn flatten(map: &Map<String, Value>, prefix: &str, out: &mut Vec<(String, String)>) {
+        for (name, value) in map {
+            let path = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            match value {
+                Value::String(text) => out.push((path, text.clone())),
+                Value::Object(object) => Self::flatten(object, &path, out),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl CatalogFormat for I18next {
+    fn name(&self) -> &'static str {
+        "i18next"
+    }
+
+    fn default_directory(&self) -> &'static str {
+        "locales"
+    }
+
+    fn file_path(&self, language: &str) -> PathBuf {
+        PathBuf::from(language).join("translation.json")
+    }
+
+    fn detect_language(&self, path: &Path, _content: &str) -> Option<String> {
+        language_from_path(path)
+    }
+
+    fn export(&self, file: &XCStringsFile, language: &str) -> Result<FormatExport> {
+        let mut map = Map::new();
+        let mut skipped = Vec::new();
+        let wrap = |name: &str| format!("{{{{{}}}}}", name);
+
+        for (key, value) in export_values(file, language) {
+            let placeholders = Placeholders::new(&source_value(file, &key));
+            match value {
+                TranslationValue::Text(text) => {
+                    Self::insert(&mut map, &key, placeholders.to_named(&text, wrap, str::to_string))
+                }
+                TranslationValue::Plural(forms) => {
+                    for category in PLURAL_CATEGORIES {
+                        if let Some(form) = forms.get(category) {
+                            let text = placeholders.to_named(form, wrap, str::to_string);
+                            Self::insert(&mut map, &format!("{}_{}", key, category), text);
+                        }
+                    }
+                }
+                _ => skipped.push(key),
+            }
+        }
+
+        Ok(FormatExport {
+            content: to_json(&Value::Object(map))?,
+            skipped,
+        })
+    }
+
+    fn import(&self, file: &mut XCStringsFile, content: &str, language: &str) -> Result<ImportSummary> {
+        let mut entries = Vec::new();
+        Self::flatten(&parse_object(content)?, "", &mut entries);
+
+        let mut summary = ImportSummary::default();
+        let mut values: BTreeMap<String, ValueBuilder> = BTreeMap::new();
+        for (id, text) in entries {
+            let target = if file.data.strings.contains_key(&id) {
+                Some((id.clone(), String::new()))
+            } else {
+                id.rsplit_once('_')
+                    .filter(|(key, category)| {
+                        PLURAL_CATEGORIES.contains(category) && file.data.strings.contains_key(*key)
+                    })
+                    .map(|(key, category)| (key.to_string(), format!("plural.{}", category)))
+            };
+            let Some((key, path)) = target else {
+                summary.skipped.push(id);
+                continue;
+            };
+
+            let placeholders = Placeholders::new(&source_value(file, &key));
+            let text = placeholders.to_specifiers(&text, "{{", "}}", str::to_string);
+            values.entry(key).or_default().insert(&path, text);
+        }
+
+        apply_values(file, language, values, &mut summary)?;
+        Ok(summary)
+    }
+}
+
+/// Flutter ARB: camelCase message names, ICU plurals and `{name}` placeholders, with
+/// descriptions and placeholder types in the source language's template
+pub struct FlutterArb;
+
+impl FlutterArb {
+    /// Message name of every key: a lowerCamelCase Dart identifier, unique across the catalog
+    fn message_names(keys: &[String]) -> HashMap<String, String> {
+        let mut sorted: Vec<&String> = keys.iter().collect();
+        sorted.sort_by(|a, b| compare_keys(a, b));
+
+        let mut used = HashSet::new();
+        let mut names = HashMap::new();
+        for key in sorted {
+            let is_identifier = key.starts_with(|c: char| c.is_ascii_lowercase())
+                && key.chars().all(|c| c.is_ascii_alphanumeric());
+            let base = if is_identifier {
+                key.clone()
+            } else {
+                // Drop format specifiers so `%lld items` becomes `items`
+                let mut stripped = key.clone();
+                for spec in specifiers(key).iter().rev() {
+                    stripped.replace_range(spec.range.clone(), " ");
+                }
+                // Apostrophes join words, so `It's` becomes `its`
+                let stripped = stripped.replace(['\'', '\u{2019}'], "");
+                let mut name = String::new();
+                for word in stripped.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()) {
+                    let word = word.to_ascii_lowercase();
+                    if name.is_empty() {
+                        name.push_str(&word);
+                    } else {
+                        name.push_str(&word[..1].to_ascii_uppercase());
+                        name.push_str(&word[1..]);
+                    }
+                }
+                if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+                    name = format!("string{}", name);
+                }
+                name
+            };
+
+            let mut name = base.clone();
+            let mut counter = 2;
+            while !used.insert(name.clone()) {
+                name = format!("{}{}", base, counter);
+                counter += 1;
+            }
+            names.insert(key.clone(), name);
+        }
+        names
+    }
+
+    fn placeholder_type(format: &str) -> &'static str {
+        match format.chars().last() {
+            Some('d' | 'i' | 'u' | 'o' | 'x' | 'X') => "int",
+            Some('f' | 'F' | 'e' | 'E' | 'g' | 'G' | 'a' | 'A') => "double",
+            _ => "String",
+        }
+    }
+
+    /// Parse `{count, plural, =0{...} one{...} other{...}}` into its variable and forms
+    fn parse_plural(text: &str) -> Option<(String, BTreeMap<String, String>)> {
+        let inner = text.trim().strip_prefix('{')?.strip_suffix('}')?;
+        let mut parts = inner.splitn(3, ',');
+        let variable = parts.next()?.trim();
+        if parts.next()?.trim() != "plural" {
+            return None;
+        }
+
+        let mut rest = parts.next()?.trim_start();
+        let mut forms = BTreeMap::new();
+        while !rest.is_empty() {
+            let open = rest.find('{')?;
+            let category = match rest[..open].trim() {
+                "=0" => "zero",
+                "=1" => "one",
+                "=2" => "two",
+                category => category,
+            };
+            let mut depth = 0;
+            let mut end = None;
+            for (i, c) in rest[open..].char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = Some(open + i);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let end = end?;
+            if PLURAL_CATEGORIES.contains(&category) {
+                forms.insert(category.to_string(), rest[open + 1..end].to_string());
+            }
+            rest = rest[end + 1..].trim_start();
+        }
+        Some((variable.to_string(), forms))
+    }
+
+    /// Write entries in order, each message followed by its `@` metadata
+    fn to_arb(entries: &[(String, Value)]) -> Result<String> {
+        let mut out = String::from("{\n");
+        for (i, (name, value)) in entries.iter().enumerate() {
+            let value = serde_json::to_string_pretty(value).map_err(json_error)?;
+            out.push_str(&format!(
+                "  {}: {}",
+                Value::String(name.clone()),
+                value.replace('\n', "\n  ")
+            ));
+            out.push_str(if i + 1 < entries.len() { ",\n" } else { "\n" });
+        }
+        out.push_str("}\n");
+        Ok(out)
+    }
+}
+
+impl CatalogFormat for FlutterArb {
+    fn name(&self) -> &'static str {
+        "Flutter ARB"
+    }
+
+    fn default_directory(&self) -> &'static str {
+        "l10n"
+    }
+
+    fn file_path(&self, language: &str) -> PathBuf {
+        PathBuf::from(format!("app_{}.arb", language.replace('-', "_")))
+    }
+
+    fn detect_language(&self, path: &Path, content: &str) -> Option<String> {
+        let locale = parse_object(content)
+            .ok()
+            .and_then(|map| map.get("@@locale")?.as_str().map(str::to_string))
+            .or_else(|| {
+                let stem = path.file_stem()?.to_str()?;
+                Some(stem.strip_prefix("app_").unwrap_or(stem).to_string())
+            })?;
+        Some(locale.replace('_', "-"))
+    }
+
+    fn export(&self, file: &XCStringsFile, language: &str) -> Result<FormatExport> {
+        let names = Self::message_names(&file.get_keys());
+        let is_source = language == file.data.source_language;
+        let wrap = |name: &str| format!("{{{}}}", name);
+        let mut entries = vec![("@@locale".to_string(), json!(language.replace('-', "_")))];
+        let mut skipped = Vec::new();
+
+        for (key, value) in export_values(file, language) {
+            let placeholders = Placeholders::new(&source_value(file, &key));
+            let message = match value {
+                TranslationValue::Text(text) => placeholders.to_named(&text, wrap, str::to_string),
+                TranslationValue::Plural(forms) => {
+                    let count = placeholders.count().unwrap_or("count");
+                    let branches: Vec<String> = PLURAL_CATEGORIES
+                        .iter()
+                        .filter_map(|category| {
+                            let form = forms.get(*category)?;
+                            Some(format!("{}{{{}}}", category, placeholders.to_named(form, wrap, str::to_string)))
+                        })
+                        .collect();
+                    format!("{{{}, plural, {}}}", count, branches.join(" "))
+                }
+                _ => {
+                    skipped.push(key);
+                    continue;
+                }
+            };
+            let name = names[&key].clone();
+            entries.push((name.clone(), Value::String(message)));
+
+            if !is_source {
+                continue;
+            }
+            let mut metadata = Map::new();
+            if let Some(comment) = &file.data.strings[&key].comment {
+                metadata.insert("description".to_string(), json!(comment));
+            }
+            if !placeholders.names.is_empty() {
+                let types: Map<String, Value> = placeholders
+                    .names
+                    .iter()
+                    .map(|(position, name)| {
+                        let format = &placeholders.formats[position];
+                        (name.clone(), json!({ "type": Self::placeholder_type(format) }))
+                    })
+                    .collect();
+                metadata.insert("placeholders".to_string(), Value::Object(types));
+            }
+            if !metadata.is_empty() {
+                entries.push((format!("@{}", name), Value::Object(metadata)));
+            }
+        }
+
+        Ok(FormatExport {
+            content: Self::to_arb(&entries)?,
+            skipped,
+        })
+    }
+
+    fn import(&self, file: &mut XCStringsFile, content: &str, language: &str) -> Result<ImportSummary> {
+        let keys: HashMap<String, String> = Self::message_names(&file.get_keys())
+            .into_iter()
+            .map(|(key, name)| (name, key))
+            .collect();
+
+        let mut summary = ImportSummary::default();
+        let mut values: BTreeMap<String, ValueBuilder> = BTreeMap::new();
+        for (name, message) in parse_object(content)? {
+            let Some(text) = message.as_str().filter(|_| !name.starts_with('@')) else {
+                continue;
+            };
+            let Some(key) = keys.get(&name) else {
+                summary.skipped.push(name);
+                continue;
+            };
+
+            let placeholders = Placeholders::new(&source_value(file, key));
+            let builder = values.entry(key.clone()).or_default();
+            match Self::parse_plural(text) {
+                Some((variable, forms)) => {
+                    for (category, form) in forms {
+                        // `#` stands for the plural argument inside a branch
+                        let form = form.replace('#', &format!("{{{}}}", variable));
+                        let form = placeholders.to_specifiers(&form, "{", "}", str::to_string);
+                        builder.insert(&format!("plural.{}", category), form);
+                    }
+                }
+                None => {
+                    builder.insert("", placeholders.to_specifiers(text, "{", "}", str::to_string));
+                }
+            }
+        }
+
+        apply_values(file, language, values, &mut summary)?;
+        Ok(summary)
+    }
+}
+
+/// Chrome extension `_locales/<locale>/messages.json`: `$NAME$` placeholders that point at
+/// `$1`-style arguments. Plurals have no equivalent.
+pub struct ChromeMessages;
+
+impl ChromeMessages {
+    fn locale(language: &str) -> String {
+        match language {
+            "zh-Hans" => "zh_CN".to_string(),
+            "zh-Hant" => "zh_TW".to_string(),
+            _ => language.replace('-', "_"),
+        }
+    }
+}
+
+impl CatalogFormat for ChromeMessages {
+    fn name(&self) -> &'static str {
+        "Chrome messages.json"
+    }
+
+    fn default_directory(&self) -> &'static str {
+        "_locales"
+    }
+
+    fn file_path(&self, language: &str) -> PathBuf {
+        PathBuf::from(Self::locale(language)).join("messages.json")
+    }
+
+    fn detect_language(&self, path: &Path, _content: &str) -> Option<String> {
+        let locale = path.parent()?.file_name()?.to_str()?;
+        Some(match locale {
+            "zh_CN" => "zh-Hans".to_string(),
+            "zh_TW" => "zh-Hant".to_string(),
+            _ => locale.replace('_', "-"),
+        })
+    }
+
+    fn export(&self, file: &XCStringsFile, language: &str) -> Result<FormatExport> {
+        let names = resource_names(&file.get_keys());
+        let is_source = language == file.data.source_language;
+        let mut map = Map::new();
+        let mut skipped = Vec::new();
+
+        for (key, value) in export_values(file, language) {
+            let TranslationValue::Text(text) = value else {
+                skipped.push(key);
+                continue;
+            };
+            let placeholders = Placeholders::new(&source_value(file, &key));
+            let used: BTreeMap<usize, String> = specifiers(&text)
+                .iter()
+                .map(|spec| (spec.position, placeholders.name(spec.position)))
+                .collect();
+
+            let mut message = Map::new();
+            message.insert(
+                "message".to_string(),
+                json!(placeholders.to_named(
+                    &text,
+                    |name| format!("${}$", name.to_uppercase()),
+                    |literal| literal.replace('$', "$$")
+                )),
+            );
+            if let Some(comment) = file.data.strings[&key].comment.as_ref().filter(|_| is_source) {
+                message.insert("description".to_string(), json!(comment));
+            }
+            if !used.is_empty() {
+                let arguments: Map<String, Value> = used
+                    .into_iter()
+                    .map(|(position, name)| (name, json!({ "content": format!("${}", position) })))
+                    .collect();
+                message.insert("placeholders".to_string(), Value::Object(arguments));
+            }
+            map.insert(names[&key].clone(), Value::Object(message));
+        }
+
+        Ok(FormatExport {
+            content: to_json(&Value::Object(map))?,
+            skipped,
+        })
+    }
+
+    fn import(&self, file: &mut XCStringsFile, content: &str, language: &str) -> Result<ImportSummary> {
+        let keys: HashMap<String, String> = resource_names(&file.get_keys())
+            .into_iter()
+            .map(|(key, name)| (name, key))
+            .collect();
+
+        let mut summary = ImportSummary::default();
+        let mut values: BTreeMap<String, ValueBuilder> = BTreeMap::new();
+        for (name, message) in parse_object(content)? {
+            let Some(text) = message.get("message").and_then(Value::as_str) else {
+                continue;
+            };
+            // Message names are case-insensitive
+            let Some(key) = keys.get(&name.to_lowercase()) else {
+                summary.skipped.push(name);
+                continue;
+            };
+
+            // Placeholders map names to `$1`-style arguments, which may differ from ours
+            let mut placeholders = Placeholders::new(&source_value(file, key));
+            if let Some(Value::Object(arguments)) = message.get("placeholders") {
+                for (placeholder, argument) in arguments {
+                    let position = argument
+                        .get("content")
+                        .and_then(Value::as_str)
+                        .and_then(|c| c.strip_prefix('$'))
+                        .and_then(|n| n.parse::<usize>().ok());
+                    if let Some(position) = position {
+                        placeholders.names.insert(position, placeholder.clone());
+                    }
+                }
+            }
+
+            let text = placeholders.to_specifiers(text, "$", "$", |literal| literal.replace("$$", "$"));
+            values.entry(key.clone()).or_default().insert("", text);
+        }
+
+        apply_values(file, language, values, &mut summary)?;
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xcstrings::tests::load_catalog;
+
+    const CATALOG: &str = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "%lld items" : {
+      "comment" : "Items in the cart",
+      "localizations" : {
+        "en" : {
+          "variations" : {
+            "plural" : {
+              "one" : { "stringUnit" : { "state" : "translated", "value" : "%lld item" } },
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld items" } }
+            }
+          }
+        },
+        "de" : {
+          "variations" : {
+            "plural" : {
+              "one" : { "stringUnit" : { "state" : "translated", "value" : "%lld Artikel" } },
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld Artikel" } }
+            }
+          }
+        }
+      }
+    },
+    "%@ sent %@ $5" : {
+      "localizations" : {
+        "de" : { "stringUnit" : { "state" : "translated", "value" : "%2$@ von %1$@ für $5" } }
+      }
+    },
+    "settings.title" : {
+      "localizations" : {
+        "en" : { "stringUnit" : { "state" : "translated", "value" : "Settings" } },
+        "de" : { "stringUnit" : { "state" : "translated", "value" : "Einstellungen" } }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#;
+
+    fn assert_round_trip(format: &dyn CatalogFormat, file: &XCStringsFile, language: &str) {
+        let (_dir, mut copy) = load_catalog(CATALOG);
+        for entry in copy.data.strings.values_mut() {
+            entry.localizations.remove(language);
+        }
+        let export = format.export(file, language).unwrap();
+        let summary = format.import(&mut copy, &export.content, language).unwrap();
+        assert!(summary.skipped.is_empty(), "{}: {:?}", format.name(), summary.skipped);
+        for key in file.get_keys() {
+            assert_eq!(
+                copy.data.strings[&key].localizations.get(language).and_then(|l| l.value()),
+                file.data.strings[&key].localizations.get(language).and_then(|l| l.value()),
+                "{}: {}",
+                format.name(),
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let source = TranslationValue::Text("%@ has %lld new %1$@".to_string());
+        let placeholders = Placeholders::new(&source);
+        assert_eq!(
+            placeholders.to_named("%2$lld für %1$@ (100%%)", |n| format!("{{{}}}", n), str::to_string),
+            "{arg2} für {arg1} (100%)"
+        );
+        assert_eq!(
+            placeholders.to_specifiers("{arg2} für {arg1} (100%)", "{", "}", str::to_string),
+            "%2$lld für %1$@ (100%%)"
+        );
+
+        let plural = Placeholders::new(&TranslationValue::Plural(
+            [("other".to_string(), "%@: %lld items".to_string())].into_iter().collect(),
+        ));
+        assert_eq!(plural.count(), Some("count"));
+        assert_eq!(
+            plural.to_named("%@: %lld items", |n| format!("{{{{{}}}}}", n), str::to_string),
+            "{{arg1}}: {{count}} items"
+        );
+    }
+
+    #[test]
+    fn test_i18next_export() {
+        let (_dir, file) = load_catalog(CATALOG);
+        let export = I18next.export(&file, "de").unwrap();
+        let json: Value = serde_json::from_str(&export.content).unwrap();
+        assert_eq!(json["%lld items_one"], "{{count}} Artikel");
+        assert_eq!(json["%@ sent %@ $5"], "{{arg2}} von {{arg1}} für $5");
+        assert_eq!(json["settings"]["title"], "Einstellungen");
+        assert_round_trip(&I18next, &file, "de");
+    }
+
+    #[test]
+    fn test_arb_export() {
+        let (_dir, file) = load_catalog(CATALOG);
+        let template = FlutterArb.export(&file, "en").unwrap().content;
+        assert!(template.starts_with("{\n  \"@@locale\": \"en\",\n"));
+        assert!(template.contains("  \"items\": \"{count, plural, one{{count} item} other{{count} items}}\",\n  \"@items\": {\n    \"description\": \"Items in the cart\","));
+        assert!(template.contains("\"type\": \"int\""));
+        assert_eq!(
+            FlutterArb.detect_language(Path::new("app_pt_BR.arb"), &template).as_deref(),
+            Some("en")
+        );
+
+        let (_dir, mut copy) = load_catalog(CATALOG);
+        let arb = r#"{"@@locale": "de", "items": "{count, plural, =1{Ein Artikel} other{# Artikel}}"}"#;
+        FlutterArb.import(&mut copy, arb, "de").unwrap();
+        let forms = copy.data.strings["%lld items"].localizations["de"].plural_forms();
+        assert_eq!(forms["one"], "Ein Artikel");
+        assert_eq!(forms["other"], "%lld Artikel");
+        assert_round_trip(&FlutterArb, &file, "de");
+    }
+
+    #[test]
+    fn test_chrome_export() {
+        let (_dir, file) = load_catalog(CATALOG);
+        let export = ChromeMessages.export(&file, "de").unwrap();
+        assert_eq!(export.skipped, vec!["%lld items".to_string()]);
+        let json: Value = serde_json::from_str(&export.content).unwrap();
+        let message = &json["sent_5"];
+        assert_eq!(message["message"], "$ARG2$ von $ARG1$ für $$5");
+        assert_eq!(message["placeholders"]["arg2"]["content"], "$2");
+        assert_eq!(
+            ChromeMessages.file_path("zh-Hans"),
+            PathBuf::from("zh_CN").join("messages.json")
+        );
+
+        let (_dir, mut copy) = load_catalog(CATALOG);
+        let summary = ChromeMessages.import(&mut copy, &export.content, "de").unwrap();
+        assert_eq!(summary.keys, 2);
+        assert_eq!(
+            copy.data.strings["%@ sent %@ $5"].localizations["de"].string_unit.as_ref().unwrap().value,
+            "%2$@ von %1$@ für $5"
+        );
+    }
+}
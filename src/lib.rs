@@ -1,11 +1,30 @@
+pub mod android;
 pub mod ai_provider;
+pub mod backend;
 pub mod ascii_art;
 pub mod config;
 pub mod error;
+pub mod format_specifiers;
+pub mod gettext;
+pub mod glossary;
+pub mod json_formats;
 pub mod key_mappings;
 pub mod onboarding;
+pub mod plural_rules;
+pub mod prompts;
+pub mod rate_limit;
+pub mod retry;
+pub mod spreadsheet;
+pub mod strings_file;
+pub mod stringsdict;
+pub mod tmx;
+pub mod translation_memory;
 pub mod translator;
 pub mod ui;
+pub mod workspace;
+pub mod xliff;
+pub mod xml;
+pub mod xcode_json;
 pub mod xcstrings;
 
 use clap::ValueEnum;
@@ -1,27 +1,51 @@
+mod android;
 mod ascii_art;
+mod backend;
 mod config;
 mod error;
+mod format_specifiers;
+mod gettext;
+mod glossary;
+mod json_formats;
 mod key_mappings;
 mod onboarding;
+mod plural_rules;
+mod prompts;
+mod rate_limit;
+mod retry;
+mod spreadsheet;
+mod strings_file;
+mod stringsdict;
+mod tmx;
+mod translation_memory;
 mod translator;
 mod ui;
+mod xcode_json;
+mod workspace;
+mod xliff;
+mod xml;
 mod xcstrings;
 mod ai_provider;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand, ValueEnum, CommandFactory};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::env;
 use std::fs;
 use colored::*;
 use dialoguer::{theme::ColorfulTheme, Confirm, MultiSelect, Select};
 use chrono::DateTime;
 
+use crate::ai_provider::AIProvider;
 use crate::config::Config;
+use crate::glossary::{Glossary, GLOSSARY_FILE};
 use crate::onboarding::Onboarding;
+use crate::prompts::Prompts;
+use crate::spreadsheet::SheetFormat;
+use crate::translation_memory::{MemoryUpdate, TranslationMemory};
 use crate::translator::Translator;
 use crate::ui::UI;
-use crate::xcstrings::XCStringsFile;
+use crate::xcstrings::{CatalogFormat, TranslationFilter, UnitState, XCStringsFile};
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum TranslationMode {
@@ -31,6 +55,30 @@ pub enum TranslationMode {
     Fresh,
 }
 
+#[derive(Debug, Clone, ValueEnum)]
+enum ExportFormat {
+    /// XLIFF 1.2, as written by Xcode's Export Localizations
+    Xliff,
+    /// Comma-separated spreadsheet
+    Csv,
+    /// Tab-separated spreadsheet
+    Tsv,
+    /// Android res/values*/strings.xml
+    Android,
+    /// Gettext PO file for one language
+    Po,
+    /// Gettext POT template without translations
+    Pot,
+    /// i18next JSON, one <language>/translation.json per language
+    I18next,
+    /// Flutter ARB, one app_<language>.arb per language
+    Arb,
+    /// Chrome extension _locales/<locale>/messages.json
+    Chrome,
+    /// TMX 1.4 translation memory of every approved translation
+    Tmx,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Translate strings to target language
@@ -40,19 +88,28 @@ enum Commands {
         #[arg(help = "Target language code (ja, zh-Hans, zh-Hant, ko, fr, de, es, etc.)")]
         language: Option<String>,
 
-        /// Path to .xcstrings file
+        /// Path to .xcstrings file or legacy .strings file
         #[arg(
             short,
             long,
-            help = "Path to .xcstrings file (auto-detected if not specified)"
+            help = "Path to .xcstrings file, or a source-language .strings/.stringsdict file (auto-detected if not specified)"
         )]
         file: Option<PathBuf>,
 
-        /// OpenRouter API key
+        /// Catalogs to translate
+        #[arg(
+            short = 'c',
+            long = "catalog",
+            value_delimiter = ',',
+            help = "Only translate catalogs matching this file name or path fragment (repeatable)"
+        )]
+        catalogs: Vec<String>,
+
+        /// API key of the provider
         #[arg(
             short = 'k',
             long,
-            help = "OpenRouter API key (or set OPENROUTER_API_KEY env var)"
+            help = "API key of the configured provider (or set OPENROUTER_API_KEY, OPENAI_API_KEY, ANTHROPIC_API_KEY or GEMINI_API_KEY)"
         )]
         api_key: Option<String>,
 
@@ -65,25 +122,184 @@ enum Commands {
         )]
         mode: TranslationMode,
 
-        /// Base URL for OpenRouter API
+        /// Base URL of the provider's API
         #[arg(
             long,
-            default_value = "https://openrouter.ai/api/v1",
-            help = "OpenRouter API base URL"
+            help = "API base URL (default: the configured provider's, or OpenRouter's without a config)"
         )]
-        base_url: String,
+        base_url: Option<String>,
 
         /// Model to use for translation
         #[arg(
             long,
-            default_value = "anthropic/claude-3.5-sonnet",
-            help = "AI model to use for translation"
+            help = "AI model to use for translation (default: the configured provider's model)"
         )]
-        model: String,
+        model: Option<String>,
 
         /// Skip interactive mode (auto-translate all)
         #[arg(long, help = "Skip interactive mode and auto-translate all keys")]
         auto: bool,
+
+        /// State written on AI translations
+        #[arg(
+            long,
+            value_enum,
+            help = "State written on AI translations (default from config: needs_review)"
+        )]
+        state: Option<UnitState>,
+
+        /// Requests sent at once during batch translation
+        #[arg(
+            long,
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "Requests sent to the provider at once during batch translation (default from config: 4)"
+        )]
+        concurrency: Option<u16>,
+
+        /// Plain strings packed into one request during batch translation
+        #[arg(
+            long,
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "Plain strings packed into one request during batch translation; 1 sends each key alone (default from config: 10)"
+        )]
+        keys_per_request: Option<u16>,
+
+        /// Glossary of mandated term translations
+        #[arg(
+            long,
+            help = "Glossary file of mandated term translations (default: rosetta-glossary.json at the workspace root)"
+        )]
+        glossary: Option<PathBuf>,
+
+        /// Unit states that supplement mode retranslates
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            default_values_t = [UnitState::New, UnitState::Stale],
+            help = "Comma-separated unit states that supplement mode retranslates"
+        )]
+        retranslate: Vec<UnitState>,
+
+        /// Translate keys Xcode marked as stale
+        #[arg(long, help = "Also translate keys Xcode marked as stale (removed from source code)")]
+        include_stale: bool,
+    },
+
+    /// Review AI translations and mark them as translated
+    #[command(arg_required_else_help = true)]
+    Review {
+        /// Language to review
+        #[arg(help = "Language code to review (ja, zh-Hans, ko, etc.)")]
+        language: String,
+
+        /// Path to .xcstrings file
+        #[arg(
+            short,
+            long,
+            help = "Path to .xcstrings file (auto-detected if not specified)"
+        )]
+        file: Option<PathBuf>,
+
+        /// Approve everything without prompting
+        #[arg(long, help = "Mark every needs_review unit as translated without prompting")]
+        all: bool,
+    },
+
+    /// List and delete keys Xcode marked as stale
+    Prune {
+        /// Path to .xcstrings file
+        #[arg(
+            short,
+            long,
+            help = "Path to .xcstrings file (auto-detected if not specified)"
+        )]
+        file: Option<PathBuf>,
+
+        /// Only list stale keys
+        #[arg(long, help = "List stale keys without deleting them")]
+        dry_run: bool,
+
+        /// Delete without asking for confirmation
+        #[arg(short, long, help = "Delete stale keys without asking for confirmation")]
+        yes: bool,
+    },
+
+    /// Export strings for translation outside Rosetta
+    #[command(arg_required_else_help = true)]
+    Export {
+        /// Language to export
+        #[arg(help = "Target language code to export (ja, zh-Hans, ko, etc.); csv/tsv export every language when omitted")]
+        language: Option<String>,
+
+        /// Export format
+        #[arg(long, value_enum, default_value = "xliff", help = "Export format")]
+        format: ExportFormat,
+
+        /// Path to .xcstrings file
+        #[arg(
+            short,
+            long,
+            help = "Path to .xcstrings file (default: every catalog in the workspace)"
+        )]
+        file: Option<PathBuf>,
+
+        /// Catalogs to export
+        #[arg(
+            short = 'c',
+            long = "catalog",
+            value_delimiter = ',',
+            help = "Only export catalogs matching this file name or path fragment (repeatable)"
+        )]
+        catalogs: Vec<String>,
+
+        /// Where to write the export
+        #[arg(
+            short,
+            long,
+            help = "Output file; for xliff, a path ending in .xcloc writes an Xcode localization bundle; for android and the JSON formats, a directory (default: <language>.xliff, <catalog>.csv, <language>.po, <catalog>.pot, <catalog>.tmx, res, locales, l10n or _locales)"
+        )]
+        output: Option<PathBuf>,
+    },
+
+    /// Import translations from an XLIFF file, .xcloc bundle, spreadsheet, PO, JSON or ARB file, or Android res directory, or a TMX file into the translation memory
+    #[command(arg_required_else_help = true)]
+    Import {
+        /// File to import
+        #[arg(help = "XLIFF file, .xcloc bundle, .csv/.tsv spreadsheet, .po, i18next .json, .arb or Chrome messages.json file, Android res directory, or .tmx translation memory to import")]
+        input: PathBuf,
+
+        /// Catalog to import into
+        #[arg(
+            short,
+            long,
+            help = "Path to .xcstrings file (default: matched by each <file>'s original path)"
+        )]
+        file: Option<PathBuf>,
+
+        /// Only report what would be imported
+        #[arg(long, help = "Show what would be imported without writing")]
+        dry_run: bool,
+    },
+
+    /// Migrate a legacy .strings/.stringsdict table into a String Catalog
+    #[command(arg_required_else_help = true)]
+    Migrate {
+        /// Source language file of the table
+        #[arg(help = "Source language .strings or .stringsdict file (e.g. en.lproj/Localizable.strings)")]
+        file: PathBuf,
+
+        /// Where to write the catalog
+        #[arg(
+            short,
+            long,
+            help = "Path of the new catalog (default: <Table>.xcstrings next to the .lproj directories)"
+        )]
+        output: Option<PathBuf>,
+
+        /// Replace an existing catalog
+        #[arg(long, help = "Overwrite the output file if it already exists")]
+        force: bool,
     },
 
     /// Clean up backup files
@@ -122,34 +338,43 @@ struct Cli {
     command: Option<Commands>,
 }
 
-fn find_project_root() -> Option<PathBuf> {
-    let mut current_dir = env::current_dir().ok()?;
-    
-    loop {
-        let xcstrings_path = current_dir.join("Shared/Resources/Localizable.xcstrings");
-        if xcstrings_path.exists() {
-            return Some(xcstrings_path);
-        }
-        
-        if !current_dir.pop() {
-            break;
+/// Find every catalog in the workspace containing the current directory, falling back
+/// to the configured project path when the workspace has none
+fn find_catalogs() -> Result<(PathBuf, Vec<PathBuf>)> {
+    let current_dir = env::current_dir()?;
+    let root = workspace::find_workspace_root(&current_dir);
+    let catalogs = workspace::discover_catalogs(&root);
+    if !catalogs.is_empty() {
+        return Ok((root, catalogs));
+    }
+
+    if let Some(project_path) = Config::load()?.and_then(|config| config.project_path) {
+        let project_root = PathBuf::from(project_path);
+        let catalogs = workspace::discover_catalogs(&project_root);
+        if !catalogs.is_empty() {
+            return Ok((project_root, catalogs));
         }
     }
-    
-    None
+
+    Ok((root, Vec::new()))
 }
 
-fn get_api_key(provided_key: Option<String>) -> Result<String> {
-    if let Some(key) = provided_key {
-        return Ok(key);
+/// Fill in the provider's API key from its environment variable when none is configured
+fn get_api_key(provider: AIProvider) -> Result<AIProvider> {
+    if !provider.api_key().is_empty() {
+        return Ok(provider);
     }
     
-    if let Ok(key) = env::var("OPENROUTER_API_KEY") {
-        return Ok(key);
+    if let Ok(key) = env::var(provider.api_key_variable()) {
+        if !key.is_empty() {
+            return Ok(provider.with_api_key(key));
+        }
     }
     
     anyhow::bail!(
-        "No API key found. Please:\n  • Use --api-key parameter\n  • Set OPENROUTER_API_KEY environment variable"
+        "No {} API key found. Please:\n  • Use --api-key parameter\n  • Set {} environment variable\n  • Run 'rosetta setup'",
+        provider.name(),
+        provider.api_key_variable()
     );
 }
 
@@ -161,27 +386,32 @@ async fn main() -> Result<()> {
         Some(Commands::Translate {
             language,
             file,
+            catalogs,
             api_key,
             mode,
             base_url,
             model,
             auto,
+            state,
+            concurrency,
+            keys_per_request,
+            glossary,
+            retranslate,
+            include_stale,
         }) => {
             // Load config (or default) and merge CLI overrides.
             let mut config = Config::load()?.unwrap_or_default();
 
-            // Determine effective values with CLI having priority.
-            let effective_api_key = api_key.unwrap_or_else(|| config.api_key.clone());
-            let effective_base_url = if base_url == "https://openrouter.ai/api/v1" {
-                config.base_url.clone()
-            } else {
-                base_url
-            };
-            let effective_model = if model == "anthropic/claude-3.5-sonnet" {
-                config.model.clone()
-            } else {
-                model
-            };
+            // The configured provider picks the backend (OpenRouter until `rosetta setup` runs),
+            // with CLI values having priority.
+            let mut provider = config.translation_provider();
+            if let Some(key) = api_key {
+                provider = provider.with_api_key(key);
+            }
+            if let Some(model) = model {
+                provider = provider.with_model(model);
+            }
+            let effective_base_url = base_url.unwrap_or_else(|| config.base_url.clone());
 
             // Resolve language (CLI > config default).
             let language_value = language.as_ref().unwrap_or(&config.default_language).clone();
@@ -191,17 +421,47 @@ async fn main() -> Result<()> {
                 config.update_default_language(language_value.clone())?;
             }
 
+            config.prompt_templates.validate().map_err(anyhow::Error::msg)?;
+
+            let options = TranslateOptions {
+                language: language_value,
+                mode,
+                auto,
+                ai_state: state.unwrap_or(config.ai_translation_state),
+                concurrency: concurrency.map_or(config.concurrency, usize::from),
+                keys_per_request: keys_per_request.map_or(config.keys_per_request, usize::from),
+                prompts: Prompts::new(config.app_profile.clone(), config.prompt_templates.clone()),
+                glossary,
+                filter: TranslationFilter {
+                    retranslate_states: retranslate,
+                    include_stale_keys: include_stale,
+                },
+            };
+
             translate_command(
                 file,
-                Some(effective_api_key),
-                mode,
+                catalogs,
+                provider,
                 effective_base_url,
-                effective_model,
-                auto,
-                language_value,
+                options,
             )
             .await?;
         }
+        Some(Commands::Review { language, file, all }) => {
+            review_command(file, language, all).await?;
+        }
+        Some(Commands::Prune { file, dry_run, yes }) => {
+            prune_command(file, dry_run, yes)?;
+        }
+        Some(Commands::Export { language, format, file, catalogs, output }) => {
+            export_command(language, format, file, catalogs, output)?;
+        }
+        Some(Commands::Import { input, file, dry_run }) => {
+            import_command(input, file, dry_run)?;
+        }
+        Some(Commands::Migrate { file, output, force }) => {
+            migrate_command(file, output, force)?;
+        }
         Some(Commands::Clean { directory }) => {
             clean_command(directory)?;
         }
@@ -227,7 +487,7 @@ async fn main() -> Result<()> {
         }
         Some(Commands::Test) => {
             if let Some(config) = Config::load()? {
-                match config.ai_provider.test_connection().await {
+                match config.translation_provider().test_connection().await {
                     Ok(true) => println!("{}", "Connection test successful!".green()),
                     Ok(false) => println!("{}", "Connection test failed.".red()),
                     Err(e) => println!("{} {}", "Error:".red(), e),
@@ -253,13 +513,13 @@ async fn main() -> Result<()> {
             // No command provided, check if config exists
             if Config::load()?.is_none() {
                 // No config found, run onboarding
-                if let Some(config) = Onboarding::start().await? {
-                    let config = Config::new(
-                        config.api_key,
-                        config.default_language,
-                        config.project_path,
+                if let Some(ob) = Onboarding::start().await? {
+                    let mut config = Config::new(
+                        ob.api_key,
+                        ob.default_language,
+                        ob.project_path,
                     );
-                    config.save()?;
+                    config.update_ai_provider(ob.ai_provider)?;
                     println!("\n✨ Configuration saved successfully!");
                 }
             } else {
@@ -267,6 +527,11 @@ async fn main() -> Result<()> {
                 println!("\nUsage: rosetta <COMMAND>");
                 println!("\nCommands:");
                 println!("  translate    Translate strings to target language");
+                println!("  review       Review AI translations and mark them as translated");
+                println!("  prune        List and delete keys Xcode marked as stale");
+                println!("  export       Export strings for translation outside Rosetta");
+                println!("  import       Import translations from XLIFF, spreadsheets, PO, JSON, ARB, Android resources or TMX");
+                println!("  migrate      Migrate a legacy .strings/.stringsdict table into a String Catalog");
                 println!("  clean        Clean up backup files");
                 println!("  setup       Run initial setup and configuration");
                 println!("  config      Show configuration settings");
@@ -280,60 +545,243 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn translate_command(
+/// Use the given catalog path, or auto-detect the workspace's catalog, asking which
+/// one to use when there are several
+fn resolve_file_path(file: Option<PathBuf>) -> Result<PathBuf> {
+    if let Some(path) = file {
+        if !path.exists() {
+            anyhow::bail!("File not found: {}", path.display());
+        }
+        return Ok(path);
+    }
+
+    UI::print_step("Auto-detecting catalogs...");
+    let (root, catalogs) = find_catalogs()?;
+    let file_path = match catalogs.len() {
+        0 => anyhow::bail!("Could not find any .xcstrings file. Use --file to specify path."),
+        1 => catalogs[0].clone(),
+        _ => {
+            let names: Vec<String> = catalogs
+                .iter()
+                .map(|path| workspace::display_name(path, &root))
+                .collect();
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Select a catalog")
+                .items(&names)
+                .default(0)
+                .interact()?;
+            catalogs[selection].clone()
+        }
+    };
+
+    UI::print_success(&format!("Found: {}", file_path.display()));
+    Ok(file_path)
+}
+
+/// Resolve the catalogs to translate: the given file, the catalogs matching the
+/// selectors, or every catalog in the workspace (chosen interactively unless `auto`)
+fn resolve_catalogs(
     file: Option<PathBuf>,
-    api_key: Option<String>,
-    mode: TranslationMode,
-    base_url: String,
-    model: String,
+    selectors: &[String],
     auto: bool,
+) -> Result<(PathBuf, Vec<PathBuf>)> {
+    if let Some(path) = file {
+        if !path.exists() {
+            anyhow::bail!("File not found: {}", path.display());
+        }
+        let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        return Ok((root, vec![path]));
+    }
+
+    UI::print_step("Auto-detecting catalogs...");
+    let (root, all_catalogs) = find_catalogs()?;
+    if all_catalogs.is_empty() {
+        anyhow::bail!("Could not find any .xcstrings file. Use --file to specify path.");
+    }
+
+    let catalogs = workspace::filter_catalogs(&all_catalogs, selectors);
+    if catalogs.is_empty() {
+        anyhow::bail!("No catalog matches: {}", selectors.join(", "));
+    }
+
+    UI::print_success(&format!(
+        "Found {} catalogs in {}",
+        all_catalogs.len(),
+        root.display()
+    ));
+
+    if catalogs.len() == 1 || !selectors.is_empty() || auto {
+        return Ok((root, catalogs));
+    }
+
+    let names: Vec<String> = catalogs
+        .iter()
+        .map(|path| workspace::display_name(path, &root))
+        .collect();
+    let defaults = vec![true; names.len()];
+    let selection = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Select catalogs to translate (space to toggle, enter to confirm)")
+        .items(&names)
+        .defaults(&defaults)
+        .interact()?;
+
+    let selected = selection.into_iter().map(|i| catalogs[i].clone()).collect();
+    Ok((root, selected))
+}
+
+async fn review_command(file: Option<PathBuf>, language: String, all: bool) -> Result<()> {
+    UI::print_banner();
+
+    let file_path = resolve_file_path(file)?;
+
+    UI::print_step("Loading localization file...");
+    let mut xcstrings = XCStringsFile::load(&file_path)?;
+
+    let keys = xcstrings.get_keys_in_state(&language, UnitState::NeedsReview);
+    if keys.is_empty() {
+        UI::print_success(&format!("Nothing needs review for language '{}'", language));
+        return Ok(());
+    }
+
+    UI::print_info("Language", &language);
+    UI::print_info("Keys", &keys.len().to_string());
+    println!();
+
+    // Approved translations go into the translation memory
+    let root = file_path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let glossary = load_glossary(None, &root)?;
+    let mut memory = TranslationMemory::load()?;
+
+    if all {
+        let mut units = 0;
+        for key in &keys {
+            units += xcstrings.mark_reviewed(key, &language)?;
+            UI::remember_reviewed(&xcstrings, &mut memory, &glossary, key, &language, &file_path);
+        }
+        xcstrings.save()?;
+        memory.save()?;
+        UI::print_success(&format!("Marked {} units in {} keys as translated", units, keys.len()));
+    } else {
+        UI::interactive_review(&mut xcstrings, &mut memory, &glossary, &keys, &language, &file_path).await?;
+    }
+
+    UI::print_info("Output", &file_path.display().to_string());
+
+    Ok(())
+}
+
+/// The glossary given on the command line, or the workspace's glossary file; empty without one
+fn load_glossary(path: Option<PathBuf>, root: &Path) -> Result<Glossary> {
+    let path = path.or_else(|| Some(workspace::find_workspace_root(root).join(GLOSSARY_FILE)).filter(|p| p.exists()));
+    let Some(path) = path else {
+        return Ok(Glossary::default());
+    };
+    let glossary = Glossary::load(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to load glossary {}: {}", path.display(), e))?;
+    if glossary.is_empty() {
+        UI::print_warning(&format!("Glossary {} has no terms", path.display()));
+    } else {
+        UI::print_info("Glossary", &format!("{} terms", glossary.len()));
+    }
+    Ok(glossary)
+}
+
+/// Options shared by every catalog in one `translate` run
+struct TranslateOptions {
     language: String,
+    mode: TranslationMode,
+    auto: bool,
+    ai_state: UnitState,
+    concurrency: usize,
+    keys_per_request: usize,
+    prompts: Prompts,
+    /// Glossary file given on the command line
+    glossary: Option<PathBuf>,
+    filter: TranslationFilter,
+}
+
+async fn translate_command(
+    file: Option<PathBuf>,
+    selectors: Vec<String>,
+    provider: AIProvider,
+    base_url: String,
+    options: TranslateOptions,
 ) -> Result<()> {
     // Print welcome banner
     UI::print_banner();
     
-    // Get file path
-    let file_path = match file {
-        Some(path) => {
-            if !path.exists() {
-                anyhow::bail!("File not found: {}", path.display());
-            }
-            path
-        }
-        None => {
-            UI::print_step("Auto-detecting project file...");
-            match find_project_root() {
-                Some(path) => {
-                    UI::print_success(&format!("Found: {}", path.display()));
-                    path
-                }
-                None => {
-                    anyhow::bail!("Could not find Localizable.xcstrings. Use --file to specify path.");
-                }
-            }
-        }
-    };
+    // Get catalog paths
+    let (root, catalogs) = resolve_catalogs(file, &selectors, options.auto)?;
+    if catalogs.is_empty() {
+        UI::print_warning("No catalogs selected");
+        return Ok(());
+    }
     
     // Get API key
-    let api_key = get_api_key(api_key)?;
+    let provider = get_api_key(provider)?;
     
     // Initialize components
     UI::print_step("Initializing translator...");
-    let translator = Translator::new(api_key, base_url, model);
+    let backend = provider.backend(Some(&base_url));
+    UI::print_info("Provider", &format!("{} ({})", backend.name(), backend.model()));
+    let translator = Translator::new(backend)
+        .with_concurrency(options.concurrency)
+        .with_keys_per_request(options.keys_per_request)
+        .with_prompts(options.prompts.clone());
+    if let Some(name) = &options.prompts.profile.name {
+        UI::print_info("App", name);
+    }
+    let translator = translator.with_glossary(load_glossary(options.glossary.clone(), &root)?);
+    let mut memory = TranslationMemory::load()?;
+    if !memory.is_empty() {
+        UI::print_info("Translation memory", &format!("{} entries", memory.len()));
+    }
     
-    UI::print_step("Loading localization file...");
-    let mut xcstrings = XCStringsFile::load(&file_path)?;
+    let total = catalogs.len();
+    let mut summary = Vec::with_capacity(total);
+    for (index, file_path) in catalogs.iter().enumerate() {
+        let name = workspace::display_name(file_path, &root);
+        if total > 1 {
+            UI::print_header(&format!("Catalog {}/{}: {}", index + 1, total, name));
+        }
+        
+        let keys = translate_catalog(&translator, &mut memory, file_path, &options).await?;
+        summary.push((name, keys));
+    }
     
-    // Create backup
-    UI::print_step("Creating backup...");
-    let backup_path = xcstrings.create_backup()?;
-    UI::print_success(&format!("Backup: {}", backup_path.display()));
+    if total > 1 {
+        println!();
+        println!("Catalog Summary");
+        for (name, keys) in &summary {
+            let status = if *keys == 0 {
+                "up to date".to_string()
+            } else {
+                format!("{} keys", keys)
+            };
+            UI::print_info(name, &status);
+        }
+    }
+    
+    Ok(())
+}
+
+/// Translate one catalog, returning the number of keys that needed translation
+async fn translate_catalog(
+    translator: &Translator,
+    memory: &mut TranslationMemory,
+    file_path: &Path,
+    options: &TranslateOptions,
+) -> Result<usize> {
+    let language = &options.language;
+    
+    UI::print_step("Loading localization file...");
+    let mut xcstrings = XCStringsFile::load(file_path)?;
     
     // Get keys to translate
-    let keys = xcstrings.get_keys_needing_translation(&language, &mode);
+    let keys = xcstrings.get_keys_needing_translation(language, &options.mode, &options.filter);
     
     if keys.is_empty() {
-        let mode_desc = match mode {
+        let mode_desc = match options.mode {
             TranslationMode::Supplement => "supplement translation",
             TranslationMode::Fresh => "fresh translation",
         };
@@ -341,33 +789,681 @@ async fn translate_command(
             "No keys need {} for language '{}'",
             mode_desc, language
         ));
-        return Ok(());
+        return Ok(0);
     }
     
+    // Create backup
+    UI::print_step("Creating backup...");
+    let backup_path = xcstrings.create_backup()?;
+    UI::print_success(&format!("Backup: {}", backup_path.display()));
+    
     // Start translation process
     println!();
     println!("Translation Task");
-    UI::print_info("Target", &language);
-    UI::print_info("Mode", match mode {
+    UI::print_info("Target", language);
+    UI::print_info("Mode", match options.mode {
         TranslationMode::Supplement => "Supplement (skip existing)",
         TranslationMode::Fresh => "Fresh (retranslate all)",
     });
     UI::print_info("Keys", &keys.len().to_string());
+    UI::print_info("Saved as", options.ai_state.as_str());
     println!();
     
     // Translation process
-    if auto {
+    if options.auto {
         // Auto mode - translate all without interaction
-        UI::auto_translate_all(&mut xcstrings, &translator, &keys, &language, &file_path).await?;
+        UI::auto_translate_all(&mut xcstrings, translator, memory, &keys, language, file_path, options.ai_state).await?;
     } else {
         // Interactive mode
-        UI::interactive_translate(&mut xcstrings, &translator, &keys, &language, &file_path).await?;
+        UI::interactive_translate(&mut xcstrings, translator, memory, &keys, language, file_path, options.ai_state).await?;
     }
     
     UI::print_success("Translation completed");
     UI::print_info("Backup", &backup_path.display().to_string());
     UI::print_info("Output", &file_path.display().to_string());
     
+    Ok(keys.len())
+}
+
+fn export_command(
+    language: Option<String>,
+    format: ExportFormat,
+    file: Option<PathBuf>,
+    selectors: Vec<String>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    UI::print_banner();
+
+    let (root, catalogs) = resolve_catalogs(file, &selectors, true)?;
+
+    let output = match format {
+        ExportFormat::Xliff => {
+            let Some(language) = language else {
+                anyhow::bail!("XLIFF export needs a target language");
+            };
+            export_xliff(&root, &catalogs, &language, output)?
+        }
+        ExportFormat::Csv => export_sheet(&catalogs, language, SheetFormat::Csv, output)?,
+        ExportFormat::Tsv => export_sheet(&catalogs, language, SheetFormat::Tsv, output)?,
+        ExportFormat::Android => export_android(&catalogs, language, output)?,
+        ExportFormat::Po => {
+            let Some(language) = language else {
+                anyhow::bail!("PO export needs a target language; use --format pot for a template");
+            };
+            export_po(&catalogs, Some(&language), output)?
+        }
+        ExportFormat::Pot => export_po(&catalogs, None, output)?,
+        ExportFormat::I18next => export_json(&json_formats::I18next, &catalogs, language, output)?,
+        ExportFormat::Arb => export_json(&json_formats::FlutterArb, &catalogs, language, output)?,
+        ExportFormat::Chrome => export_json(&json_formats::ChromeMessages, &catalogs, language, output)?,
+        ExportFormat::Tmx => export_tmx(&catalogs, language, output)?,
+    };
+
+    UI::print_success(&format!("Exported to {}", output.display()));
+    Ok(())
+}
+
+fn export_xliff(
+    root: &Path,
+    catalogs: &[PathBuf],
+    language: &str,
+    output: Option<PathBuf>,
+) -> Result<PathBuf> {
+    let mut files = Vec::with_capacity(catalogs.len());
+    let mut source_language = String::new();
+    for path in catalogs {
+        let xcstrings = XCStringsFile::load(path)?;
+        let units = xliff::export_units(&xcstrings, language);
+        let untranslated = units.iter().filter(|unit| unit.target.is_none()).count();
+        let name = workspace::display_name(path, root);
+        UI::print_info(&name, &format!("{} units, {} untranslated", units.len(), untranslated));
+
+        source_language = xcstrings.data.source_language.clone();
+        files.push(xliff::XliffFile {
+            original: name,
+            source_language: xcstrings.data.source_language.clone(),
+            target_language: Some(language.to_string()),
+            units,
+        });
+    }
+
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.xliff", language)));
+    if output.extension().and_then(|e| e.to_str()) == Some("xcloc") {
+        xliff::write_xcloc(&output, &files, &source_language, language)?;
+    } else {
+        fs::write(&output, xliff::to_string(&files))?;
+    }
+    Ok(output)
+}
+
+/// Export one catalog as a spreadsheet, with every translated language unless one is given
+fn export_sheet(
+    catalogs: &[PathBuf],
+    language: Option<String>,
+    format: SheetFormat,
+    output: Option<PathBuf>,
+) -> Result<PathBuf> {
+    let [path] = catalogs else {
+        anyhow::bail!(
+            "A spreadsheet holds one catalog; found {}. Use --file or --catalog to pick one.",
+            catalogs.len()
+        );
+    };
+    let xcstrings = XCStringsFile::load(path)?;
+    let languages = export_languages(&xcstrings, language);
+    UI::print_info("Languages", &languages.join(", "));
+
+    let output = output.unwrap_or_else(|| {
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("Localizable");
+        PathBuf::from(format!("{}.{}", stem, format.extension()))
+    });
+    fs::write(&output, spreadsheet::export(&xcstrings, &languages, format))?;
+    Ok(output)
+}
+
+/// Export one catalog as Android string resources under a `res` directory
+fn export_android(
+    catalogs: &[PathBuf],
+    language: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<PathBuf> {
+    let [path] = catalogs else {
+        anyhow::bail!(
+            "Android export writes one catalog; found {}. Use --file or --catalog to pick one.",
+            catalogs.len()
+        );
+    };
+    let xcstrings = XCStringsFile::load(path)?;
+    let languages = export_languages(&xcstrings, language);
+    UI::print_info("Languages", &languages.join(", "));
+
+    let output = output.unwrap_or_else(|| PathBuf::from("res"));
+    let summary = android::export(&xcstrings, &languages, &output)?;
+    UI::print_info("Files", &summary.files.len().to_string());
+    if !summary.skipped.is_empty() {
+        UI::print_warning(&format!(
+            "{} keys with substitutions have no Android equivalent and were skipped:",
+            summary.skipped.len()
+        ));
+        for key in &summary.skipped {
+            println!("  {}", key.cyan());
+        }
+    }
+    Ok(output)
+}
+
+/// Export one catalog as a gettext PO file, or a POT template without a language
+fn export_po(catalogs: &[PathBuf], language: Option<&str>, output: Option<PathBuf>) -> Result<PathBuf> {
+    let [path] = catalogs else {
+        anyhow::bail!(
+            "A PO file holds one catalog; found {}. Use --file or --catalog to pick one.",
+            catalogs.len()
+        );
+    };
+    let xcstrings = XCStringsFile::load(path)?;
+    let po = gettext::export(&xcstrings, language);
+    let untranslated = po
+        .entries
+        .iter()
+        .filter(|entry| entry.strings.iter().any(String::is_empty))
+        .count();
+    UI::print_info("Messages", &format!("{}, {} untranslated", po.entries.len(), untranslated));
+
+    let output = output.unwrap_or_else(|| match language {
+        Some(language) => PathBuf::from(format!("{}.po", language)),
+        None => {
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("Localizable");
+            PathBuf::from(format!("{}.pot", stem))
+        }
+    });
+    fs::write(&output, gettext::to_string(&po))?;
+    Ok(output)
+}
+
+/// Export one catalog as one file per language, the source language included
+fn export_json(
+    format: &dyn CatalogFormat,
+    catalogs: &[PathBuf],
+    language: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<PathBuf> {
+    let [path] = catalogs else {
+        anyhow::bail!(
+            "{} export writes one catalog; found {}. Use --file or --catalog to pick one.",
+            format.name(),
+            catalogs.len()
+        );
+    };
+    let xcstrings = XCStringsFile::load(path)?;
+    let mut languages = vec![xcstrings.data.source_language.clone()];
+    languages.extend(export_languages(&xcstrings, language));
+    UI::print_info("Languages", &languages.join(", "));
+
+    let output = output.unwrap_or_else(|| PathBuf::from(format.default_directory()));
+    let mut skipped = Vec::new();
+    for language in &languages {
+        let export = format.export(&xcstrings, language)?;
+        let file_path = output.join(format.file_path(language));
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&file_path, export.content)?;
+        skipped.extend(export.skipped);
+    }
+
+    skipped.sort();
+    skipped.dedup();
+    if !skipped.is_empty() {
+        UI::print_warning(&format!(
+            "{} keys have no {} equivalent and were skipped:",
+            skipped.len(),
+            format.name()
+        ));
+        for key in &skipped {
+            println!("  {}", key.cyan());
+        }
+    }
+    Ok(output)
+}
+
+/// Export the approved translations of every catalog as one TMX document
+fn export_tmx(catalogs: &[PathBuf], language: Option<String>, output: Option<PathBuf>) -> Result<PathBuf> {
+    let mut document = tmx::TmxDocument::default();
+    for path in catalogs {
+        let xcstrings = XCStringsFile::load(path)?;
+        let languages = export_languages(&xcstrings, language.clone());
+        let units = tmx::export_units(&xcstrings, &languages);
+        UI::print_info(
+            &path.display().to_string(),
+            &format!("{} approved units ({})", units.len(), languages.join(", ")),
+        );
+        document.source_language = xcstrings.data.source_language.clone();
+        document.units.extend(units);
+    }
+
+    let output = output.unwrap_or_else(|| match catalogs {
+        [path] => {
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("Localizable");
+            PathBuf::from(format!("{}.tmx", stem))
+        }
+        _ => PathBuf::from("translations.tmx"),
+    });
+    fs::write(&output, tmx::to_string(&document))?;
+    Ok(output)
+}
+
+/// The requested language, or every language the catalog has translations for
+fn export_languages(xcstrings: &XCStringsFile, language: Option<String>) -> Vec<String> {
+    if let Some(language) = language {
+        return vec![language];
+    }
+    let mut languages: Vec<String> = xcstrings
+        .data
+        .strings
+        .values()
+        .flat_map(|entry| entry.localizations.keys())
+        .filter(|language| **language != xcstrings.data.source_language)
+        .cloned()
+        .collect();
+    languages.sort();
+    languages.dedup();
+    languages
+}
+
+/// Whether a `<file original>` path names the given catalog: the same path tail, or the same
+/// file name when no `<file>` with another path shares it
+fn matches_original(catalog: &Path, original: &str, files: &[xliff::XliffFile]) -> bool {
+    let file_name = Path::new(original).file_name();
+    catalog.ends_with(original)
+        || (catalog.file_name() == file_name
+            && files
+                .iter()
+                .filter(|f| Path::new(&f.original).file_name() == file_name)
+                .all(|f| f.original == original))
+}
+
+fn import_command(input: PathBuf, file: Option<PathBuf>, dry_run: bool) -> Result<()> {
+    UI::print_banner();
+
+    if !input.exists() {
+        anyhow::bail!("File not found: {}", input.display());
+    }
+
+    if let Some(format) = SheetFormat::from_path(&input) {
+        return import_sheet(&input, format, file, dry_run);
+    }
+    if let Some(res_dir) = android_res_dir(&input) {
+        return import_android(&res_dir, file, dry_run);
+    }
+    if input.extension().and_then(|e| e.to_str()) == Some("po") {
+        return import_po(&input, file, dry_run);
+    }
+    if input.extension().and_then(|e| e.to_str()) == Some("tmx") {
+        return import_tmx(&input, dry_run);
+    }
+    if let Some(format) = json_formats::format_for_path(&input) {
+        return import_json(format.as_ref(), &input, file, dry_run);
+    }
+
+    UI::print_step("Reading translations...");
+    let files = if input.is_dir() {
+        xliff::read_xcloc(&input)?
+    } else {
+        xliff::parse(&fs::read_to_string(&input)?)?
+    };
+
+    let (root, catalogs) = match &file {
+        Some(_) => (PathBuf::new(), Vec::new()),
+        None => find_catalogs()?,
+    };
+
+    for xliff_file in &files {
+        let Some(language) = &xliff_file.target_language else {
+            UI::print_warning(&format!("Skipping {}: no target-language", xliff_file.original));
+            continue;
+        };
+
+        // The original path is relative to the workspace root; fall back to a catalog with the same file name.
+        // With --file, only the <file> elements naming that catalog are imported into it.
+        let catalog = match &file {
+            Some(path) if files.len() == 1 || matches_original(path, &xliff_file.original, &files) => Some(path.clone()),
+            Some(path) => {
+                UI::print_warning(&format!("Skipping {}: not {}", xliff_file.original, path.display()));
+                continue;
+            }
+            None => {
+                let path = root.join(&xliff_file.original);
+                if path.exists() {
+                    Some(path)
+                } else {
+                    let file_name = Path::new(&xliff_file.original).file_name();
+                    catalogs.iter().find(|c| c.file_name() == file_name).cloned()
+                }
+            }
+        };
+        let Some(catalog) = catalog else {
+            UI::print_warning(&format!("Skipping {}: catalog not found", xliff_file.original));
+            continue;
+        };
+
+        println!();
+        UI::print_step(&format!("Importing {} into {}", language, catalog.display()));
+        let mut xcstrings = XCStringsFile::load(&catalog)?;
+        let summary = xliff::import_units(&mut xcstrings, &xliff_file.units, language)?;
+
+        UI::print_info("Keys", &summary.keys.to_string());
+        if !summary.skipped.is_empty() {
+            UI::print_warning(&format!("{} units did not match the catalog:", summary.skipped.len()));
+            for id in &summary.skipped {
+                println!("  {}", id.cyan());
+            }
+        }
+
+        if dry_run || summary.keys == 0 {
+            continue;
+        }
+        let backup_path = xcstrings.create_backup()?;
+        xcstrings.save()?;
+        UI::print_success(&format!("Saved (backup: {})", backup_path.display()));
+    }
+
+    if dry_run {
+        UI::print_warning("Dry run: nothing was written");
+    }
+
+    Ok(())
+}
+
+/// Apply an edited spreadsheet to one catalog, listing rows that conflict with later catalog changes
+fn import_sheet(input: &Path, format: SheetFormat, file: Option<PathBuf>, dry_run: bool) -> Result<()> {
+    let file_path = resolve_file_path(file)?;
+
+    UI::print_step("Loading localization file...");
+    let mut xcstrings = XCStringsFile::load(&file_path)?;
+    let content = fs::read_to_string(input)?;
+    let summary = spreadsheet::import(&mut xcstrings, &content, format)?;
+
+    UI::print_info("Updated", &summary.updated.to_string());
+    if !summary.ignored_columns.is_empty() {
+        UI::print_warning(&format!(
+            "Ignored columns that are not languages: {}",
+            summary.ignored_columns.join(", ")
+        ));
+    }
+    if !summary.conflicts.is_empty() {
+        println!();
+        UI::print_warning(&format!("{} conflicts were not applied:", summary.conflicts.len()));
+        for conflict in &summary.conflicts {
+            println!("  {}: {}", conflict.key.cyan(), conflict.reason);
+        }
+        println!();
+    }
+
+    if dry_run {
+        UI::print_warning("Dry run: nothing was written");
+        return Ok(());
+    }
+    if summary.updated > 0 {
+        let backup_path = xcstrings.create_backup()?;
+        xcstrings.save()?;
+        UI::print_success(&format!("Saved (backup: {})", backup_path.display()));
+    }
+
+    Ok(())
+}
+
+/// Add the translations of a TMX file to the local translation memory
+fn import_tmx(input: &Path, dry_run: bool) -> Result<()> {
+    UI::print_step("Reading translation memory...");
+    let document = tmx::parse(&fs::read_to_string(input)?)?;
+    let entries = tmx::memory_entries(&document, &input.display().to_string());
+
+    let mut memory = TranslationMemory::load()?;
+    let (mut added, mut updated) = (0, 0);
+    for entry in entries {
+        match memory.add(entry) {
+            MemoryUpdate::Added => added += 1,
+            MemoryUpdate::Updated => updated += 1,
+            MemoryUpdate::Unchanged => {}
+        }
+    }
+
+    UI::print_info("Units", &document.units.len().to_string());
+    UI::print_info("Added", &added.to_string());
+    UI::print_info("Updated", &updated.to_string());
+
+    if dry_run {
+        UI::print_warning("Dry run: nothing was written");
+        return Ok(());
+    }
+    memory.save()?;
+    UI::print_success(&format!("Saved to {}", memory.path().display()));
+    Ok(())
+}
+
+/// Apply a translated PO file to one catalog; the language comes from the PO header
+fn import_po(input: &Path, file: Option<PathBuf>, dry_run: bool) -> Result<()> {
+    let po = gettext::parse(&fs::read_to_string(input)?)?;
+    let Some(language) = po.language.clone() else {
+        anyhow::bail!("{} has no Language header", input.display());
+    };
+    let file_path = resolve_file_path(file)?;
+
+    UI::print_step(&format!("Importing {} into {}", language, file_path.display()));
+    let mut xcstrings = XCStringsFile::load(&file_path)?;
+    let summary = gettext::import(&mut xcstrings, &po, &language)?;
+
+    UI::print_info("Keys", &summary.keys.to_string());
+    if !summary.skipped.is_empty() {
+        UI::print_warning(&format!("{} messages did not match the catalog:", summary.skipped.len()));
+        for id in &summary.skipped {
+            println!("  {}", id.cyan());
+        }
+    }
+
+    if dry_run {
+        UI::print_warning("Dry run: nothing was written");
+        return Ok(());
+    }
+    if summary.keys > 0 {
+        let backup_path = xcstrings.create_backup()?;
+        xcstrings.save()?;
+        UI::print_success(&format!("Saved (backup: {})", backup_path.display()));
+    }
+
+    Ok(())
+}
+
+/// Apply one language's i18next, ARB or Chrome messages file to one catalog
+fn import_json(format: &dyn CatalogFormat, input: &Path, file: Option<PathBuf>, dry_run: bool) -> Result<()> {
+    let content = fs::read_to_string(input)?;
+    let Some(language) = format.detect_language(input, &content) else {
+        anyhow::bail!("Could not tell the language of {}", input.display());
+    };
+    let file_path = resolve_file_path(file)?;
+
+    UI::print_step(&format!(
+        "Importing {} ({}) into {}",
+        language,
+        format.name(),
+        file_path.display()
+    ));
+    let mut xcstrings = XCStringsFile::load(&file_path)?;
+    let summary = format.import(&mut xcstrings, &content, &language)?;
+
+    UI::print_info("Keys", &summary.keys.to_string());
+    if !summary.skipped.is_empty() {
+        UI::print_warning(&format!("{} messages did not match the catalog:", summary.skipped.len()));
+        for id in &summary.skipped {
+            println!("  {}", id.cyan());
+        }
+    }
+
+    if dry_run {
+        UI::print_warning("Dry run: nothing was written");
+        return Ok(());
+    }
+    if summary.keys > 0 {
+        let backup_path = xcstrings.create_backup()?;
+        xcstrings.save()?;
+        UI::print_success(&format!("Saved (backup: {})", backup_path.display()));
+    }
+
+    Ok(())
+}
+
+/// The `res` directory of an Android input: the directory itself or the parent of `values/strings.xml`
+fn android_res_dir(input: &Path) -> Option<PathBuf> {
+    if input.join("values").join("strings.xml").is_file() {
+        return Some(input.to_path_buf());
+    }
+    if input.file_name().and_then(|n| n.to_str()) == Some("strings.xml") {
+        let values = input.parent()?;
+        if values.file_name().and_then(|n| n.to_str()) == Some("values") {
+            return values.parent().map(Path::to_path_buf);
+        }
+    }
+    None
+}
+
+/// Bring strings that only exist on Android into a catalog, with their translations
+fn import_android(res_dir: &Path, file: Option<PathBuf>, dry_run: bool) -> Result<()> {
+    let file_path = resolve_file_path(file)?;
+
+    UI::print_step("Loading localization file...");
+    let mut xcstrings = XCStringsFile::load(&file_path)?;
+    let summary = android::import(&mut xcstrings, res_dir, UnitState::Translated)?;
+
+    if summary.keys.is_empty() {
+        UI::print_success("Every Android string is already in the catalog");
+        return Ok(());
+    }
+    UI::print_info("New keys", &summary.keys.len().to_string());
+    for key in &summary.keys {
+        println!("  {}", key.cyan());
+    }
+    if !summary.languages.is_empty() {
+        UI::print_info("Languages", &summary.languages.join(", "));
+    }
+
+    if dry_run {
+        UI::print_warning("Dry run: nothing was written");
+        return Ok(());
+    }
+    let backup_path = xcstrings.create_backup()?;
+    xcstrings.save()?;
+    UI::print_success(&format!("Saved (backup: {})", backup_path.display()));
+
+    Ok(())
+}
+
+fn migrate_command(file: PathBuf, output: Option<PathBuf>, force: bool) -> Result<()> {
+    UI::print_banner();
+
+    if !file.exists() {
+        anyhow::bail!("File not found: {}", file.display());
+    }
+
+    // Xcode keeps migrated catalogs next to the .lproj directories, named after the table
+    let output = match output {
+        Some(path) => path,
+        None => {
+            let table = file.file_stem().and_then(|s| s.to_str()).unwrap_or("Localizable");
+            let root = file
+                .parent()
+                .and_then(Path::parent)
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            root.join(format!("{}.xcstrings", table))
+        }
+    };
+    if output.exists() && !force {
+        anyhow::bail!(
+            "{} already exists. Use --force to overwrite it.",
+            output.display()
+        );
+    }
+
+    UI::print_step("Reading legacy table...");
+    let data = strings_file::migrate(&file)?;
+
+    let mut languages: Vec<&String> = data
+        .strings
+        .values()
+        .flat_map(|entry| entry.localizations.keys())
+        .collect();
+    languages.sort();
+    languages.dedup();
+    let plurals = data
+        .strings
+        .values()
+        .filter_map(|entry| entry.localizations.get(&data.source_language))
+        .filter(|localization| localization.variations.is_some() || localization.substitutions.is_some())
+        .count();
+
+    UI::print_info("Source language", &data.source_language);
+    UI::print_info(
+        "Languages",
+        &languages.iter().map(|l| l.as_str()).collect::<Vec<_>>().join(", "),
+    );
+    UI::print_info("Keys", &data.strings.len().to_string());
+    UI::print_info("Plurals", &plurals.to_string());
+
+    UI::print_step("Writing catalog...");
+    XCStringsFile::new(&output, data).save()?;
+    UI::print_success(&format!("Created {}", output.display()));
+    UI::print_warning(
+        "Add the catalog to your target and remove the old .strings/.stringsdict files from it",
+    );
+
+    Ok(())
+}
+
+fn prune_command(file: Option<PathBuf>, dry_run: bool, yes: bool) -> Result<()> {
+    UI::print_banner();
+
+    let file_path = resolve_file_path(file)?;
+
+    UI::print_step("Loading localization file...");
+    let mut xcstrings = XCStringsFile::load(&file_path)?;
+
+    let stale_keys = xcstrings.get_stale_keys();
+    if stale_keys.is_empty() {
+        UI::print_success("No stale keys found");
+        return Ok(());
+    }
+
+    println!();
+    UI::print_warning(&format!("Found {} stale keys:", stale_keys.len()));
+    for (i, key) in stale_keys.iter().enumerate() {
+        println!("  {}. {}", (i + 1).to_string().bright_white(), key.cyan());
+    }
+    println!();
+
+    if dry_run {
+        return Ok(());
+    }
+
+    let confirm = yes
+        || Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Delete {} stale keys?", stale_keys.len()))
+            .default(false)
+            .interact()?;
+
+    if !confirm {
+        UI::print_info("Prune", "Cancelled");
+        return Ok(());
+    }
+
+    UI::print_step("Creating backup...");
+    let backup_path = xcstrings.create_backup()?;
+    UI::print_success(&format!("Backup: {}", backup_path.display()));
+
+    let removed = xcstrings.remove_keys(&stale_keys);
+    xcstrings.save()?;
+    UI::print_success(&format!("Deleted {} stale keys", removed));
+    UI::print_info("Output", &file_path.display().to_string());
+
     Ok(())
 }
 
@@ -493,7 +1589,7 @@ fn search_directory_recursive(directory: &PathBuf, backup_files: &mut Vec<Backup
         } else if path.is_file() {
             // Check if it's a backup file
             if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                if file_name.contains(".xcstrings.backup_") {
+                if file_name.contains(".strings.backup_") || file_name.contains(".stringsdict.backup_") {
                     let metadata = fs::metadata(&path)?;
                     let modified_time = metadata.modified()?;
                     let modified_time: DateTime<chrono::Local> = modified_time.into();
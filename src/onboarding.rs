@@ -47,7 +47,7 @@ impl Onboarding {
         println!("{}", "🔑 API Configuration".bright_white().bold());
         
         // Select AI provider
-        let providers = vec!["OpenAI", "Claude", "Google Gemini"];
+        let providers = vec!["OpenAI", "Claude", "Google Gemini", "OpenRouter"];
         let provider_idx = Select::with_theme(&ColorfulTheme::default())
             .with_prompt("Select your AI provider")
             .items(&providers)
@@ -111,6 +111,23 @@ impl Onboarding {
                     model: models[model_idx].to_string(),
                 }
             }
+            3 => {
+                let models = vec![
+                    "anthropic/claude-3.5-sonnet",
+                    "openai/gpt-4o",
+                    "google/gemini-2.5-flash",
+                ];
+                let model_idx = Select::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Select OpenRouter model")
+                    .items(&models)
+                    .default(0)
+                    .interact()?;
+                
+                AIProvider::OpenRouter {
+                    api_key: api_key.clone(),
+                    model: models[model_idx].to_string(),
+                }
+            }
             _ => unreachable!(),
         };
 
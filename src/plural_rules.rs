@@ -0,0 +1,79 @@
+/// CLDR plural categories in the order Xcode presents them
+pub const PLURAL_CATEGORIES: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+
+/// Return the CLDR cardinal plural categories a language requires.
+///
+/// Region and script subtags are ignored (`pt-BR` → `pt`, `zh-Hans` → `zh`).
+/// Unknown languages fall back to `one`/`other`, which is what most languages use.
+pub fn plural_categories(language: &str) -> &'static [&'static str] {
+    let base = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or(language)
+        .to_lowercase();
+
+    match base.as_str() {
+        // No plural distinction
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" | "my" | "lo" | "km" | "yue" => &["other"],
+
+        // one / few / many / other
+        "ru" | "uk" | "be" | "pl" | "cs" | "sk" | "lt" => &["one", "few", "many", "other"],
+
+        // one / few / other
+        "hr" | "sr" | "bs" | "ro" => &["one", "few", "other"],
+
+        // one / many / other (CLDR "many" for large round numbers)
+        "fr" | "es" | "it" | "pt" | "ca" => &["one", "many", "other"],
+
+        // zero / one / other
+        "lv" => &["zero", "one", "other"],
+
+        // one / two / other
+        "he" | "iw" => &["one", "two", "other"],
+
+        // one / two / few / other
+        "sl" => &["one", "two", "few", "other"],
+
+        // one / two / few / many / other
+        "ga" | "mt" => &["one", "two", "few", "many", "other"],
+
+        // All six categories
+        "ar" | "cy" => &["zero", "one", "two", "few", "many", "other"],
+
+        _ => &["one", "other"],
+    }
+}
+
+/// Sort plural categories into CLDR order, leaving unknown names at the end
+pub fn sort_categories<S: AsRef<str>>(categories: &mut [S]) {
+    categories.sort_by_key(|c| {
+        PLURAL_CATEGORIES
+            .iter()
+            .position(|p| *p == c.as_ref())
+            .unwrap_or(PLURAL_CATEGORIES.len())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plural_categories() {
+        assert_eq!(plural_categories("ja"), &["other"]);
+        assert_eq!(plural_categories("zh-Hans"), &["other"]);
+        assert_eq!(plural_categories("en"), &["one", "other"]);
+        assert_eq!(plural_categories("ru"), &["one", "few", "many", "other"]);
+        assert_eq!(plural_categories("pl"), &["one", "few", "many", "other"]);
+        assert_eq!(plural_categories("uk"), &["one", "few", "many", "other"]);
+        assert_eq!(plural_categories("pt-BR"), &["one", "many", "other"]);
+        assert_eq!(plural_categories("ar"), &PLURAL_CATEGORIES);
+    }
+
+    #[test]
+    fn test_sort_categories() {
+        let mut categories = vec!["other", "few", "one", "many"];
+        sort_categories(&mut categories);
+        assert_eq!(categories, vec!["one", "few", "many", "other"]);
+    }
+}
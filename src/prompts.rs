@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+
+/// What the app is, so prompts can describe the product being translated
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AppProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The app's domain, such as "habit tracker for runners"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    /// Product and brand names that stay untranslated unless they have an official localization
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub brand_terms: Vec<String>,
+}
+
+impl AppProfile {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The profile as prompt lines, ending with a newline; empty without a profile
+    fn summary(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(name) = &self.name {
+            lines.push(format!("App: {}", name));
+        }
+        if let Some(description) = &self.description {
+            lines.push(format!("About: {}", description));
+        }
+        if let Some(audience) = &self.audience {
+            lines.push(format!("Audience: {}", audience));
+        }
+        if !self.brand_terms.is_empty() {
+            lines.push(format!("Brand terms (keep unchanged): {}", self.quoted_brand_terms()));
+        }
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    fn quoted_brand_terms(&self) -> String {
+        self.brand_terms
+            .iter()
+            .map(|term| format!("\"{}\"", term))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Prompt templates replacing the built-in ones. Placeholders in braces are filled in, and any
+/// other text, braces included, is sent as written.
+///
+/// Every template can use `{target_language}`, `{app_profile}`, `{app_name}`,
+/// `{app_description}`, `{audience}`, `{brand_terms}` and `{requirements}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplates {
+    /// A catalog key with its comment and references: `{source_text}` and `{context_info}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Several keys answered as one JSON object: `{count}` and `{entries}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch: Option<String>,
+}
+
+impl PromptTemplates {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Report templates that leave out the text to translate
+    pub fn validate(&self) -> Result<(), String> {
+        let required = [
+            ("context", &self.context, "{source_text}"),
+            ("batch", &self.batch, "{entries}"),
+        ];
+        for (name, template, placeholder) in required {
+            if template.as_ref().is_some_and(|t| !t.contains(placeholder)) {
+                return Err(format!("The {} prompt template must contain {}", name, placeholder));
+            }
+        }
+        Ok(())
+    }
+}
+
+const CONTEXT_TEMPLATE: &str = r#"请将以下iOS应用本地化字符串翻译为{target_language}。
+{app_profile}
+翻译信息:
+{context_info}
+
+原文: "{source_text}"
+
+{requirements}
+
+请只提供翻译结果，不要包含解释。"#;
+
+const BATCH_TEMPLATE: &str = r#"请将以下{count}条iOS应用本地化字符串翻译为{target_language}。
+{app_profile}
+{entries}
+
+{requirements}
+
+请以JSON对象返回结果：键为每条字符串的Key（与上面完全一致），值为对应的{target_language}译文。只返回JSON，不要包含解释。"#;
+
+/// Builds the translation prompts from the app profile and the configured templates
+#[derive(Debug, Clone, Default)]
+pub struct Prompts {
+    pub profile: AppProfile,
+    pub templates: PromptTemplates,
+}
+
+impl Prompts {
+    pub fn new(profile: AppProfile, templates: PromptTemplates) -> Self {
+        Self { profile, templates }
+    }
+
+    /// Prompt for one catalog key, with its key, comment and references in `context_info`
+    pub fn context(&self, target_language: &str, source_text: &str, context_info: &str) -> String {
+        let requirements = self.requirements_zh(target_language);
+        self.render(
+            self.templates.context.as_deref().unwrap_or(CONTEXT_TEMPLATE),
+            target_language,
+            &[
+                ("source_text", source_text),
+                ("context_info", context_info),
+                ("requirements", &requirements),
+            ],
+        )
+    }
+
+    /// Prompt for several keys answered as one JSON object keyed by string key
+    pub fn batch(&self, target_language: &str, count: usize, entries: &str) -> String {
+        let requirements = self.requirements_zh(target_language);
+        self.render(
+            self.templates.batch.as_deref().unwrap_or(BATCH_TEMPLATE),
+            target_language,
+            &[
+                ("count", &count.to_string()),
+                ("entries", entries),
+                ("requirements", &requirements),
+            ],
+        )
+    }
+
+    fn render(&self, template: &str, target_language: &str, values: &[(&str, &str)]) -> String {
+        let summary = self.profile.summary();
+        let brand_terms = self.profile.quoted_brand_terms();
+        let mut all = vec![
+            ("target_language", target_language),
+            ("app_profile", summary.as_str()),
+            ("app_name", self.profile.name.as_deref().unwrap_or_default()),
+            ("app_description", self.profile.description.as_deref().unwrap_or_default()),
+            ("audience", self.profile.audience.as_deref().unwrap_or_default()),
+            ("brand_terms", brand_terms.as_str()),
+        ];
+        all.extend_from_slice(values);
+        render(template, &all)
+    }
+
+    fn requirements_zh(&self, target_language: &str) -> String {
+        let brands = if self.profile.brand_terms.is_empty() {
+            "- 品牌名称保持不变，除非有官方本地化版本".to_string()
+        } else {
+            format!("- 品牌名称如{}保持不变，除非有官方本地化版本", self.profile.quoted_brand_terms())
+        };
+        format!(
+            "翻译要求:
+- 保持翻译自然流畅，符合{}使用习惯
+- 保留所有格式化占位符（如 %@, %d, {{}}等）
+- 技术术语使用标准翻译
+{}
+- 参考其他语言的翻译风格保持一致性
+- 根据用途类别选择合适的语言风格和正式程度",
+            target_language, brands
+        )
+    }
+}
+
+/// Replace `{name}` placeholders in one pass, so placeholder-like text inside the values (a
+/// source string containing `{count}`, say) is left alone. Unknown names are kept as written.
+fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after
+            .find('}')
+            .and_then(|end| values.iter().find(|(name, _)| *name == &after[..end]).map(|(_, v)| (end, v)));
+        match value {
+            Some((end, value)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> AppProfile {
+        AppProfile {
+            name: Some("Trailmate".to_string()),
+            description: Some("route planner for hikers".to_string()),
+            audience: None,
+            brand_terms: vec!["Trailmate".to_string(), "Summit Pass".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let values = [("source_text", "{count} left"), ("count", "3")];
+        assert_eq!(render("「{source_text}」 {count} {other} {", &values), "「{count} left」 3 {other} {");
+    }
+
+    #[test]
+    fn test_built_in_prompts_use_profile() {
+        let prompts = Prompts::new(profile(), PromptTemplates::default());
+        let prompt = prompts.context("Japanese", "Start hike", "Key: start_hike");
+        assert!(prompt.contains("App: Trailmate\nAbout: route planner for hikers\n"));
+        assert!(prompt.contains("\"Trailmate\", \"Summit Pass\"保持不变"));
+        assert!(prompt.contains("原文: \"Start hike\""));
+        assert!(!prompt.contains("{app_profile}") && !prompt.contains("{requirements}"), "{}", prompt);
+
+        let prompt = Prompts::default().context("French", "Save", "Key: save");
+        assert!(!prompt.contains("App:"));
+        assert!(!prompt.contains("Hands Time"));
+    }
+
+    #[test]
+    fn test_template_overrides() {
+        let templates = PromptTemplates {
+            context: Some("{app_name} → {target_language}: {source_text} ({brand_terms})".to_string()),
+            ..Default::default()
+        };
+        assert!(templates.validate().is_ok());
+        let prompts = Prompts::new(profile(), templates);
+        assert_eq!(
+            prompts.context("German", "Start", "Key: start"),
+            "Trailmate → German: Start (\"Trailmate\", \"Summit Pass\")"
+        );
+
+        let templates = PromptTemplates {
+            batch: Some("Translate to {target_language}".to_string()),
+            ..Default::default()
+        };
+        assert!(templates.validate().unwrap_err().contains("{entries}"));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::error::Result;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+/// Spacing between request starts after the first sign of throttling
+const MIN_BACKOFF: Duration = Duration::from_millis(250);
+const MAX_INTERVAL: Duration = Duration::from_secs(30);
+
+struct Pacing {
+    /// Time between the starts of two requests
+    interval: Duration,
+    next_start: Option<Instant>,
+}
+
+/// Bounds the number of requests in flight and spaces out their starts. The spacing widens
+/// when the provider throttles and narrows again as requests succeed.
+pub struct RateLimiter {
+    permits: Semaphore,
+    concurrency: usize,
+    pacing: Mutex<Pacing>,
+}
+
+impl RateLimiter {
+    pub fn new(concurrency: usize) -> Self {
+        let concurrency = concurrency.max(1);
+        Self {
+            permits: Semaphore::new(concurrency),
+            concurrency,
+            pacing: Mutex::new(Pacing {
+                interval: Duration::ZERO,
+                next_start: None,
+            }),
+        }
+    }
+
+    /// Maximum number of requests in flight
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Wait for a free slot and this request's turn; the request runs while the permit is held
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        let permit = self.permits.acquire().await.expect("rate limiter semaphore is never closed");
+        let start = {
+            let mut pacing = self.pacing.lock().unwrap();
+            let now = Instant::now();
+            let start = pacing.next_start.map_or(now, |next| next.max(now));
+            pacing.next_start = Some(start + pacing.interval);
+            start
+        };
+        tokio::time::sleep_until(start).await;
+        permit
+    }
+
+    /// Adapt the spacing to how the provider answered a request. A requested wait holds back
+    /// every request, not just the one that is retried.
+    pub fn record<T>(&self, result: &Result<T>) {
+        let mut pacing = self.pacing.lock().unwrap();
+        match result {
+            Err(error) if error.is_retryable() => {
+                pacing.interval = (pacing.interval * 2).clamp(MIN_BACKOFF, MAX_INTERVAL);
+                if let Some(wait) = error.retry_after() {
+                    let resume = Instant::now() + wait.min(MAX_INTERVAL);
+                    pacing.next_start = Some(pacing.next_start.map_or(resume, |next| next.max(resume)));
+                }
+            }
+            Ok(_) => {
+                pacing.interval = pacing.interval * 4 / 5;
+                if pacing.interval < Duration::from_millis(10) {
+                    pacing.interval = Duration::ZERO;
+                }
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TranslatorError;
+
+    fn throttled() -> Result<()> {
+        Err(TranslatorError::RateLimited {
+            retry_after: None,
+            message: "Too Many Requests".to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_bounds_and_adapts() {
+        let limiter = RateLimiter::new(2);
+        let first = limiter.acquire().await;
+        let _second = limiter.acquire().await;
+        // A third request waits for a free slot
+        assert!(tokio::time::timeout(Duration::from_millis(20), limiter.acquire()).await.is_err());
+        drop(first);
+        assert!(tokio::time::timeout(Duration::from_millis(20), limiter.acquire()).await.is_ok());
+
+        let interval = |limiter: &RateLimiter| limiter.pacing.lock().unwrap().interval;
+        limiter.record(&throttled());
+        assert_eq!(interval(&limiter), MIN_BACKOFF);
+        limiter.record(&throttled());
+        assert_eq!(interval(&limiter), MIN_BACKOFF * 2);
+        limiter.record::<()>(&Err(TranslatorError::TranslationFailed("bad".to_string())));
+        assert_eq!(interval(&limiter), MIN_BACKOFF * 2);
+        for _ in 0..30 {
+            limiter.record(&Ok(()));
+        }
+        assert_eq!(interval(&limiter), Duration::ZERO);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::error::{Result, TranslatorError};
+use std::future::Future;
+use std::time::Duration;
+
+/// A `Retry-After` longer than this (a daily quota, say) is not waited out
+const LONGEST_WAIT: Duration = Duration::from_secs(120);
+
+/// When and how often a failed provider request is sent again
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before the next attempt after `attempt` attempts failed with `error`, or `None` to give up.
+    ///
+    /// The backoff doubles with every attempt and is jittered so concurrent requests do not retry
+    /// in lockstep; a delay the provider asked for is always waited out in full.
+    pub fn delay(&self, attempt: u32, error: &TranslatorError) -> Option<Duration> {
+        if !error.is_retryable() || attempt >= self.max_attempts {
+            return None;
+        }
+
+        let exponent = attempt.saturating_sub(1).min(16);
+        let backoff = self.base_delay.saturating_mul(1 << exponent).min(self.max_delay);
+        let jittered = backoff / 2 + backoff.mul_f64(fastrand::f64() / 2.0);
+
+        match error.retry_after() {
+            Some(wait) if wait > LONGEST_WAIT => None,
+            Some(wait) => Some(wait.max(jittered)),
+            None => Some(jittered),
+        }
+    }
+
+    /// Run `operation` until it succeeds, fails for good, or runs out of attempts
+    pub async fn run<T, F, Fut>(&self, mut operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(error) => match self.delay(attempt, &error) {
+                    Some(delay) => {
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => return Err(error),
+                },
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transient(retry_after: Option<Duration>) -> TranslatorError {
+        TranslatorError::Transient {
+            retry_after,
+            message: "status 503".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::default();
+        for attempt in 1..=3 {
+            let delay = policy.delay(attempt, &transient(None)).unwrap();
+            let backoff = Duration::from_secs(1 << (attempt - 1));
+            assert!(delay >= backoff / 2 && delay <= backoff, "{:?}", delay);
+        }
+        assert_eq!(policy.delay(4, &transient(None)), None);
+
+        let wait = Duration::from_secs(7);
+        assert!(policy.delay(1, &transient(Some(wait))).unwrap() >= wait);
+        assert_eq!(policy.delay(1, &transient(Some(Duration::from_secs(3600)))), None);
+
+        let unauthorized = TranslatorError::Unauthorized {
+            status: 401,
+            message: "invalid key".to_string(),
+        };
+        assert_eq!(policy.delay(1, &unauthorized), None);
+        let quota = TranslatorError::QuotaExceeded {
+            message: "insufficient_quota".to_string(),
+        };
+        assert_eq!(policy.delay(1, &quota), None);
+    }
+
+    #[tokio::test]
+    async fn test_run_retries_transient_failures() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        };
+
+        let mut calls = 0;
+        let result = policy
+            .run(|| {
+                calls += 1;
+                let outcome = if calls < 3 { Err(transient(None)) } else { Ok(calls) };
+                async move { outcome }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 3);
+
+        calls = 0;
+        let result: Result<()> = policy
+            .run(|| {
+                calls += 1;
+                async { Err(transient(None)) }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+}
@@ -0,0 +1,544 @@
+use crate::error::{Result, TranslatorError};
+use crate::plural_rules::{plural_categories, PLURAL_CATEGORIES};
+use crate::xcstrings::{
+    split_variation_id, variation_id, TranslationValue, UnitNode, UnitState, ValueBuilder,
+    XCStringsFile,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+const KEY_COLUMN: &str = "key";
+const COMMENT_COLUMN: &str = "comment";
+const SOURCE_COLUMN: &str = "source";
+/// Hash of a row's catalog content at export time, used to detect later catalog changes
+const FINGERPRINT_COLUMN: &str = "fingerprint";
+const STATE_SUFFIX: &str = " state";
+
+/// Written first so spreadsheet apps open the file as UTF-8
+const BOM: char = '\u{feff}';
+
+/// Delimited text format of a spreadsheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetFormat {
+    Csv,
+    Tsv,
+}
+
+impl SheetFormat {
+    /// Format matching a `.csv` or `.tsv` file name
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(SheetFormat::Csv),
+            "tsv" => Some(SheetFormat::Tsv),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SheetFormat::Csv => "csv",
+            SheetFormat::Tsv => "tsv",
+        }
+    }
+
+    fn delimiter(&self) -> char {
+        match self {
+            SheetFormat::Csv => ',',
+            SheetFormat::Tsv => '\t',
+        }
+    }
+}
+
+/// A row that could not be applied because the catalog no longer matches the export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub key: String,
+    pub reason: String,
+}
+
+/// One row of a key: its variation path and cells
+type PathRow = (String, Vec<String>);
+
+/// Outcome of importing a sheet
+#[derive(Debug, Default)]
+pub struct SheetImport {
+    /// Translations written, counted per key and language
+    pub updated: usize,
+    pub conflicts: Vec<Conflict>,
+    /// Columns that are neither a language nor one of the sheet's own columns
+    pub ignored_columns: Vec<String>,
+}
+
+/// Export a catalog with columns key, comment, source, then a translation and a state column
+/// per language. Plural, device and substitution variations get a row each.
+pub fn export(file: &XCStringsFile, languages: &[String], format: SheetFormat) -> String {
+    let mut header = vec![
+        KEY_COLUMN.to_string(),
+        COMMENT_COLUMN.to_string(),
+        SOURCE_COLUMN.to_string(),
+    ];
+    for language in languages {
+        header.push(language.clone());
+        header.push(format!("{}{}", language, STATE_SUFFIX));
+    }
+    header.push(FINGERPRINT_COLUMN.to_string());
+
+    // Plurals get a row for every category the source or any exported language needs
+    let categories: Vec<&str> = PLURAL_CATEGORIES
+        .iter()
+        .copied()
+        .filter(|category| {
+            languages
+                .iter()
+                .chain(std::iter::once(&file.data.source_language))
+                .any(|language| plural_categories(language).contains(category))
+        })
+        .collect();
+
+    let mut rows = vec![header];
+    for key in file.get_translatable_keys() {
+        let snapshot = KeySnapshot::new(file, &key, languages);
+        let comment = file.data.strings[&key].comment.clone().unwrap_or_default();
+
+        for (path, source) in snapshot.source.flatten(Some(&categories)) {
+            let mut row = vec![variation_id(&key, &path), comment.clone(), source];
+            for language in languages {
+                let (targets, state) = &snapshot.targets[language];
+                row.push(targets.get(&path).cloned().unwrap_or_default());
+                row.push(state.map(|s| s.as_str().to_string()).unwrap_or_default());
+            }
+            row.push(snapshot.fingerprint());
+            rows.push(row);
+        }
+    }
+
+    let mut out = String::new();
+    out.push(BOM);
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|field| quote(field, format)).collect();
+        out.push_str(&fields.join(&format.delimiter().to_string()));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Apply edited cells back to the catalog. Rows whose key disappeared, or whose key changed in
+/// the catalog since the export, are reported as conflicts instead of being applied.
+pub fn import(file: &mut XCStringsFile, content: &str, format: SheetFormat) -> Result<SheetImport> {
+    let mut rows = parse(content, format)?.into_iter();
+    let header = rows
+        .next()
+        .ok_or_else(|| TranslatorError::FileFormatError("The sheet is empty".to_string()))?;
+
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let key_column = column(KEY_COLUMN).ok_or_else(|| {
+        TranslatorError::FileFormatError(format!("The sheet has no '{}' column", KEY_COLUMN))
+    })?;
+    let source_column = column(SOURCE_COLUMN);
+    let fingerprint_column = column(FINGERPRINT_COLUMN);
+    // A column is a language when it has a state column beside it, as exported, or names a
+    // language of the catalog; anything else a reviewer added is left alone
+    let catalog_languages: HashSet<&String> = file
+        .data
+        .strings
+        .values()
+        .flat_map(|entry| entry.localizations.keys())
+        .collect();
+    let mut languages: Vec<(String, usize, Option<usize>)> = Vec::new();
+    let mut ignored_columns = Vec::new();
+    for (i, name) in header.iter().enumerate() {
+        if [KEY_COLUMN, COMMENT_COLUMN, SOURCE_COLUMN, FINGERPRINT_COLUMN].contains(&name.as_str())
+            || name.ends_with(STATE_SUFFIX)
+            || name.is_empty()
+        {
+            continue;
+        }
+        let state_column = column(&format!("{}{}", name, STATE_SUFFIX));
+        if state_column.is_some() || catalog_languages.contains(name) {
+            languages.push((name.clone(), i, state_column));
+        } else {
+            ignored_columns.push(name.clone());
+        }
+    }
+    let language_names: Vec<String> = languages.iter().map(|(name, _, _)| name.clone()).collect();
+
+    // Rows of one key stay together, in sheet order
+    let mut keys: Vec<(String, Vec<PathRow>)> = Vec::new();
+    for row in rows {
+        let cell = |i: usize| row.get(i).cloned().unwrap_or_default();
+        let id = cell(key_column);
+        if id.is_empty() {
+            continue;
+        }
+        let (key, path) = split_variation_id(&id);
+        match keys.last_mut().filter(|(last, _)| last == key) {
+            Some((_, paths)) => paths.push((path.to_string(), row.clone())),
+            None => keys.push((key.to_string(), vec![(path.to_string(), row.clone())])),
+        }
+    }
+
+    let mut summary = SheetImport {
+        ignored_columns,
+        ..Default::default()
+    };
+    for (key, paths) in keys {
+        if !file.data.strings.contains_key(&key) {
+            summary.conflicts.push(Conflict {
+                key,
+                reason: "key no longer exists in the catalog".to_string(),
+            });
+            continue;
+        }
+
+        let snapshot = KeySnapshot::new(file, &key, &language_names);
+        let exported = fingerprint_column.and_then(|i| paths[0].1.get(i).cloned());
+        let catalog_changed = exported
+            .as_ref()
+            .is_some_and(|fingerprint| snapshot.changed_since(fingerprint));
+
+        // Without a fingerprint, a changed source is the only sign the row is outdated
+        let sources: HashMap<String, String> = snapshot.source.flatten(None).into_iter().collect();
+        let source_changed = source_column.is_some_and(|i| {
+            paths.iter().any(|(path, row)| {
+                let cell = row.get(i).map(String::as_str).unwrap_or_default();
+                sources.get(path).is_some_and(|current| current != cell)
+            })
+        });
+
+        let mut updates = Vec::new();
+        for (language, column, state_column) in &languages {
+            let (current, current_state) = &snapshot.targets[language];
+            let categories = plural_categories(language);
+
+            let cells: Vec<(&String, String)> = paths
+                .iter()
+                .filter(|(path, _)| {
+                    plural_category(path).is_none_or(|category| categories.contains(&category))
+                })
+                .map(|(path, row)| (path, row.get(*column).cloned().unwrap_or_default()))
+                .collect();
+            let text_changed = cells.iter().any(|(path, cell)| {
+                current.get(*path).map(String::as_str).unwrap_or_default() != cell
+            });
+            let state = state_column
+                .and_then(|i| paths[0].1.get(i))
+                .and_then(|cell| UnitState::parse(cell.trim()));
+            let state_changed = state.is_some() && state != *current_state;
+
+            if !text_changed && !state_changed {
+                continue;
+            }
+            if cells.iter().all(|(_, cell)| cell.is_empty()) {
+                continue;
+            }
+
+            // A human edit approves the translation unless the state was set explicitly
+            let state = match state {
+                Some(state) if state_changed => state,
+                _ => UnitState::Translated,
+            };
+            updates.push((language.clone(), cells, state));
+        }
+
+        if updates.is_empty() {
+            continue;
+        }
+        if catalog_changed || source_changed {
+            summary.conflicts.push(Conflict {
+                key,
+                reason: if source_changed {
+                    "source text changed since export".to_string()
+                } else {
+                    "catalog changed since export".to_string()
+                },
+            });
+            continue;
+        }
+
+        for (language, cells, state) in updates {
+            if let [(path, text)] = cells.as_slice() {
+                if path.is_empty() {
+                    file.add_translation(&key, &language, text, state)?;
+                    summary.updated += 1;
+                    continue;
+                }
+            }
+
+            let mut builder = ValueBuilder::default();
+            for (path, text) in cells.into_iter().filter(|(_, text)| !text.is_empty()) {
+                builder.insert(path, text);
+            }
+            if let Some(value) = builder.build() {
+                file.add_translation_value(&key, &language, &value, state)?;
+                summary.updated += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// A key's source, translations and weakest states as the sheet sees them
+struct KeySnapshot {
+    source: TranslationValue,
+    targets: HashMap<String, (HashMap<String, String>, Option<UnitState>)>,
+    /// Hash of the source, then of each language's translation and state, in sheet order
+    fingerprints: Vec<(String, String)>,
+}
+
+impl KeySnapshot {
+    fn new(file: &XCStringsFile, key: &str, languages: &[String]) -> Self {
+        let entry = &file.data.strings[key];
+        let source = entry
+            .localizations
+            .get(&file.data.source_language)
+            .and_then(|loc| loc.value())
+            .unwrap_or_else(|| TranslationValue::Text(key.to_string()));
+
+        let mut fingerprints = vec![(String::new(), fingerprint(&[source.to_string()]))];
+        let mut targets = HashMap::new();
+        for language in languages {
+            let localization = entry.localizations.get(language);
+            let value = localization.and_then(|loc| loc.value());
+            let state = localization.and_then(|loc| loc.unit_states().into_iter().min_by_key(UnitState::rank));
+
+            let parts = [
+                value.as_ref().map(|v| v.to_string()).unwrap_or_default(),
+                state.map(|s| s.as_str().to_string()).unwrap_or_default(),
+            ];
+            fingerprints.push((language.clone(), fingerprint(&parts)));
+
+            let flattened = value
+                .map(|v| v.flatten(None).into_iter().collect())
+                .unwrap_or_default();
+            targets.insert(language.clone(), (flattened, state));
+        }
+
+        Self {
+            source,
+            targets,
+            fingerprints,
+        }
+    }
+
+    /// The fingerprint cell: the source's hash, then `language=hash` for each language
+    fn fingerprint(&self) -> String {
+        self.fingerprints
+            .iter()
+            .map(|(language, hash)| match language.as_str() {
+                "" => hash.clone(),
+                language => format!("{}={}", language, hash),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Whether the source, or a language both in the sheet and in the exported fingerprint,
+    /// changed since the export. Language columns added or removed later don't count.
+    fn changed_since(&self, exported: &str) -> bool {
+        let mut parts = exported.split_whitespace();
+        let source = parts.next().unwrap_or_default();
+        let exported: HashMap<&str, &str> = parts.filter_map(|part| part.split_once('=')).collect();
+        self.fingerprints.iter().any(|(language, hash)| match language.as_str() {
+            "" => source != hash,
+            language => exported.get(language).is_some_and(|exported| exported != hash),
+        })
+    }
+}
+
+/// Category of a plural variation path such as `plural.few` or `device.mac.plural.one`
+fn plural_category(path: &str) -> Option<&str> {
+    let (rest, category) = path.rsplit_once('.')?;
+    (rest == "plural" || rest.ends_with(".plural")).then_some(category)
+}
+
+/// FNV-1a hash of the parts, stable across runs and Rust versions
+fn fingerprint(parts: &[String]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+fn quote(field: &str, format: SheetFormat) -> String {
+    let needs_quotes = field.contains(format.delimiter())
+        || field.contains('"')
+        || field.contains('\n')
+        || field.contains('\r');
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Parse delimited text into rows of fields, with RFC 4180 quoting
+fn parse(content: &str, format: SheetFormat) -> Result<Vec<Vec<String>>> {
+    let content = content.strip_prefix(BOM).unwrap_or(content);
+    let delimiter = format.delimiter();
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                c => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(TranslatorError::FileFormatError(
+            "Unterminated quoted field".to_string(),
+        ));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xcstrings::tests::load_catalog;
+
+    const CATALOG: &str = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "%lld items" : {
+      "localizations" : {
+        "en" : {
+          "variations" : {
+            "plural" : {
+              "one" : { "stringUnit" : { "state" : "translated", "value" : "%lld item" } },
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld items" } }
+            }
+          }
+        }
+      }
+    },
+    "Save" : {
+      "comment" : "Button, \"save\" action",
+      "localizations" : {
+        "de" : { "stringUnit" : { "state" : "needs_review", "value" : "Sichern" } }
+      }
+    },
+    "Cancel" : {
+      "localizations" : {
+        "de" : { "stringUnit" : { "state" : "translated", "value" : "Abbrechen" } }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#;
+
+    #[test]
+    fn test_export_sheet() {
+        let (_dir, file) = load_catalog(CATALOG);
+        let csv = export(&file, &["de".to_string()], SheetFormat::Csv);
+        let rows = parse(&csv, SheetFormat::Csv).unwrap();
+
+        assert_eq!(rows[0], vec!["key", "comment", "source", "de", "de state", "fingerprint"]);
+        let keys: Vec<&str> = rows[1..].iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(
+            keys,
+            vec!["%lld items|==|plural.one", "%lld items|==|plural.other", "Cancel", "Save"]
+        );
+        assert_eq!(rows[4][1], "Button, \"save\" action");
+        assert_eq!(rows[4][3..5], ["Sichern", "needs_review"]);
+    }
+
+    #[test]
+    fn test_import_sheet() {
+        let (_dir, mut file) = load_catalog(CATALOG);
+        let languages = ["de".to_string()];
+        let mut rows = parse(&export(&file, &languages, SheetFormat::Tsv), SheetFormat::Tsv).unwrap();
+
+        // Reviewer fills the plural forms, fixes "Save" and edits "Cancel"
+        rows[1][3] = "%lld Element".to_string();
+        rows[2][3] = "%lld Elemente".to_string();
+        rows[4][3] = "Speichern".to_string();
+        rows[3][3] = "Abbruch".to_string();
+        // ...while someone else changed "Cancel" in the catalog
+        file.add_translation("Cancel", "de", "Schließen", UnitState::Translated).unwrap();
+
+        let sheet: String = rows.iter().map(|row| row.join("\t") + "\n").collect();
+        let summary = import(&mut file, &sheet, SheetFormat::Tsv).unwrap();
+
+        assert_eq!(summary.updated, 2);
+        assert_eq!(
+            summary.conflicts,
+            vec![Conflict {
+                key: "Cancel".to_string(),
+                reason: "catalog changed since export".to_string(),
+            }]
+        );
+        let de = |key: &str| file.data.strings[key].localizations["de"].clone();
+        assert_eq!(de("%lld items").plural_forms()["other"], "%lld Elemente");
+        assert_eq!(de("Save").string_unit.unwrap().state, "translated");
+        assert_eq!(de("Cancel").string_unit.unwrap().value, "Schließen");
+    }
+
+    #[test]
+    fn test_import_with_changed_language_columns() {
+        let (_dir, mut file) = load_catalog(CATALOG);
+        let languages = ["de".to_string()];
+        let mut rows = parse(&export(&file, &languages, SheetFormat::Tsv), SheetFormat::Tsv).unwrap();
+
+        // The reviewer adds a French column next to the exported German one
+        rows[0].extend(["fr".to_string(), "fr state".to_string()]);
+        for row in rows.iter_mut().skip(1) {
+            row.extend([String::new(), String::new()]);
+        }
+        rows[4][3] = "Speichern".to_string();
+        rows[4][6] = "Enregistrer".to_string();
+
+        let sheet: String = rows.iter().map(|row| row.join("\t") + "\n").collect();
+        let summary = import(&mut file, &sheet, SheetFormat::Tsv).unwrap();
+        assert!(summary.conflicts.is_empty(), "{:?}", summary.conflicts);
+        assert_eq!(summary.updated, 2);
+        assert_eq!(file.get_existing_translation("Save", "fr").as_deref(), Some("Enregistrer"));
+    }
+
+    #[test]
+    fn test_import_column_languages() {
+        let (_dir, mut file) = load_catalog(CATALOG);
+        // "de" is a catalog language, "fr" comes with its state column, "notes" is neither
+        let sheet = "key\tsource\tde\tfr\tfr state\tnotes\n\
+                     Save\tSave\tSpeichern\tEnregistrer\ttranslated\tcheck tone\n";
+        let summary = import(&mut file, sheet, SheetFormat::Tsv).unwrap();
+
+        assert_eq!(summary.updated, 2);
+        assert_eq!(summary.ignored_columns, vec!["notes"]);
+        let localizations = &file.data.strings["Save"].localizations;
+        assert_eq!(localizations["fr"].string_unit.as_ref().unwrap().value, "Enregistrer");
+        assert!(!localizations.contains_key("notes"));
+    }
+}
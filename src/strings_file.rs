@@ -0,0 +1,808 @@
+use crate::error::{Result, TranslatorError};
+use crate::stringsdict::{self, PluralSpec, StringsDictEntry};
+use crate::xcode_json::compare_keys;
+use crate::xcstrings::{Localization, LocalizationEntry, StringUnit, UnitState, XCStringsData};
+use serde_json::Map;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Comment genstrings writes when the developer left none
+const EMPTY_COMMENT: &str = "No comment provided by engineer.";
+
+const STRINGS_EXTENSION: &str = "strings";
+const STRINGSDICT_EXTENSION: &str = "stringsdict";
+
+/// Text encoding of a `.strings` file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringsEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// One `"key" = "value";` pair with the comment written above it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringsEntry {
+    pub key: String,
+    pub value: String,
+    pub comment: Option<String>,
+}
+
+/// Decode a `.strings` file, detecting UTF-16 (with or without a byte order mark) and UTF-8
+pub fn decode(bytes: &[u8]) -> Result<(String, StringsEncoding)> {
+    let encoding = match bytes {
+        [0xFF, 0xFE, ..] => StringsEncoding::Utf16Le,
+        [0xFE, 0xFF, ..] => StringsEncoding::Utf16Be,
+        // Without a BOM, ASCII text in UTF-16 has a zero byte in every pair
+        [a, 0, ..] if *a != 0 && bytes.len().is_multiple_of(2) => StringsEncoding::Utf16Le,
+        [0, b, ..] if *b != 0 && bytes.len().is_multiple_of(2) => StringsEncoding::Utf16Be,
+        _ => StringsEncoding::Utf8,
+    };
+
+    let text = match encoding {
+        StringsEncoding::Utf8 => {
+            let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+            String::from_utf8(bytes.to_vec())
+                .map_err(|e| TranslatorError::FileFormatError(format!("Invalid UTF-8: {}", e)))?
+        }
+        StringsEncoding::Utf16Le | StringsEncoding::Utf16Be => {
+            if !bytes.len().is_multiple_of(2) {
+                return Err(TranslatorError::FileFormatError(
+                    "Invalid UTF-16: odd number of bytes".to_string(),
+                ));
+            }
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| match encoding {
+                    StringsEncoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                    _ => u16::from_be_bytes([pair[0], pair[1]]),
+                })
+                .collect();
+            let units = units.strip_prefix(&[0xFEFF]).unwrap_or(&units);
+            String::from_utf16(units)
+                .map_err(|e| TranslatorError::FileFormatError(format!("Invalid UTF-16: {}", e)))?
+        }
+    };
+
+    Ok((text, encoding))
+}
+
+/// Encode text for a `.strings` file; UTF-16 gets a byte order mark like Xcode writes
+pub fn encode(text: &str, encoding: StringsEncoding) -> Vec<u8> {
+    match encoding {
+        StringsEncoding::Utf8 => text.as_bytes().to_vec(),
+        StringsEncoding::Utf16Le => std::iter::once(0xFEFF)
+            .chain(text.encode_utf16())
+            .flat_map(u16::to_le_bytes)
+            .collect(),
+        StringsEncoding::Utf16Be => std::iter::once(0xFEFF)
+            .chain(text.encode_utf16())
+            .flat_map(u16::to_be_bytes)
+            .collect(),
+    }
+}
+
+/// Parse the contents of a `.strings` file
+pub fn parse(content: &str) -> Result<Vec<StringsEntry>> {
+    let mut parser = Parser {
+        chars: content.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    let mut entries = Vec::new();
+
+    loop {
+        let comment = parser.skip_whitespace_and_comments()?;
+        if parser.peek().is_none() {
+            break;
+        }
+
+        let key = parser.string()?;
+        parser.skip_whitespace_and_comments()?;
+
+        // A bare `"key";` stands for `"key" = "key";`
+        let value = if parser.peek() == Some(';') {
+            key.clone()
+        } else {
+            parser.expect('=')?;
+            parser.skip_whitespace_and_comments()?;
+            parser.string()?
+        };
+        parser.skip_whitespace_and_comments()?;
+        parser.expect(';')?;
+
+        let comment = comment.filter(|c| !c.is_empty() && c != EMPTY_COMMENT);
+        entries.push(StringsEntry { key, value, comment });
+    }
+
+    Ok(entries)
+}
+
+/// Write entries in genstrings' layout: each pair preceded by its comment, separated by blank lines
+pub fn to_string(entries: &[StringsEntry]) -> String {
+    let mut out = String::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        if let Some(comment) = &entry.comment {
+            out.push_str(&format!("/* {} */\n", comment));
+        }
+        out.push_str(&format!("{} = {};\n", quote(&entry.key), quote(&entry.value)));
+    }
+    out
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> TranslatorError {
+        TranslatorError::FileFormatError(format!("{} on line {}", message, self.line))
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(&format!("Expected '{}' but found '{}'", expected, c))),
+            None => Err(self.error(&format!("Expected '{}' but reached end of file", expected))),
+        }
+    }
+
+    /// Skip whitespace and comments, returning the text of the last comment seen
+    fn skip_whitespace_and_comments(&mut self) -> Result<Option<String>> {
+        let mut comment = None;
+        loop {
+            match (self.peek(), self.chars.get(self.pos + 1).copied()) {
+                (Some(c), _) if c.is_whitespace() => {
+                    self.next();
+                }
+                (Some('/'), Some('*')) => {
+                    self.pos += 2;
+                    let mut text = String::new();
+                    loop {
+                        match self.next() {
+                            Some('*') if self.peek() == Some('/') => {
+                                self.next();
+                                break;
+                            }
+                            Some(c) => text.push(c),
+                            None => return Err(self.error("Unterminated comment")),
+                        }
+                    }
+                    comment = Some(text.trim().to_string());
+                }
+                (Some('/'), Some('/')) => {
+                    let mut text = String::new();
+                    while let Some(c) = self.peek().filter(|c| *c != '\n') {
+                        text.push(c);
+                        self.next();
+                    }
+                    comment = Some(text.trim_start_matches('/').trim().to_string());
+                }
+                _ => return Ok(comment),
+            }
+        }
+    }
+
+    /// A quoted string, or an unquoted token as old-style property lists allow
+    fn string(&mut self) -> Result<String> {
+        match self.peek() {
+            Some('"') => self.quoted_string(),
+            Some(c) if is_unquoted_char(c) => {
+                let mut token = String::new();
+                while let Some(c) = self.peek().filter(|c| is_unquoted_char(*c)) {
+                    token.push(c);
+                    self.next();
+                }
+                Ok(token)
+            }
+            Some(c) => Err(self.error(&format!("Unexpected '{}'", c))),
+            None => Err(self.error("Unexpected end of file")),
+        }
+    }
+
+    fn quoted_string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(out),
+                Some('\\') => self.escape(&mut out)?,
+                Some(c) => out.push(c),
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+    }
+
+    fn escape(&mut self, out: &mut String) -> Result<()> {
+        match self.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('a') => out.push('\u{7}'),
+            Some('b') => out.push('\u{8}'),
+            Some('f') => out.push('\u{c}'),
+            Some('v') => out.push('\u{b}'),
+            Some('U') | Some('u') => {
+                let code = self.hex_code()?;
+                // Characters outside the BMP are written as a surrogate pair of escapes
+                if (0xD800..0xDC00).contains(&code)
+                    && self.chars.get(self.pos) == Some(&'\\')
+                    && matches!(self.chars.get(self.pos + 1), Some('U') | Some('u'))
+                {
+                    self.pos += 2;
+                    let low = self.hex_code()?;
+                    let decoded = String::from_utf16(&[code as u16, low as u16])
+                        .map_err(|_| self.error("Invalid surrogate pair"))?;
+                    out.push_str(&decoded);
+                } else {
+                    out.push(char::from_u32(code).ok_or_else(|| self.error("Invalid unicode escape"))?);
+                }
+            }
+            Some(c @ '0'..='7') => {
+                let mut code = c.to_digit(8).unwrap_or(0);
+                for _ in 0..2 {
+                    match self.peek().and_then(|c| c.to_digit(8)) {
+                        Some(digit) => {
+                            code = code * 8 + digit;
+                            self.next();
+                        }
+                        None => break,
+                    }
+                }
+                out.push(char::from_u32(code).ok_or_else(|| self.error("Invalid octal escape"))?);
+            }
+            // `\"`, `\\`, `\'` and any other escaped character stand for themselves
+            Some(c) => out.push(c),
+            None => return Err(self.error("Unterminated escape")),
+        }
+        Ok(())
+    }
+
+    fn hex_code(&mut self) -> Result<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .next()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("Invalid unicode escape"))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+}
+
+fn is_unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '$' | ':' | '/' | '-')
+}
+
+/// A legacy table: `Name.strings` and `Name.stringsdict` in every `xx.lproj` directory
+/// next to each other
+#[derive(Debug, Clone)]
+pub struct StringsTable {
+    /// Directory containing the `.lproj` directories
+    root: PathBuf,
+    /// Table name shared by every language, e.g. `Localizable`
+    name: String,
+    source_language: String,
+    /// Encoding each existing `.strings` file was read with, kept when writing it back
+    encodings: HashMap<String, StringsEncoding>,
+    /// Key order of the source `.strings` file, kept when writing translations
+    key_order: Vec<String>,
+    /// Keys of the source `.stringsdict` in file order, with how each was written
+    plural_keys: Vec<(String, Option<PluralSpec>)>,
+}
+
+impl StringsTable {
+    /// Load the table containing `path` (a source language file, e.g. `en.lproj/Localizable.strings`
+    /// or `en.lproj/Localizable.stringsdict`) together with every translation next to it
+    pub fn load(path: &Path) -> Result<(Self, XCStringsData)> {
+        let lproj = path.parent().unwrap_or_else(|| Path::new("."));
+        let source_language = lproj_language(lproj).ok_or_else(|| {
+            TranslatorError::FileFormatError(format!(
+                "{} is not inside an .lproj directory",
+                path.display()
+            ))
+        })?;
+        let name = path
+            .file_stem()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let root = lproj.parent().unwrap_or_else(|| Path::new(".")).to_path_buf();
+
+        let mut table = Self {
+            root,
+            name,
+            source_language: source_language.clone(),
+            encodings: HashMap::new(),
+            key_order: Vec::new(),
+            plural_keys: Vec::new(),
+        };
+
+        let mut data = XCStringsData {
+            source_language: source_language.clone(),
+            version: "1.0".to_string(),
+            strings: HashMap::new(),
+            extra: Map::new(),
+        };
+
+        // Source first, so its comments and key order win
+        let mut languages = vec![source_language.clone()];
+        languages.extend(
+            table
+                .languages()?
+                .into_iter()
+                .filter(|language| *language != source_language),
+        );
+
+        for language in languages {
+            let is_source = language == source_language;
+
+            let file = table.language_path(&language, STRINGS_EXTENSION);
+            if file.is_file() {
+                let (content, encoding) = decode(&fs::read(&file)?)?;
+                let entries = parse(&content).map_err(|e| {
+                    TranslatorError::FileFormatError(format!("{}: {}", file.display(), e))
+                })?;
+                table.encodings.insert(language.clone(), encoding);
+
+                for entry in entries {
+                    if is_source {
+                        table.key_order.push(entry.key.clone());
+                    }
+                    let localization = Localization {
+                        string_unit: Some(StringUnit {
+                            state: UnitState::Translated.as_str().to_string(),
+                            value: entry.value,
+                            extra: Map::new(),
+                        }),
+                        ..Default::default()
+                    };
+                    insert_localization(&mut data, entry.key, entry.comment, &language, localization);
+                }
+            }
+
+            let file = table.language_path(&language, STRINGSDICT_EXTENSION);
+            if file.is_file() {
+                let entries = stringsdict::parse(&fs::read(&file)?, UnitState::Translated).map_err(|e| {
+                    TranslatorError::FileFormatError(format!("{}: {}", file.display(), e))
+                })?;
+
+                for entry in entries {
+                    if is_source {
+                        table.plural_keys.push((entry.key.clone(), entry.spec));
+                    }
+                    insert_localization(&mut data, entry.key, None, &language, entry.localization);
+                }
+            }
+        }
+
+        Ok((table, data))
+    }
+
+    /// Write every translated language back into its `xx.lproj` directory: plain strings
+    /// into the `.strings` file and plurals into the `.stringsdict`. Source files are never rewritten.
+    pub fn save(&self, data: &XCStringsData) -> Result<()> {
+        let mut languages: Vec<&String> = data
+            .strings
+            .values()
+            .flat_map(|entry| entry.localizations.keys())
+            .filter(|language| **language != self.source_language)
+            .collect();
+        languages.sort();
+        languages.dedup();
+
+        for language in languages {
+            let entries = self.strings_entries_for(data, language);
+            if !entries.is_empty() {
+                let encoding = self
+                    .encodings
+                    .get(language)
+                    .copied()
+                    .unwrap_or(StringsEncoding::Utf8);
+                let path = self.language_path(language, STRINGS_EXTENSION);
+                write_file(&path, &encode(&to_string(&entries), encoding))?;
+            }
+
+            let entries = self.plural_entries_for(data, language);
+            if !entries.is_empty() {
+                let path = self.language_path(language, STRINGSDICT_EXTENSION);
+                write_file(&path, &stringsdict::to_bytes(&entries)?)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every existing file of the table, in any language
+    pub fn existing_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for language in self.languages()? {
+            for extension in [STRINGS_EXTENSION, STRINGSDICT_EXTENSION] {
+                let file = self.language_path(&language, extension);
+                if file.is_file() {
+                    files.push(file);
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    /// Languages with at least one file of the table, sorted
+    fn languages(&self) -> Result<Vec<String>> {
+        let mut languages = BTreeSet::new();
+        for entry in fs::read_dir(&self.root)? {
+            let dir = entry?.path();
+            if let Some(language) = lproj_language(&dir) {
+                let has_file = [STRINGS_EXTENSION, STRINGSDICT_EXTENSION]
+                    .iter()
+                    .any(|extension| dir.join(format!("{}.{}", self.name, extension)).is_file());
+                if has_file {
+                    languages.insert(language);
+                }
+            }
+        }
+        Ok(languages.into_iter().collect())
+    }
+
+    fn language_path(&self, language: &str, extension: &str) -> PathBuf {
+        self.root
+            .join(format!("{}.lproj", language))
+            .join(format!("{}.{}", self.name, extension))
+    }
+
+    fn is_plural_key(&self, key: &str) -> bool {
+        self.plural_keys.iter().any(|(plural_key, _)| plural_key == key)
+    }
+
+    /// Plain strings of one language, in the source file's order followed by any other keys
+    fn strings_entries_for(&self, data: &XCStringsData, language: &str) -> Vec<StringsEntry> {
+        let mut extra_keys: Vec<&String> = data
+            .strings
+            .keys()
+            .filter(|key| !self.key_order.contains(key) && !self.is_plural_key(key))
+            .collect();
+        extra_keys.sort_by(|a, b| compare_keys(a, b));
+
+        self.key_order
+            .iter()
+            .chain(extra_keys)
+            .filter_map(|key| {
+                let entry = data.strings.get(key)?;
+                let unit = entry.localizations.get(language)?.string_unit.as_ref()?;
+                Some(StringsEntry {
+                    key: key.clone(),
+                    value: unit.value.clone(),
+                    comment: entry.comment.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Plural strings of one language, in the source `.stringsdict` order
+    fn plural_entries_for(&self, data: &XCStringsData, language: &str) -> Vec<StringsDictEntry> {
+        self.plural_keys
+            .iter()
+            .filter_map(|(key, spec)| {
+                let localization = data.strings.get(key)?.localizations.get(language)?;
+                Some(StringsDictEntry {
+                    key: key.clone(),
+                    localization: localization.clone(),
+                    spec: spec.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Load a legacy table as String Catalog data the way Xcode's migration writes it: every key
+/// marked `migrated`, and each substitution's argument written as `%arg` in its plural forms
+pub fn migrate(path: &Path) -> Result<XCStringsData> {
+    let (_, mut data) = StringsTable::load(path)?;
+
+    for entry in data.strings.values_mut() {
+        entry.extraction_state = Some("migrated".to_string());
+        for localization in entry.localizations.values_mut() {
+            for substitution in localization.substitutions.iter_mut().flat_map(|subs| subs.values_mut()) {
+                let Some(value_type) = substitution.format_specifier.clone() else {
+                    continue;
+                };
+                let plural = substitution
+                    .variations
+                    .iter_mut()
+                    .flat_map(|variations| variations.plural.iter_mut())
+                    .flat_map(|forms| forms.values_mut());
+                for unit in plural.filter_map(|variation| variation.string_unit.as_mut()) {
+                    unit.value = argument_placeholder(&unit.value, &value_type);
+                }
+            }
+        }
+    }
+
+    Ok(data)
+}
+
+/// Replace the first `%d` / `%1$d` style specifier of `value_type` with `%arg`
+fn argument_placeholder(form: &str, value_type: &str) -> String {
+    let mut search = 0;
+    while let Some(offset) = form[search..].find('%') {
+        let start = search + offset;
+        let rest = &form[start + 1..];
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        let after_position = if digits > 0 && rest[digits..].starts_with('$') {
+            &rest[digits + 1..]
+        } else {
+            rest
+        };
+        if after_position.starts_with(value_type) {
+            let end = form.len() - after_position.len() + value_type.len();
+            return format!("{}%arg{}", &form[..start], &form[end..]);
+        }
+        search = start + 1;
+    }
+    form.to_string()
+}
+
+fn insert_localization(
+    data: &mut XCStringsData,
+    key: String,
+    comment: Option<String>,
+    language: &str,
+    localization: Localization,
+) {
+    let entry = data.strings.entry(key).or_insert_with(|| LocalizationEntry {
+        comment: None,
+        extraction_state: None,
+        should_translate: None,
+        localizations: HashMap::new(),
+        extra: Map::new(),
+    });
+    if entry.comment.is_none() {
+        entry.comment = comment;
+    }
+    entry.localizations.insert(language.to_string(), localization);
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Language of an `xx.lproj` directory
+fn lproj_language(dir: &Path) -> Option<String> {
+    let name = dir.file_name()?.to_str()?;
+    name.strip_suffix(".lproj")
+        .filter(|language| !language.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xcstrings::{UnitNode, Variations};
+    use tempfile::tempdir;
+
+    const SOURCE: &str = r#"/* Title of the welcome screen */
+"welcome_title" = "Welcome";
+
+// Shown under the title
+"welcome_body" = "Tap \"Start\" to begin.\nIt only takes a minute.";
+
+/* No comment provided by engineer. */
+"Cancel";
+"emoji" = "\U2764\UFE0F \UD83C\UDF0D";
+"#;
+
+    #[test]
+    fn test_parse_strings() {
+        let entries = parse(SOURCE).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].key, "welcome_title");
+        assert_eq!(entries[0].comment.as_deref(), Some("Title of the welcome screen"));
+        assert_eq!(entries[1].value, "Tap \"Start\" to begin.\nIt only takes a minute.");
+        assert_eq!(entries[1].comment.as_deref(), Some("Shown under the title"));
+        assert_eq!(entries[2].value, "Cancel");
+        assert_eq!(entries[2].comment, None);
+        assert_eq!(entries[3].value, "❤️ 🌍");
+
+        let reparsed = parse(&to_string(&entries)).unwrap();
+        assert_eq!(reparsed, entries);
+
+        assert!(parse("\"key\" = \"value\"").is_err());
+    }
+
+    #[test]
+    fn test_encoding_detection() {
+        let text = "\"key\" = \"värde\";\n";
+        for encoding in [StringsEncoding::Utf8, StringsEncoding::Utf16Le, StringsEncoding::Utf16Be] {
+            assert_eq!(decode(&encode(text, encoding)).unwrap(), (text.to_string(), encoding));
+        }
+
+        // UTF-16 written without a byte order mark
+        let bare: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(decode(&bare).unwrap(), (text.to_string(), StringsEncoding::Utf16Le));
+    }
+
+    #[test]
+    fn test_table_round_trip() {
+        let dir = tempdir().unwrap();
+        let en = dir.path().join("en.lproj");
+        let de = dir.path().join("de.lproj");
+        fs::create_dir_all(&en).unwrap();
+        fs::create_dir_all(&de).unwrap();
+        fs::write(en.join("Localizable.strings"), SOURCE).unwrap();
+        fs::write(
+            de.join("Localizable.strings"),
+            encode("\"welcome_title\" = \"Willkommen\";\n", StringsEncoding::Utf16Le),
+        )
+        .unwrap();
+
+        let (table, mut data) = StringsTable::load(&en.join("Localizable.strings")).unwrap();
+        assert_eq!(data.source_language, "en");
+        let title = &data.strings["welcome_title"];
+        assert_eq!(title.comment.as_deref(), Some("Title of the welcome screen"));
+        assert_eq!(
+            title.localizations["de"].string_unit.as_ref().unwrap().value,
+            "Willkommen"
+        );
+
+        for (language, key, value) in [("de", "Cancel", "Abbrechen"), ("ja", "welcome_title", "ようこそ")] {
+            data.strings.get_mut(key).unwrap().localizations.insert(
+                language.to_string(),
+                Localization {
+                    string_unit: Some(StringUnit {
+                        state: "translated".to_string(),
+                        value: value.to_string(),
+                        extra: Map::new(),
+                    }),
+                    ..Default::default()
+                },
+            );
+        }
+        table.save(&data).unwrap();
+
+        let (de_content, encoding) = decode(&fs::read(de.join("Localizable.strings")).unwrap()).unwrap();
+        assert_eq!(encoding, StringsEncoding::Utf16Le);
+        assert_eq!(
+            de_content,
+            "/* Title of the welcome screen */\n\"welcome_title\" = \"Willkommen\";\n\n\"Cancel\" = \"Abbrechen\";\n"
+        );
+        let ja = fs::read_to_string(dir.path().join("ja.lproj/Localizable.strings")).unwrap();
+        assert_eq!(ja, "/* Title of the welcome screen */\n\"welcome_title\" = \"ようこそ\";\n");
+        // The source file is left untouched
+        assert_eq!(fs::read_to_string(en.join("Localizable.strings")).unwrap(), SOURCE);
+    }
+
+    #[test]
+    fn test_table_with_stringsdict() {
+        let dir = tempdir().unwrap();
+        let en = dir.path().join("en.lproj");
+        fs::create_dir_all(&en).unwrap();
+        fs::write(en.join("Localizable.strings"), "\"title\" = \"Inbox\";\n").unwrap();
+        let plural = Localization {
+            variations: Some(Variations::from_plural_forms(
+                &[("one", "%d message"), ("other", "%d messages")]
+                    .iter()
+                    .map(|(c, v)| (c.to_string(), v.to_string()))
+                    .collect(),
+                UnitState::Translated,
+            )),
+            ..Default::default()
+        };
+        let source = [StringsDictEntry {
+            key: "%d messages".to_string(),
+            localization: plural,
+            spec: None,
+        }];
+        fs::write(en.join("Localizable.stringsdict"), stringsdict::to_bytes(&source).unwrap()).unwrap();
+
+        let (table, mut data) = StringsTable::load(&en.join("Localizable.stringsdict")).unwrap();
+        assert_eq!(data.strings.len(), 2);
+        assert_eq!(table.existing_files().unwrap().len(), 2);
+
+        let mut ja = source[0].localization.clone();
+        ja.variations = Some(Variations::from_plural_forms(
+            &[("other".to_string(), "%d 件のメッセージ".to_string())].into_iter().collect(),
+            UnitState::Translated,
+        ));
+        data.strings.get_mut("%d messages").unwrap().localizations.insert("ja".to_string(), ja);
+        table.save(&data).unwrap();
+
+        // Only the plural key exists in Japanese, so no .strings file is written for it
+        assert!(!dir.path().join("ja.lproj/Localizable.strings").exists());
+        let written = fs::read(dir.path().join("ja.lproj/Localizable.stringsdict")).unwrap();
+        let entries = stringsdict::parse(&written, UnitState::Translated).unwrap();
+        assert_eq!(entries[0].key, "%d messages");
+        assert_eq!(entries[0].localization.plural_forms()["other"], "%d 件のメッセージ");
+    }
+
+    #[test]
+    fn test_migrate_table() {
+        let dir = tempdir().unwrap();
+        let en = dir.path().join("en.lproj");
+        fs::create_dir_all(&en).unwrap();
+        fs::write(en.join("Localizable.strings"), SOURCE).unwrap();
+        let source = stringsdict::parse(
+            br#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+	<key>files_in_folders</key>
+	<dict>
+		<key>NSStringLocalizedFormatKey</key>
+		<string>%@ keeps %#@files@ in %#@folders@</string>
+		<key>files</key>
+		<dict>
+			<key>NSStringFormatSpecTypeKey</key>
+			<string>NSStringPluralRuleType</string>
+			<key>NSStringFormatValueTypeKey</key>
+			<string>d</string>
+			<key>one</key>
+			<string>%d file</string>
+			<key>other</key>
+			<string>%1$d files</string>
+		</dict>
+	</dict>
+</dict>
+</plist>"#,
+            UnitState::Translated,
+        )
+        .unwrap();
+        fs::write(en.join("Localizable.stringsdict"), stringsdict::to_bytes(&source).unwrap()).unwrap();
+
+        let data = migrate(&en.join("Localizable.strings")).unwrap();
+        assert_eq!(data.strings.len(), 5);
+        assert!(data
+            .strings
+            .values()
+            .all(|entry| entry.extraction_state.as_deref() == Some("migrated")));
+
+        let files = &data.strings["files_in_folders"].localizations["en"]
+            .substitutions
+            .as_ref()
+            .unwrap()["files"];
+        assert_eq!(files.format_specifier.as_deref(), Some("d"));
+        // The owner's name is argument 1, so the count is argument 2
+        assert_eq!(serde_json::to_value(files).unwrap()["argNum"], 2);
+        assert_eq!(files.plural_forms()["one"], "%arg file");
+        assert_eq!(files.plural_forms()["other"], "%arg files");
+    }
+}
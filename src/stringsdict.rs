@@ -0,0 +1,305 @@
+use crate::error::{Result, TranslatorError};
+use crate::format_specifiers::FormatArguments;
+use crate::plural_rules::PLURAL_CATEGORIES;
+use crate::xcstrings::{
+    substitution_names, Localization, Substitution, TranslationValue, UnitNode, UnitState,
+    Variations,
+};
+use plist::{Dictionary, Value};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+const FORMAT_KEY: &str = "NSStringLocalizedFormatKey";
+const SPEC_TYPE_KEY: &str = "NSStringFormatSpecTypeKey";
+const VALUE_TYPE_KEY: &str = "NSStringFormatValueTypeKey";
+const PLURAL_RULE_TYPE: &str = "NSStringPluralRuleType";
+
+/// How a key whose whole format is one plural variable (`%#@items@`) was written,
+/// so it can be written back the same way
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluralSpec {
+    pub variable: String,
+    /// `NSStringFormatValueTypeKey`, e.g. `d` or `lld`
+    pub value_type: Option<String>,
+}
+
+/// One key of a `.stringsdict` file
+#[derive(Debug, Clone)]
+pub struct StringsDictEntry {
+    pub key: String,
+    /// Plural variations when the format is a single variable, otherwise a string unit with substitutions
+    pub localization: Localization,
+    pub spec: Option<PluralSpec>,
+}
+
+/// Parse a `.stringsdict` property list (XML or binary), marking every unit with `state`
+pub fn parse(bytes: &[u8], state: UnitState) -> Result<Vec<StringsDictEntry>> {
+    let root = Value::from_reader(Cursor::new(bytes))
+        .map_err(|e| TranslatorError::FileFormatError(format!("Invalid plist: {}", e)))?;
+    let root = root.as_dictionary().ok_or_else(|| {
+        TranslatorError::FileFormatError("The plist root is not a dictionary".to_string())
+    })?;
+
+    let mut entries = Vec::new();
+    for (key, value) in root {
+        let dict = value.as_dictionary().ok_or_else(|| {
+            TranslatorError::FileFormatError(format!("Entry '{}' is not a dictionary", key))
+        })?;
+        let format = dict
+            .get(FORMAT_KEY)
+            .and_then(Value::as_string)
+            .ok_or_else(|| {
+                TranslatorError::FileFormatError(format!("Entry '{}' has no {}", key, FORMAT_KEY))
+            })?;
+
+        let names = substitution_names(format);
+        let variables: Vec<(String, &Dictionary)> = names
+            .iter()
+            .filter_map(|name| {
+                let variable = dict.get(name)?.as_dictionary()?;
+                let is_plural = variable.get(SPEC_TYPE_KEY).and_then(Value::as_string)
+                    == Some(PLURAL_RULE_TYPE);
+                is_plural.then(|| (name.clone(), variable))
+            })
+            .collect();
+
+        let mut localization = Localization::default();
+        let spec = match variables.as_slice() {
+            // The whole string is one plural: store it as plain plural variations
+            [(name, variable)] if format.trim() == format!("%#@{}@", name) => {
+                localization.set_value(&TranslationValue::Plural(plural_forms(variable)), state);
+                Some(PluralSpec {
+                    variable: name.clone(),
+                    value_type: value_type(variable),
+                })
+            }
+            _ => {
+                localization.set_value(&TranslationValue::Text(format.to_string()), state);
+                let positions = FormatArguments::parse(format).substitutions;
+                let substitutions = variables
+                    .iter()
+                    .map(|(name, variable)| {
+                        let arg_num = positions.iter().find(|(n, _)| n == name).map(|(_, p)| *p as u32);
+                        let substitution = Substitution {
+                            arg_num,
+                            format_specifier: value_type(variable),
+                            variations: Some(Variations::from_plural_forms(&plural_forms(variable), state)),
+                            ..Default::default()
+                        };
+                        (name.clone(), substitution)
+                    })
+                    .collect::<HashMap<_, _>>();
+                if !substitutions.is_empty() {
+                    localization.substitutions = Some(substitutions);
+                }
+                None
+            }
+        };
+
+        entries.push(StringsDictEntry {
+            key: key.clone(),
+            localization,
+            spec,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Write entries as an XML `.stringsdict`. Entries without plural forms are left out.
+pub fn to_bytes(entries: &[StringsDictEntry]) -> Result<Vec<u8>> {
+    let mut root = Dictionary::new();
+    for entry in entries {
+        if let Some(value) = entry_value(entry) {
+            root.insert(entry.key.clone(), value);
+        }
+    }
+
+    let mut bytes = Vec::new();
+    Value::Dictionary(root)
+        .to_writer_xml(&mut bytes)
+        .map_err(|e| TranslatorError::FileFormatError(format!("Could not write plist: {}", e)))?;
+    bytes.push(b'\n');
+    Ok(bytes)
+}
+
+fn entry_value(entry: &StringsDictEntry) -> Option<Value> {
+    let localization = &entry.localization;
+    let mut dict = Dictionary::new();
+
+    let forms = localization.plural_forms();
+    if !forms.is_empty() {
+        let spec = entry.spec.clone().unwrap_or_else(|| PluralSpec {
+            variable: "value".to_string(),
+            value_type: None,
+        });
+        dict.insert(FORMAT_KEY.to_string(), Value::String(format!("%#@{}@", spec.variable)));
+        dict.insert(spec.variable, variable_value(&forms, spec.value_type.as_deref()));
+        return Some(Value::Dictionary(dict));
+    }
+
+    let format = &localization.string_unit.as_ref()?.value;
+    let substitutions = localization.substitutions.as_ref()?;
+    dict.insert(FORMAT_KEY.to_string(), Value::String(format.clone()));
+    for name in substitution_names(format) {
+        if let Some(substitution) = substitutions.get(&name) {
+            let value = variable_value(
+                &substitution.plural_forms(),
+                substitution.format_specifier.as_deref(),
+            );
+            dict.insert(name, value);
+        }
+    }
+    Some(Value::Dictionary(dict))
+}
+
+fn variable_value(forms: &HashMap<String, String>, value_type: Option<&str>) -> Value {
+    let mut variable = Dictionary::new();
+    variable.insert(SPEC_TYPE_KEY.to_string(), Value::String(PLURAL_RULE_TYPE.to_string()));
+    if let Some(value_type) = value_type {
+        variable.insert(VALUE_TYPE_KEY.to_string(), Value::String(value_type.to_string()));
+    }
+    for category in PLURAL_CATEGORIES {
+        if let Some(form) = forms.get(category) {
+            variable.insert(category.to_string(), Value::String(form.clone()));
+        }
+    }
+    Value::Dictionary(variable)
+}
+
+fn plural_forms(variable: &Dictionary) -> HashMap<String, String> {
+    PLURAL_CATEGORIES
+        .iter()
+        .filter_map(|category| {
+            let form = variable.get(category)?.as_string()?;
+            Some((category.to_string(), form.to_string()))
+        })
+        .filter(|(_, form)| !form.is_empty())
+        .collect()
+}
+
+fn value_type(variable: &Dictionary) -> Option<String> {
+    variable
+        .get(VALUE_TYPE_KEY)
+        .and_then(Value::as_string)
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRINGSDICT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>%d items</key>
+	<dict>
+		<key>NSStringLocalizedFormatKey</key>
+		<string>%#@items@</string>
+		<key>items</key>
+		<dict>
+			<key>NSStringFormatSpecTypeKey</key>
+			<string>NSStringPluralRuleType</string>
+			<key>NSStringFormatValueTypeKey</key>
+			<string>d</string>
+			<key>one</key>
+			<string>%d item</string>
+			<key>other</key>
+			<string>%d items</string>
+		</dict>
+	</dict>
+	<key>%d files in %d folders</key>
+	<dict>
+		<key>NSStringLocalizedFormatKey</key>
+		<string>%#@files@ in %#@folders@</string>
+		<key>files</key>
+		<dict>
+			<key>NSStringFormatSpecTypeKey</key>
+			<string>NSStringPluralRuleType</string>
+			<key>NSStringFormatValueTypeKey</key>
+			<string>d</string>
+			<key>one</key>
+			<string>%d file</string>
+			<key>other</key>
+			<string>%d files</string>
+		</dict>
+		<key>folders</key>
+		<dict>
+			<key>NSStringFormatSpecTypeKey</key>
+			<string>NSStringPluralRuleType</string>
+			<key>NSStringFormatValueTypeKey</key>
+			<string>d</string>
+			<key>one</key>
+			<string>%d folder</string>
+			<key>other</key>
+			<string>%d folders</string>
+		</dict>
+	</dict>
+</dict>
+</plist>
+"#;
+
+    #[test]
+    fn test_parse_stringsdict() {
+        let entries = parse(STRINGSDICT.as_bytes(), UnitState::Translated).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let items = &entries[0];
+        assert_eq!(items.key, "%d items");
+        assert_eq!(items.localization.plural_forms()["one"], "%d item");
+        assert_eq!(
+            items.spec,
+            Some(PluralSpec {
+                variable: "items".to_string(),
+                value_type: Some("d".to_string()),
+            })
+        );
+
+        let files = &entries[1];
+        assert!(files.spec.is_none());
+        match files.localization.value() {
+            Some(TranslationValue::Substitutions { text, substitutions }) => {
+                assert_eq!(text, "%#@files@ in %#@folders@");
+                assert_eq!(substitutions["folders"]["other"], "%d folders");
+            }
+            other => panic!("unexpected value: {:?}", other),
+        }
+        let folders = &files.localization.substitutions.as_ref().unwrap()["folders"];
+        assert_eq!(folders.arg_num, Some(2));
+        assert_eq!(folders.format_specifier.as_deref(), Some("d"));
+    }
+
+    #[test]
+    fn test_substitution_arg_nums() {
+        let variable = |name: &str| {
+            format!(
+                "<key>{0}</key><dict><key>NSStringFormatSpecTypeKey</key><string>NSStringPluralRuleType</string>\
+                 <key>NSStringFormatValueTypeKey</key><string>lld</string>\
+                 <key>one</key><string>%lld {0}</string><key>other</key><string>%lld {0}s</string></dict>",
+                name
+            )
+        };
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0"><dict>
+<key>owner_files</key><dict><key>NSStringLocalizedFormatKey</key><string>%@ has %#@file@</string>{}</dict>
+<key>files_owner</key><dict><key>NSStringLocalizedFormatKey</key><string>%2$#@file@ belong to %1$@</string>{}</dict>
+</dict></plist>"#,
+            variable("file"),
+            variable("file")
+        );
+        let entries = parse(plist.as_bytes(), UnitState::Translated).unwrap();
+        for entry in &entries {
+            let file = &entry.localization.substitutions.as_ref().unwrap()["file"];
+            assert_eq!(file.arg_num, Some(2), "{}", entry.key);
+        }
+    }
+
+    #[test]
+    fn test_stringsdict_round_trip() {
+        let entries = parse(STRINGSDICT.as_bytes(), UnitState::Translated).unwrap();
+        let written = String::from_utf8(to_bytes(&entries).unwrap()).unwrap();
+        assert_eq!(written, STRINGSDICT);
+    }
+}
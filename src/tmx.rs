@@ -0,0 +1,284 @@
+use crate::error::{Result, TranslatorError};
+use crate::translation_memory::MemoryEntry;
+use crate::xcstrings::{UnitState, XCStringsFile};
+use crate::xliff;
+use crate::xml::{attribute, escape, escape_attribute};
+use quick_xml::escape::unescape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+
+/// One `<tu>`: a string and its translations, as `(language, text)` pairs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TmxUnit {
+    /// `tuid`: the catalog key, or the variation id of one of its variations
+    pub id: Option<String>,
+    pub note: Option<String>,
+    pub variants: Vec<(String, String)>,
+}
+
+/// A TMX 1.4 document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TmxDocument {
+    /// `srclang` of the header; `*all*` when any variant can be the source
+    pub source_language: String,
+    pub units: Vec<TmxUnit>,
+}
+
+/// Units of every approved (`translated`) translation in `languages`, with plural, device
+/// and substitution variations as units of their own
+pub fn export_units(file: &XCStringsFile, languages: &[String]) -> Vec<TmxUnit> {
+    let source_language = &file.data.source_language;
+    let mut units: Vec<TmxUnit> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for language in languages.iter().filter(|l| *l != source_language) {
+        for unit in xliff::export_units(file, language) {
+            let Some(target) = unit.target.filter(|t| !t.is_empty()) else {
+                continue;
+            };
+            if xliff::unit_state(unit.state.as_deref()) != UnitState::Translated {
+                continue;
+            }
+
+            let i = *positions.entry(unit.id.clone()).or_insert_with(|| {
+                units.push(TmxUnit {
+                    id: Some(unit.id.clone()),
+                    note: unit.note.clone(),
+                    variants: vec![(source_language.clone(), unit.source.clone())],
+                });
+                units.len() - 1
+            });
+            units[i].variants.push((language.clone(), target));
+        }
+    }
+
+    units
+}
+
+/// Memory entries of a document: every variant paired with the unit's source variant
+pub fn memory_entries(document: &TmxDocument, origin: &str) -> Vec<MemoryEntry> {
+    let mut entries = Vec::new();
+    for unit in &document.units {
+        let source = unit
+            .variants
+            .iter()
+            .find(|(language, _)| language.eq_ignore_ascii_case(&document.source_language))
+            .or_else(|| unit.variants.first());
+        let Some((source_language, source)) = source else {
+            continue;
+        };
+
+        for (language, text) in &unit.variants {
+            if language == source_language {
+                continue;
+            }
+            entries.push(MemoryEntry {
+                source_language: source_language.clone(),
+                target_language: language.clone(),
+                source: source.clone(),
+                target: text.clone(),
+                origin: Some(origin.to_string()),
+            });
+        }
+    }
+    entries
+}
+
+/// Write a TMX 1.4 document
+pub fn to_string(document: &TmxDocument) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<tmx version=\"1.4\">\n");
+    out.push_str(&format!(
+        "  <header creationtool=\"Rosetta\" creationtoolversion=\"{}\" datatype=\"plaintext\" segtype=\"sentence\" adminlang=\"en\" srclang=\"{}\" o-tmf=\"xcstrings\"/>\n",
+        env!("CARGO_PKG_VERSION"),
+        escape_attribute(&document.source_language)
+    ));
+    out.push_str("  <body>\n");
+    for unit in &document.units {
+        match &unit.id {
+            Some(id) => out.push_str(&format!("    <tu tuid=\"{}\">\n", escape_attribute(id))),
+            None => out.push_str("    <tu>\n"),
+        }
+        if let Some(note) = &unit.note {
+            out.push_str(&format!("      <note>{}</note>\n", escape(note)));
+        }
+        for (language, text) in &unit.variants {
+            out.push_str(&format!(
+                "      <tuv xml:lang=\"{}\">\n        <seg>{}</seg>\n      </tuv>\n",
+                escape_attribute(language),
+                escape(text)
+            ));
+        }
+        out.push_str("    </tu>\n");
+    }
+    out.push_str("  </body>\n");
+    out.push_str("</tmx>\n");
+    out
+}
+
+fn xml_error(e: impl std::fmt::Display) -> TranslatorError {
+    TranslatorError::FileFormatError(format!("Invalid TMX: {}", e))
+}
+
+/// Text of an element, keeping the native code inside inline markup such as `<ph>%s</ph>`
+fn read_segment(reader: &mut Reader<&[u8]>, element: &BytesStart) -> Result<String> {
+    let end = element.to_end().into_owned();
+    let raw = reader.read_text(end.name()).map_err(xml_error)?;
+
+    let mut text = String::with_capacity(raw.len());
+    let mut in_tag = false;
+    for c in raw.xml10_content().chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    Ok(unescape(&text).map_err(xml_error)?.into_owned())
+}
+
+/// Parse a TMX 1.1–1.4 document
+pub fn parse(content: &str) -> Result<TmxDocument> {
+    let mut reader = Reader::from_str(content);
+    let mut document = TmxDocument::default();
+    let mut unit: Option<TmxUnit> = None;
+    let mut language: Option<String> = None;
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) | Event::Empty(element) if element.local_name().as_ref() == "header" => {
+                document.source_language = attribute(&element, "srclang")?.unwrap_or_default();
+            }
+            Event::Start(element) => match element.local_name().as_ref() {
+                "tu" => {
+                    unit = Some(TmxUnit {
+                        id: attribute(&element, "tuid")?,
+                        ..Default::default()
+                    })
+                }
+                // TMX 1.1 uses `lang`, later versions `xml:lang`
+                "tuv" => language = attribute(&element, "xml:lang")?.or(attribute(&element, "lang")?),
+                "seg" => {
+                    let text = read_segment(&mut reader, &element)?;
+                    if let (Some(unit), Some(language)) = (unit.as_mut(), language.as_ref()) {
+                        unit.variants.push((language.clone(), text));
+                    }
+                }
+                "note" => {
+                    let note = read_segment(&mut reader, &element)?;
+                    if let Some(unit) = unit.as_mut().filter(|_| language.is_none()) {
+                        unit.note = Some(note).filter(|n| !n.is_empty());
+                    }
+                }
+                _ => {}
+            },
+            Event::End(element) => match element.local_name().as_ref() {
+                "tuv" => language = None,
+                "tu" => document.units.extend(unit.take()),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xcstrings::tests::load_catalog;
+
+    const CATALOG: &str = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "%lld items" : {
+      "comment" : "Items in the <cart>",
+      "localizations" : {
+        "en" : {
+          "variations" : {
+            "plural" : {
+              "one" : { "stringUnit" : { "state" : "translated", "value" : "%lld item" } },
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld items" } }
+            }
+          }
+        },
+        "ja" : {
+          "variations" : {
+            "plural" : {
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld 個" } }
+            }
+          }
+        },
+        "ru" : {
+          "variations" : {
+            "plural" : {
+              "one" : { "stringUnit" : { "state" : "translated", "value" : "%lld товар" } },
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld товара" } }
+            }
+          }
+        }
+      }
+    },
+    "Hello" : {
+      "localizations" : {
+        "ja" : { "stringUnit" : { "state" : "needs_review", "value" : "こんにちは" } },
+        "ru" : { "stringUnit" : { "state" : "translated", "value" : "Привет" } }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#;
+
+    #[test]
+    fn test_export_approved_units() {
+        let (_dir, file) = load_catalog(CATALOG);
+
+        let units = export_units(&file, &["ja".to_string(), "ru".to_string()]);
+        let ids: Vec<&str> = units.iter().filter_map(|u| u.id.as_deref()).collect();
+        assert_eq!(
+            ids,
+            vec!["%lld items|==|plural.other", "%lld items|==|plural.one", "Hello"]
+        );
+        assert_eq!(units[0].variants.len(), 3);
+        // The needs_review Japanese greeting is not approved
+        assert_eq!(
+            units[2].variants,
+            vec![("en".to_string(), "Hello".to_string()), ("ru".to_string(), "Привет".to_string())]
+        );
+
+        let document = TmxDocument {
+            source_language: "en".to_string(),
+            units,
+        };
+        assert_eq!(parse(&to_string(&document)).unwrap(), document);
+    }
+
+    #[test]
+    fn test_parse_vendor_tmx() {
+        let document = parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<tmx version="1.4">
+  <header creationtool="Vendor" srclang="en-US" datatype="plaintext" segtype="sentence" adminlang="en-US" o-tmf="tm"/>
+  <body>
+    <tu>
+      <tuv xml:lang="de-DE"><seg>Datei <ph x="1">%s</ph> &amp; mehr</seg></tuv>
+      <tuv xml:lang="en-US"><seg>File <ph x="1">%s</ph> &amp; more</seg></tuv>
+    </tu>
+  </body>
+</tmx>"#,
+        )
+        .unwrap();
+
+        let entries = memory_entries(&document, "vendor.tmx");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].source_language, "en-US");
+        assert_eq!(entries[0].source, "File %s & more");
+        assert_eq!(entries[0].target_language, "de-DE");
+        assert_eq!(entries[0].target, "Datei %s & mehr");
+    }
+}
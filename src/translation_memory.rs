@@ -0,0 +1,290 @@
+use crate::error::{Result, TranslatorError};
+use dirs::config_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A source string and an approved translation of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryEntry {
+    pub source_language: String,
+    pub target_language: String,
+    pub source: String,
+    pub target: String,
+    /// Where the pair came from: a catalog or an imported TMX file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+}
+
+impl MemoryEntry {
+    fn index_key(&self) -> (String, String, String) {
+        (
+            self.source_language.to_lowercase(),
+            self.target_language.to_lowercase(),
+            self.source.clone(),
+        )
+    }
+}
+
+/// What `TranslationMemory::add` did with a pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryUpdate {
+    Added,
+    Updated,
+    Unchanged,
+}
+
+/// Approved translations shared by every project, stored under the config directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TranslationMemory {
+    entries: Vec<MemoryEntry>,
+    #[serde(skip)]
+    index: HashMap<(String, String, String), usize>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl TranslationMemory {
+    /// `<config dir>/rosetta/translation_memory.json`, next to the config file
+    pub fn default_path() -> Result<PathBuf> {
+        let mut path = config_dir().ok_or_else(|| {
+            TranslatorError::FileFormatError("Could not find config directory".to_string())
+        })?;
+        path.push("rosetta");
+        path.push("translation_memory.json");
+        Ok(path)
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from(Self::default_path()?)
+    }
+
+    /// Load a memory file; a missing file is an empty memory
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut memory = if path.exists() {
+            serde_json::from_str::<Self>(&fs::read_to_string(path)?)?
+        } else {
+            Self::default()
+        };
+        memory.path = path.to_path_buf();
+        memory.index = memory
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.index_key(), i))
+            .collect();
+        Ok(memory)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Store a pair, replacing the translation of the same source string
+    pub fn add(&mut self, entry: MemoryEntry) -> MemoryUpdate {
+        if entry.source.trim().is_empty() || entry.target.trim().is_empty() {
+            return MemoryUpdate::Unchanged;
+        }
+        match self.index.get(&entry.index_key()) {
+            Some(&i) if self.entries[i].target == entry.target => MemoryUpdate::Unchanged,
+            Some(&i) => {
+                self.entries[i] = entry;
+                MemoryUpdate::Updated
+            }
+            None => {
+                self.index.insert(entry.index_key(), self.entries.len());
+                self.entries.push(entry);
+                MemoryUpdate::Added
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Pairs between two languages; `ja` also matches memory entries in `ja-JP` and vice versa
+    fn entries_for<'a: 'b, 'b>(
+        &'a self,
+        source_language: &'b str,
+        target_language: &'b str,
+    ) -> impl Iterator<Item = &'a MemoryEntry> + 'b {
+        self.entries.iter().filter(move |entry| {
+            languages_match(&entry.source_language, source_language)
+                && languages_match(&entry.target_language, target_language)
+        })
+    }
+
+    /// The stored translation of exactly this source string
+    pub fn lookup(&self, source_language: &str, target_language: &str, source: &str) -> Option<&MemoryEntry> {
+        let key = (
+            source_language.to_lowercase(),
+            target_language.to_lowercase(),
+            source.to_string(),
+        );
+        match self.index.get(&key) {
+            Some(&i) => Some(&self.entries[i]),
+            None => self
+                .entries_for(source_language, target_language)
+                .find(|entry| entry.source == source),
+        }
+    }
+
+    /// Translations of similar source strings, best first. Exact matches are left to `lookup`.
+    pub fn fuzzy_matches(
+        &self,
+        source_language: &str,
+        target_language: &str,
+        source: &str,
+        limit: usize,
+    ) -> Vec<MemoryMatch> {
+        let source: Vec<char> = source.chars().collect();
+        if source.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<MemoryMatch> = Vec::new();
+        for entry in self.entries_for(source_language, target_language) {
+            let candidate: Vec<char> = entry.source.chars().collect();
+            let longest = source.len().max(candidate.len());
+            // The length difference alone is a lower bound on the distance
+            if similarity(source.len().abs_diff(candidate.len()), longest) < FUZZY_THRESHOLD {
+                continue;
+            }
+
+            let score = similarity(edit_distance(&source, &candidate), longest);
+            if (FUZZY_THRESHOLD..100).contains(&score) {
+                matches.push(MemoryMatch {
+                    source: entry.source.clone(),
+                    target: entry.target.clone(),
+                    score,
+                });
+            }
+        }
+
+        matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+        matches.dedup_by(|a, b| a.source == b.source && a.target == b.target);
+        matches.truncate(limit);
+        matches
+    }
+}
+
+/// Lowest similarity, in percent, for a memory entry to count as a fuzzy match
+pub const FUZZY_THRESHOLD: u8 = 70;
+
+/// A memory entry whose source resembles the string being translated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryMatch {
+    pub source: String,
+    pub target: String,
+    /// Similarity of the sources in percent, from edit distance
+    pub score: u8,
+}
+
+/// `en_US`, `en-us` and `en-US` are the same language, and a bare language matches any of its regions
+pub fn languages_match(a: &str, b: &str) -> bool {
+    let a = a.replace('_', "-").to_lowercase();
+    let b = b.replace('_', "-").to_lowercase();
+    let (short, long) = if a.len() <= b.len() { (&a, &b) } else { (&b, &a) };
+    long == short || (long.starts_with(short.as_str()) && long[short.len()..].starts_with('-'))
+}
+
+fn similarity(distance: usize, longest: usize) -> u8 {
+    if longest == 0 {
+        return 100;
+    }
+    (100 - distance * 100 / longest) as u8
+}
+
+/// Levenshtein distance over characters
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn entry(source: &str, target: &str) -> MemoryEntry {
+        MemoryEntry {
+            source_language: "en".to_string(),
+            target_language: "ja".to_string(),
+            source: source.to_string(),
+            target: target.to_string(),
+            origin: None,
+        }
+    }
+
+    #[test]
+    fn test_add_and_reload() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("rosetta").join("translation_memory.json");
+        let mut memory = TranslationMemory::load_from(&path).unwrap();
+        assert!(memory.entries.is_empty());
+
+        assert_eq!(memory.add(entry("Save", "保存")), MemoryUpdate::Added);
+        assert_eq!(memory.add(entry("Save", "保存")), MemoryUpdate::Unchanged);
+        assert_eq!(memory.add(entry("Save", "保存する")), MemoryUpdate::Updated);
+        assert_eq!(memory.add(entry("Cancel", "キャンセル")), MemoryUpdate::Added);
+        assert_eq!(memory.add(entry("Empty", " ")), MemoryUpdate::Unchanged);
+        memory.save().unwrap();
+
+        let memory = TranslationMemory::load_from(&path).unwrap();
+        assert_eq!(memory.entries.len(), 2);
+        assert_eq!(memory.entries[0].target, "保存する");
+    }
+
+    #[test]
+    fn test_lookup_and_fuzzy_matches() {
+        let mut memory = TranslationMemory::default();
+        memory.add(entry("Delete this item?", "この項目を削除しますか？"));
+        memory.add(entry("Delete all items?", "すべての項目を削除しますか？"));
+        memory.add(entry("Settings", "設定"));
+        memory.add(MemoryEntry {
+            source_language: "en-US".to_string(),
+            target_language: "ja-JP".to_string(),
+            ..entry("Open", "開く")
+        });
+
+        assert_eq!(memory.lookup("en", "ja", "Settings").unwrap().target, "設定");
+        assert_eq!(memory.lookup("en", "ja", "Open").unwrap().target, "開く");
+        assert!(memory.lookup("en", "ko", "Settings").is_none());
+        assert!(memory.lookup("en", "ja", "settings").is_none());
+
+        let matches = memory.fuzzy_matches("en", "ja", "Delete these items?", 5);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].source, "Delete this item?");
+        assert!(matches[0].score >= matches[1].score);
+        assert!(memory.fuzzy_matches("en", "ja", "Delete this item?", 5).len() == 1);
+        assert!(memory.fuzzy_matches("en", "ja", "Profile", 5).is_empty());
+
+        assert!(languages_match("zh_Hans", "zh-hans"));
+        assert!(!languages_match("zh-Hans", "zh-Hant"));
+        assert_eq!(edit_distance(&['a', 'b', 'c'], &['a', 'c']), 1);
+    }
+}
@@ -1,55 +1,27 @@
+use crate::backend::{Completion, TranslationBackend};
 use crate::error::{Result, TranslatorError};
-use crate::xcstrings::TranslationContext;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use crate::format_specifiers;
+use crate::glossary::Glossary;
+use crate::prompts::Prompts;
+use crate::plural_rules::{plural_categories, sort_categories};
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
+use crate::xcstrings::{substitution_names, TranslationContext, TranslationValue};
+use futures::stream::{self, StreamExt};
 use std::collections::HashMap;
 
-#[derive(Serialize, Deserialize)]
-struct ChatMessage {
-    role: String,
-    content: String,
-}
-
-#[derive(Serialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<ChatMessage>,
-    max_tokens: u32,
-    temperature: f32,
-}
-
-#[derive(Deserialize)]
-struct ChatChoice {
-    message: ChatMessage,
-}
-
-#[derive(Deserialize)]
-struct ChatResponse {
-    choices: Vec<ChatChoice>,
-}
-
 pub struct Translator {
-    client: Client,
-    base_url: String,
-    model: String,
+    backend: Box<dyn TranslationBackend>,
+    limiter: RateLimiter,
+    retry: RetryPolicy,
+    keys_per_request: usize,
+    prompts: Prompts,
+    glossary: Glossary,
     language_map: HashMap<String, String>,
 }
 
 impl Translator {
-    pub fn new(api_key: String, base_url: String, model: String) -> Self {
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            "Authorization",
-            format!("Bearer {}", api_key).parse().unwrap(),
-        );
-        headers.insert("Content-Type", "application/json".parse().unwrap());
-
-        let client = Client::builder()
-            .default_headers(headers)
-            .timeout(std::time::Duration::from_secs(60))
-            .build()
-            .expect("Failed to create HTTP client");
-
+    pub fn new(backend: Box<dyn TranslationBackend>) -> Self {
         let mut language_map = HashMap::new();
         language_map.insert("ja".to_string(), "Japanese".to_string());
         language_map.insert("zh-Hans".to_string(), "Simplified Chinese".to_string());
@@ -95,96 +67,95 @@ impl Translator {
   
 
         Self {
-            client,
-            base_url,
-            model,
+            backend,
+            limiter: RateLimiter::new(1),
+            retry: RetryPolicy::default(),
+            keys_per_request: 1,
+            prompts: Prompts::default(),
+            glossary: Glossary::default(),
             language_map,
         }
     }
 
-    pub async fn translate_text(
-        &self,
-        text: &str,
-        target_language: &str,
-        context: Option<&str>,
-    ) -> Result<String> {
-        let target_lang_name = self
-            .language_map
-            .get(target_language)
-            .map(|s| s.as_str())
-            .unwrap_or(target_language);
-
-        let context_part = context
-            .map(|c| format!("Context: {}\n", c))
-            .unwrap_or_default();
-
-        let prompt = format!(
-            r#"Please translate the following text to {}.
-This is a localization string for an iOS app about time widgets and clocks.
-
-Original text: "{}"
-{}
-Requirements:
-- Keep the translation natural and appropriate for mobile app users
-- Maintain any formatting like %@ placeholders
-- For technical terms, use commonly accepted translations
-- Keep brand names like "Hands Time" unchanged unless there's a standard localized version
-
-Please provide only the translated text, no explanations."#,
-            target_lang_name, text, context_part
-        );
+    /// Allow up to `concurrency` requests in flight at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.limiter = RateLimiter::new(concurrency);
+        self
+    }
 
-        let request = ChatRequest {
-            model: self.model.clone(),
-            messages: vec![ChatMessage {
-                role: "user".to_string(),
-                content: prompt,
-            }],
-            max_tokens: 500,
-            temperature: 0.3,
-        };
+    pub fn concurrency(&self) -> usize {
+        self.limiter.concurrency()
+    }
 
-        let response = self
-            .client
-            .post(&format!("{}/chat/completions", self.base_url))
-            .json(&request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status().as_u16();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(TranslatorError::ApiError {
-                status,
-                message: error_text,
-            });
-        }
+    /// Describe the app and use the configured templates in every prompt
+    pub fn with_prompts(mut self, prompts: Prompts) -> Self {
+        self.prompts = prompts;
+        self
+    }
 
-        let chat_response: ChatResponse = response.json().await?;
+    /// Mandate the glossary's translations of the terms each source string contains
+    pub fn with_glossary(mut self, glossary: Glossary) -> Self {
+        self.glossary = glossary;
+        self
+    }
 
-        let translation = chat_response
-            .choices
-            .first()
-            .ok_or_else(|| TranslatorError::TranslationFailed("No choices in response".to_string()))?
-            .message
-            .content
-            .trim()
-            .to_string();
+    pub fn glossary(&self) -> &Glossary {
+        &self.glossary
+    }
 
-        // Remove surrounding quotes if present
-        let translation = if translation.starts_with('"') && translation.ends_with('"') {
-            translation[1..translation.len() - 1].to_string()
-        } else {
-            translation
-        };
+    /// Pack up to `keys` plain strings into each batch request
+    pub fn with_keys_per_request(mut self, keys: usize) -> Self {
+        self.keys_per_request = keys.max(1);
+        self
+    }
 
-        if translation.is_empty() {
-            return Err(TranslatorError::TranslationFailed(
-                "Empty translation received".to_string(),
-            ));
+    /// Split keys into the groups `translate_keys_with_context` sends together: plain strings
+    /// in groups of up to `keys_per_request`, and every other key on its own
+    pub fn request_groups<'a, T>(
+        &self,
+        items: Vec<(T, &'a TranslationContext)>,
+    ) -> Vec<Vec<(T, &'a TranslationContext)>> {
+        let mut groups = Vec::new();
+        let mut plain = Vec::new();
+        for item in items {
+            if is_plain(item.1) {
+                plain.push(item);
+                if plain.len() == self.keys_per_request {
+                    groups.push(std::mem::take(&mut plain));
+                }
+            } else {
+                groups.push(vec![item]);
+            }
         }
+        if !plain.is_empty() {
+            groups.push(plain);
+        }
+        groups
+    }
 
-        Ok(translation)
+    /// Send a prompt through the rate limiter, retrying rate-limited and transient failures
+    async fn complete(&self, completion: Completion) -> Result<String> {
+        self.retry
+            .run(|| async {
+                let _permit = self.limiter.acquire().await;
+                let result = self.backend.complete(&completion).await;
+                self.limiter.record(&result);
+                result
+            })
+            .await
+    }
+
+    pub async fn translate_text(
+        &self,
+        text: &str,
+        target_language: &str,
+        context: Option<&str>,
+    ) -> Result<String> {
+        let context_info = context.map(|c| format!("Context: {}", c)).unwrap_or_default();
+        let prompt = self.prompts.context(self.language_name(target_language), text, &context_info);
+
+        let response = self.complete(Completion::new(prompt, 500, 0.3)).await?;
+        clean_translation(&response)
     }
 
     pub async fn batch_translate(
@@ -192,17 +163,15 @@ Please provide only the translated text, no explanations."#,
         texts: &[String],
         target_language: &str,
     ) -> Vec<Result<String>> {
-        let mut results = Vec::new();
-        
-        for text in texts {
-            let result = self.translate_text(text, target_language, None).await;
-            results.push(result);
-            
-            // Small delay to avoid rate limiting
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-        }
-        
-        results
+        stream::iter(texts)
+            .map(|text| self.translate_text(text, target_language, None))
+            .buffered(self.concurrency())
+            .collect()
+            .await
+    }
+
+    fn language_name<'a>(&'a self, language: &'a str) -> &'a str {
+        self.language_map.get(language).map(|s| s.as_str()).unwrap_or(language)
     }
 
     /// Enhanced translation method with rich context
@@ -211,11 +180,36 @@ Please provide only the translated text, no explanations."#,
         context: &TranslationContext,
         target_language: &str,
     ) -> Result<String> {
-        let target_lang_name = self
-            .language_map
-            .get(target_language)
-            .map(|s| s.as_str())
-            .unwrap_or(target_language);
+        let target_lang_name = self.language_name(target_language);
+        let context_info = self.context_info(context, target_language);
+        let mut problem = String::new();
+
+        // A translation that drops or changes a format specifier would crash at runtime, so
+        // it is sent back once with the problem spelled out, then rejected
+        for attempt in 0..FORMAT_ATTEMPTS {
+            let mut prompt = self.prompts.context(target_lang_name, &context.source_text, &context_info);
+            if attempt > 0 {
+                prompt.push_str(&format!(
+                    "\n\n注意: 上一次的译文格式化占位符有误（{}）。译文必须包含与原文相同的占位符，参数的数量和类型都不能改变。",
+                    problem
+                ));
+            }
+
+            // Lower temperature for more consistent translations
+            let response = self.complete(Completion::new(prompt, 800, 0.2)).await?;
+            let translation = clean_translation(&response)?;
+            match check_format(context, &translation) {
+                Ok(()) => return Ok(translation),
+                Err(reason) => problem = reason,
+            }
+        }
+
+        Err(TranslatorError::FormatMismatch(problem))
+    }
+
+    /// The key, comment, usage and reference translations that go with a source string
+    fn context_info(&self, context: &TranslationContext, target_language: &str) -> String {
+        let target_lang_name = self.language_name(target_language);
 
         // Build context information
         let mut context_parts = Vec::new();
@@ -245,6 +239,45 @@ Please provide only the translated text, no explanations."#,
             };
             context_parts.push(format!("用途类别: {}", category_desc));
         }
+
+        // Add target device when the string varies by device
+        if let Some(ref device) = context.device {
+            context_parts.push(format!("目标设备: {}", device_description(device)));
+        }
+
+        // Substitution tokens must be kept verbatim in the outer string
+        if !context.substitution_sources.is_empty() {
+            let tokens: Vec<String> = substitution_names(&context.source_text)
+                .iter()
+                .map(|name| format!("%#@{}@", name))
+                .collect();
+            context_parts.push(format!("替换标记: {} (必须原样保留，不要翻译或改动)", tokens.join(", ")));
+        }
+
+        // Add substitution information when translating one of its plural branches
+        if let Some(ref name) = context.substitution {
+            context_parts.push(format!(
+                "替换参数: %#@{}@ (这是外层字符串中该参数的复数分支，保留 %arg 等占位符)",
+                name
+            ));
+        }
+
+        // Add plural information when translating a single plural form
+        if let Some(ref category) = context.plural_category {
+            context_parts.push(format!(
+                "复数类别: {} (按照{}的CLDR复数规则，只翻译该类别对应的形式)",
+                category, target_lang_name
+            ));
+            let mut source_categories: Vec<&String> = context.plural_sources.keys().collect();
+            sort_categories(&mut source_categories);
+            context_parts.push("原文各复数形式:".to_string());
+            for source_category in source_categories {
+                context_parts.push(format!(
+                    "  - {}: \"{}\"",
+                    source_category, context.plural_sources[source_category]
+                ));
+            }
+        }
         
         // Add existing translations as reference
         if !context.existing_translations.is_empty() {
@@ -254,100 +287,509 @@ Please provide only the translated text, no explanations."#,
                 context_parts.push(format!("  - {}: \"{}\"", lang_name, translation));
             }
         }
+
+        // Add the glossary's mandated translations of terms in the source
+        let terms = self.glossary.terms_in(&context.source_text, target_language);
+        if !terms.is_empty() {
+            context_parts.push("术语表 (以下术语必须使用指定译法):".to_string());
+            for term in terms {
+                match term.target {
+                    Some(target) => context_parts.push(format!("  - \"{}\" → \"{}\"", term.source, target)),
+                    None => context_parts.push(format!("  - \"{}\" → 保持原文，不要翻译", term.source)),
+                }
+            }
+        }
+
+        // Add approved translations of similar strings from the translation memory
+        if !context.memory_matches.is_empty() {
+            context_parts.push(format!("翻译记忆参考 (相似原文的已审核{}译文，保持术语和风格一致):", target_lang_name));
+            for memory_match in &context.memory_matches {
+                context_parts.push(format!(
+                    "  - {}% \"{}\" → \"{}\"",
+                    memory_match.score, memory_match.source, memory_match.target
+                ));
+            }
+        }
         
-        let context_info = context_parts.join("\n");
+        context_parts.join("\n")
+    }
 
-        let prompt = format!(
-            r#"请将以下iOS应用本地化字符串翻译为{target_lang}。
+    /// Translate several keys, sending the plain strings among them as one request that
+    /// answers with a JSON object keyed by string key.
+    ///
+    /// Keys missing from the answer or with an empty or non-string value, keys with plural,
+    /// device or substitution forms, and every key of a request that failed for a reason other
+    /// than credentials or quota, are translated one by one instead. Results are in the order
+    /// of `contexts`.
+    pub async fn translate_keys_with_context(
+        &self,
+        contexts: &[&TranslationContext],
+        target_language: &str,
+    ) -> Vec<Result<TranslationValue>> {
+        let packed: Vec<&TranslationContext> = contexts.iter().copied().filter(|c| is_plain(c)).collect();
+        let mut translations = if packed.len() > 1 {
+            match self.translate_packed(&packed, target_language).await {
+                Ok(translations) => translations,
+                // Credentials and quota fail every request alike, so don't send one per key
+                Err(error @ (TranslatorError::Unauthorized { .. } | TranslatorError::QuotaExceeded { .. })) => {
+                    let message = error.to_string();
+                    return contexts
+                        .iter()
+                        .map(|_| Err(TranslatorError::TranslationFailed(message.clone())))
+                        .collect();
+                }
+                // Other failures may be down to the packed request itself, such as an answer
+                // cut off at the token limit, so each key gets a request of its own
+                Err(_) => HashMap::new(),
+            }
+        } else {
+            HashMap::new()
+        };
 
-翻译信息:
-{context_info}
+        let results = contexts.iter().map(|context| {
+            let translation = translations.remove(&context.key);
+            async move {
+                match translation {
+                    Some(text) => Ok(TranslationValue::Text(text)),
+                    None => self.translate_value_with_context(context, target_language).await,
+                }
+            }
+        });
+        futures::future::join_all(results).await
+    }
+
+    /// One request for several plain strings; the answer holds the valid translations only
+    async fn translate_packed(
+        &self,
+        contexts: &[&TranslationContext],
+        target_language: &str,
+    ) -> Result<HashMap<String, String>> {
+        let target_lang_name = self.language_name(target_language);
+        let entries: Vec<String> = contexts
+            .iter()
+            .enumerate()
+            .map(|(i, context)| {
+                format!(
+                    "[{}]\n{}\n原文: \"{}\"",
+                    i + 1,
+                    self.context_info(context, target_language),
+                    context.source_text
+                )
+            })
+            .collect();
+
+        let prompt = self.prompts.batch(target_lang_name, contexts.len(), &entries.join("\n\n"));
+
+        let keys: Vec<String> = contexts.iter().map(|c| c.key.clone()).collect();
+        let max_tokens = (200 + 300 * contexts.len()).min(8192) as u32;
+        let completion = Completion::new(prompt, max_tokens, 0.2).with_json_keys(keys.clone());
+        let response = self.complete(completion).await?;
+
+        // Translations with broken format specifiers go through the single-key retry instead
+        let mut translations = keyed_translations(&response, &keys);
+        for context in contexts {
+            if let Some(text) = translations.get(&context.key) {
+                if check_format(context, text).is_err() {
+                    translations.remove(&context.key);
+                }
+            }
+        }
+        Ok(translations)
+    }
 
-原文: "{source_text}"
+    /// Translate a key into every form the target language requires.
+    ///
+    /// Device-specific keys are translated once per device variant, and plural keys once per
+    /// CLDR category of the target language, using the matching source form (or `other` when
+    /// the source language lacks that category).
+    pub async fn translate_value_with_context(
+        &self,
+        context: &TranslationContext,
+        target_language: &str,
+    ) -> Result<TranslationValue> {
+        if context.device_variants.is_empty() {
+            return self.translate_forms_with_context(context, target_language).await;
+        }
 
-翻译要求:
-- 保持翻译自然流畅，符合{target_lang}使用习惯
-- 保留所有格式化占位符（如 %@, %d, {{}}等）
-- 技术术语使用标准翻译
-- 品牌名称如"Hands Time"保持不变，除非有官方本地化版本
-- 参考其他语言的翻译风格保持一致性
-- 根据用途类别选择合适的语言风格和正式程度
+        let mut devices = HashMap::new();
+        for variant in &context.device_variants {
+            let device = variant.device.clone().unwrap_or_else(|| "other".to_string());
+            let value = self.translate_forms_with_context(variant, target_language).await?;
+            devices.insert(device, value);
+        }
 
-请只提供翻译结果，不要包含解释。"#,
-            target_lang = target_lang_name,
-            context_info = context_info,
-            source_text = context.source_text
-        );
+        Ok(TranslationValue::Device(devices))
+    }
 
-        let request = ChatRequest {
-            model: self.model.clone(),
-            messages: vec![ChatMessage {
-                role: "user".to_string(),
-                content: prompt,
-            }],
-            max_tokens: 800,
-            temperature: 0.2, // Lower temperature for more consistent translations
-        };
+    /// Translate a plain, plural or substituted context (no device variants)
+    async fn translate_forms_with_context(
+        &self,
+        context: &TranslationContext,
+        target_language: &str,
+    ) -> Result<TranslationValue> {
+        if !context.substitution_sources.is_empty() {
+            return self.translate_substitutions_with_context(context, target_language).await;
+        }
 
-        let response = self
-            .client
-            .post(&format!("{}/chat/completions", self.base_url))
-            .json(&request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status().as_u16();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(TranslatorError::ApiError {
-                status,
-                message: error_text,
-            });
+        if context.plural_sources.is_empty() {
+            return self
+                .translate_with_context(context, target_language)
+                .await
+                .map(TranslationValue::Text);
         }
 
-        let chat_response: ChatResponse = response.json().await?;
+        self.translate_plural_forms(context, target_language)
+            .await
+            .map(TranslationValue::Plural)
+    }
 
-        let translation = chat_response
-            .choices
-            .first()
-            .ok_or_else(|| TranslatorError::TranslationFailed("No choices in response".to_string()))?
-            .message
-            .content
-            .trim()
-            .to_string();
+    /// Translate the outer string and every plural branch of each substitution
+    async fn translate_substitutions_with_context(
+        &self,
+        context: &TranslationContext,
+        target_language: &str,
+    ) -> Result<TranslationValue> {
+        // The `%#@name@` tokens tie the outer string to its substitutions; `translate_with_context`
+        // has checked they survived
+        let text = self.translate_with_context(context, target_language).await?;
+
+        let mut names: Vec<&String> = context.substitution_sources.keys().collect();
+        names.sort();
+
+        let mut substitutions = HashMap::new();
+        for name in names {
+            let mut branch_context = context.clone();
+            branch_context.substitution = Some(name.clone());
+            branch_context.plural_sources = context.substitution_sources[name].clone();
+            branch_context.substitution_sources = HashMap::new();
+
+            let forms = self.translate_plural_forms(&branch_context, target_language).await?;
+            substitutions.insert(name.clone(), forms);
+        }
 
-        // Remove surrounding quotes if present
-        let translation = if translation.starts_with('"') && translation.ends_with('"') {
-            translation[1..translation.len() - 1].to_string()
-        } else {
-            translation
-        };
+        Ok(TranslationValue::Substitutions { text, substitutions })
+    }
 
-        if translation.is_empty() {
-            return Err(TranslatorError::TranslationFailed(
-                "Empty translation received".to_string(),
-            ));
+    /// Translate each CLDR plural category the target language requires
+    async fn translate_plural_forms(
+        &self,
+        context: &TranslationContext,
+        target_language: &str,
+    ) -> Result<HashMap<String, String>> {
+        let mut forms = HashMap::new();
+        for category in plural_categories(target_language) {
+            let source_text = context
+                .plural_sources
+                .get(*category)
+                .or_else(|| context.plural_sources.get("other"))
+                .cloned()
+                .unwrap_or_else(|| context.source_text.clone());
+
+            let mut form_context = context.clone();
+            form_context.source_text = source_text;
+            form_context.plural_category = Some(category.to_string());
+
+            let translation = self.translate_with_context(&form_context, target_language).await?;
+            forms.insert(category.to_string(), translation);
         }
 
-        Ok(translation)
+        Ok(forms)
     }
 
-    /// Batch translation with context
+    /// Translate several contexts concurrently; results keep the order of `contexts`
     pub async fn batch_translate_with_context(
         &self,
         contexts: &[TranslationContext],
         target_language: &str,
     ) -> Vec<Result<String>> {
-        let mut results = Vec::new();
-        
-        for context in contexts {
-            let result = self.translate_with_context(context, target_language).await;
-            results.push(result);
-            
-            // Small delay to avoid rate limiting
-            tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+        stream::iter(contexts)
+            .map(|context| self.translate_with_context(context, target_language))
+            .buffered(self.concurrency())
+            .collect()
+            .await
+    }
+}
+
+/// Requests made for one translation before format specifier problems reject it
+const FORMAT_ATTEMPTS: usize = 2;
+
+/// Check a translation's format specifiers against its source; a plural form is checked
+/// against all of the source's forms
+fn check_format(context: &TranslationContext, translation: &str) -> std::result::Result<(), String> {
+    if context.plural_category.is_some() && !context.plural_sources.is_empty() {
+        let sources: Vec<&str> = context.plural_sources.values().map(String::as_str).collect();
+        format_specifiers::check_forms(&sources, translation)
+    } else {
+        format_specifiers::check(&context.source_text, translation)
+    }
+}
+
+/// A single string, with no plural, device or substitution forms
+fn is_plain(context: &TranslationContext) -> bool {
+    context.plural_sources.is_empty()
+        && context.device_variants.is_empty()
+        && context.substitution_sources.is_empty()
+}
+
+/// The non-empty string values of `keys` in a JSON object answer. The object may be wrapped in
+/// a code fence or, by some models, in a single outer object.
+fn keyed_translations(response: &str, keys: &[String]) -> HashMap<String, String> {
+    let json = match (response.find('{'), response.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &response[start..=end],
+        _ => return HashMap::new(),
+    };
+    let Ok(mut object) = serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(json) else {
+        return HashMap::new();
+    };
+    if object.len() == 1 && !keys.iter().any(|key| object.contains_key(key)) {
+        if let Some(serde_json::Value::Object(inner)) = object.values().next() {
+            object = inner.clone();
         }
-        
-        results
     }
-}
\ No newline at end of file
+
+    keys.iter()
+        .filter_map(|key| {
+            let text = clean_translation(object.get(key)?.as_str()?).ok()?;
+            Some((key.clone(), text))
+        })
+        .collect()
+}
+
+/// Trim the model's answer and drop quotes it wrapped the translation in
+fn clean_translation(response: &str) -> Result<String> {
+    let translation = response.trim();
+    let translation = if translation.len() >= 2 && translation.starts_with('"') && translation.ends_with('"') {
+        &translation[1..translation.len() - 1]
+    } else {
+        translation
+    };
+
+    if translation.is_empty() {
+        return Err(TranslatorError::TranslationFailed(
+            "Empty translation received".to_string(),
+        ));
+    }
+
+    Ok(translation.to_string())
+}
+
+/// Describe an Xcode device type for the prompt, including length constraints
+fn device_description(device: &str) -> &str {
+    match device {
+        "iphone" => "iPhone",
+        "ipad" => "iPad",
+        "ipod" => "iPod touch",
+        "mac" => "Mac",
+        "applewatch" => "Apple Watch（屏幕极小，译文必须尽可能简短）",
+        "appletv" => "Apple TV（远距离观看，保持简洁）",
+        "applevision" => "Apple Vision Pro",
+        "other" => "其他设备",
+        _ => device,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    /// Answers keyed requests with `keyed` and single-key requests with "single"
+    struct ScriptedBackend {
+        keyed: String,
+        /// Keys of each request, `None` for single-key ones
+        requests: Arc<Mutex<Vec<Option<Vec<String>>>>>,
+    }
+
+    #[async_trait]
+    impl TranslationBackend for ScriptedBackend {
+        fn name(&self) -> &str {
+            "Scripted"
+        }
+
+        fn model(&self) -> &str {
+            "scripted"
+        }
+
+        async fn complete(&self, completion: &Completion) -> Result<String> {
+            self.requests.lock().unwrap().push(completion.json_keys.clone());
+            Ok(match completion.json_keys {
+                Some(_) => self.keyed.clone(),
+                None => "single".to_string(),
+            })
+        }
+    }
+
+    /// Fails keyed requests with `error` and answers single-key ones with "single"
+    struct FailingKeyedBackend {
+        error: fn() -> TranslatorError,
+        requests: Arc<Mutex<Vec<Option<Vec<String>>>>>,
+    }
+
+    #[async_trait]
+    impl TranslationBackend for FailingKeyedBackend {
+        fn name(&self) -> &str {
+            "Failing"
+        }
+
+        fn model(&self) -> &str {
+            "failing"
+        }
+
+        async fn complete(&self, completion: &Completion) -> Result<String> {
+            self.requests.lock().unwrap().push(completion.json_keys.clone());
+            match completion.json_keys {
+                Some(_) => Err((self.error)()),
+                None => Ok("single".to_string()),
+            }
+        }
+    }
+
+    fn context(key: &str) -> TranslationContext {
+        TranslationContext {
+            key: key.to_string(),
+            key_meaning: None,
+            comment: None,
+            source_text: key.to_string(),
+            existing_translations: HashMap::new(),
+            usage_category: None,
+            plural_sources: HashMap::new(),
+            plural_category: None,
+            device: None,
+            device_variants: Vec::new(),
+            substitution_sources: HashMap::new(),
+            substitution: None,
+            memory_matches: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_keyed_translations() {
+        let keys = vec!["Save".to_string(), "Cancel".to_string(), "Done".to_string()];
+        let response = "```json\n{\"Save\": \"保存\", \"Cancel\": \"\", \"Done\": 3, \"Extra\": \"x\"}\n```";
+        let translations = keyed_translations(response, &keys);
+        assert_eq!(translations.len(), 1);
+        assert_eq!(translations["Save"], "保存");
+
+        let wrapped = r#"{"translations": {"Save": "保存", "Done": "完了"}}"#;
+        assert_eq!(keyed_translations(wrapped, &keys).len(), 2);
+        assert!(keyed_translations("保存", &keys).is_empty());
+        assert!(keyed_translations("{not json}", &keys).is_empty());
+    }
+
+    #[test]
+    fn test_request_groups() {
+        let translator = Translator::new(Box::new(ScriptedBackend {
+            keyed: String::new(),
+            requests: Arc::default(),
+        }))
+        .with_keys_per_request(2);
+        let mut plural = context("%lld items");
+        plural.plural_sources.insert("other".to_string(), "%lld items".to_string());
+        let contexts = [context("A"), plural, context("B"), context("C")];
+
+        let groups = translator.request_groups(contexts.iter().enumerate().collect());
+        let indices: Vec<Vec<usize>> = groups.iter().map(|g| g.iter().map(|(i, _)| *i).collect()).collect();
+        assert_eq!(indices, vec![vec![1], vec![0, 2], vec![3]]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_keys_fall_back_to_single_requests() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let backend = ScriptedBackend {
+            keyed: r#"{"A": "あ", "B": ""}"#.to_string(),
+            requests: requests.clone(),
+        };
+        let translator = Translator::new(Box::new(backend)).with_keys_per_request(3);
+        let contexts = [context("A"), context("B"), context("C")];
+        let contexts: Vec<&TranslationContext> = contexts.iter().collect();
+
+        let results = translator.translate_keys_with_context(&contexts, "ja").await;
+        let values: Vec<TranslationValue> = results.into_iter().map(|r| r.unwrap()).collect();
+        assert_eq!(
+            values,
+            vec![
+                TranslationValue::Text("あ".to_string()),
+                TranslationValue::Text("single".to_string()),
+                TranslationValue::Text("single".to_string()),
+            ]
+        );
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].as_ref().unwrap(), &["A", "B", "C"]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_packed_request_falls_back_to_single_requests() {
+        let contexts = [context("A"), context("B")];
+        let contexts: Vec<&TranslationContext> = contexts.iter().collect();
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let backend = FailingKeyedBackend {
+            error: || TranslatorError::ApiError {
+                status: 400,
+                message: "max_tokens is too large".to_string(),
+            },
+            requests: requests.clone(),
+        };
+        let translator = Translator::new(Box::new(backend)).with_keys_per_request(2);
+        let results = translator.translate_keys_with_context(&contexts, "ja").await;
+        assert!(results.iter().all(|r| matches!(r, Ok(TranslationValue::Text(text)) if text == "single")));
+        assert_eq!(requests.lock().unwrap().len(), 3);
+
+        // A rejected API key fails the other keys without sending them
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let backend = FailingKeyedBackend {
+            error: || TranslatorError::Unauthorized {
+                status: 401,
+                message: "invalid key".to_string(),
+            },
+            requests: requests.clone(),
+        };
+        let translator = Translator::new(Box::new(backend)).with_keys_per_request(2);
+        let results = translator.translate_keys_with_context(&contexts, "ja").await;
+        assert!(results.iter().all(|r| matches!(r, Err(TranslatorError::TranslationFailed(_)))));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_format_mismatch_is_retried_then_rejected() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let backend = ScriptedBackend {
+            keyed: r#"{"%lld items": "項目", "Done": "完了"}"#.to_string(),
+            requests: requests.clone(),
+        };
+        let translator = Translator::new(Box::new(backend)).with_keys_per_request(2);
+        let contexts = [context("%lld items"), context("Done")];
+        let contexts: Vec<&TranslationContext> = contexts.iter().collect();
+
+        let results = translator.translate_keys_with_context(&contexts, "ja").await;
+        match &results[0] {
+            Err(TranslatorError::FormatMismatch(reason)) => assert_eq!(reason, "missing %lld"),
+            other => panic!("expected a format mismatch, got {:?}", other),
+        }
+        assert_eq!(results[1].as_ref().unwrap(), &TranslationValue::Text("完了".to_string()));
+        // One packed request, then two single-key attempts for the rejected key
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_glossary_terms_in_context_info() {
+        let glossary: Glossary = serde_json::from_str(
+            r#"{ "terms": { "Focus Mode": { "ja": "集中モード" }, "Streak": { "ja": "連続記録" } }, "do_not_translate": ["Trailmate"] }"#,
+        )
+        .unwrap();
+        let translator = Translator::new(Box::new(ScriptedBackend {
+            keyed: String::new(),
+            requests: Arc::default(),
+        }))
+        .with_glossary(glossary);
+
+        let info = translator.context_info(&context("Focus Mode in Trailmate"), "ja");
+        assert!(info.contains("\"Focus Mode\" → \"集中モード\""));
+        assert!(info.contains("\"Trailmate\" → 保持原文"));
+        assert!(!info.contains("Streak"));
+        assert!(!translator.context_info(&context("Settings"), "ja").contains("术语表"));
+    }
+}
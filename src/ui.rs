@@ -1,7 +1,10 @@
 use crate::ai_provider::AIProvider;
 use crate::config::Config;
+use crate::glossary::Glossary;
+use crate::translation_memory::{MemoryEntry, TranslationMemory};
 use crate::translator::Translator;
-use crate::xcstrings::XCStringsFile;
+use crate::plural_rules::sort_categories;
+use crate::xcstrings::{sort_devices, TranslationContext, TranslationValue, UnitNode, UnitState, XCStringsFile};
 use colored::Colorize;
 use console::Term;
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
@@ -11,6 +14,7 @@ use std::time::Duration;
 use anyhow::Result;
 use crate::ascii_art::ROSETTA_LOGO;
 use comfy_table::{Table, presets::UTF8_FULL, ContentArrangement};
+use futures::stream::{self, StreamExt};
 
 pub struct UI {
     pub provider: AIProvider,
@@ -64,9 +68,11 @@ impl UI {
     pub async fn interactive_translate(
         xcstrings: &mut XCStringsFile,
         translator: &Translator,
+        memory: &mut TranslationMemory,
         keys: &[String],
         target_language: &str,
-        _file_path: &Path,
+        file_path: &Path,
+        ai_state: UnitState,
     ) -> Result<()> {
         let total = keys.len();
         let mut current = 0;
@@ -138,8 +144,9 @@ impl UI {
             match (selection, remaining > 0) {
                 (0, _) => {
                     // Translate single key
-                    if Self::translate_single_key(xcstrings, translator, key, target_language).await? {
+                    if Self::translate_single_key(xcstrings, translator, memory, key, target_language, file_path, ai_state).await? {
                         xcstrings.save()?;
+                        memory.save()?;
                         Self::print_success("Translation saved");
                         tokio::time::sleep(Duration::from_millis(800)).await;
                     }
@@ -159,7 +166,7 @@ impl UI {
                     let batch_keys = &keys[current..current + batch_size];
 
                     if Self::batch_translate_confirm(batch_keys, target_language).await? {
-                        Self::batch_translate_keys(xcstrings, translator, batch_keys, target_language).await?;
+                        Self::batch_translate_keys(xcstrings, translator, memory, batch_keys, target_language, file_path, ai_state).await?;
                         current += batch_size;
                     }
                 }
@@ -184,7 +191,7 @@ impl UI {
                     let batch_keys = &keys[current..current + size];
 
                     if Self::batch_translate_confirm(batch_keys, target_language).await? {
-                        Self::batch_translate_keys(xcstrings, translator, batch_keys, target_language).await?;
+                        Self::batch_translate_keys(xcstrings, translator, memory, batch_keys, target_language, file_path, ai_state).await?;
                         current += size;
                     }
                 }
@@ -210,13 +217,16 @@ impl UI {
     async fn translate_single_key(
         xcstrings: &mut XCStringsFile,
         translator: &Translator,
+        memory: &mut TranslationMemory,
         key: &str,
         target_language: &str,
+        file_path: &Path,
+        ai_state: UnitState,
     ) -> Result<bool> {
         println!("Translating...");
         
         // Get rich translation context
-        let context = match xcstrings.get_translation_context(key, &xcstrings.data.source_language) {
+        let mut context = match xcstrings.get_translation_context(key, &xcstrings.data.source_language) {
             Some(ctx) => ctx,
             None => {
                 Self::print_warning("Could not get translation context for this key");
@@ -236,6 +246,33 @@ impl UI {
         if let Some(ref category) = context.usage_category {
             Self::print_info("Category", category);
         }
+        if !context.plural_sources.is_empty() {
+            println!("  {}: ", "Plural forms".bright_black());
+            let mut categories: Vec<&String> = context.plural_sources.keys().collect();
+            sort_categories(&mut categories);
+            for category in categories {
+                println!("    {}: {}", category.bright_black(), context.plural_sources[category]);
+            }
+        }
+        if !context.substitution_sources.is_empty() {
+            println!("  {}: ", "Substitutions".bright_black());
+            let mut names: Vec<&String> = context.substitution_sources.keys().collect();
+            names.sort();
+            for name in names {
+                let forms = TranslationValue::Plural(context.substitution_sources[name].clone());
+                println!("    {}: {}", format!("%#@{}@", name).bright_black(), forms);
+            }
+        }
+        if !context.device_variants.is_empty() {
+            println!("  {}: ", "Device variants".bright_black());
+            for variant in &context.device_variants {
+                println!(
+                    "    {}: {}",
+                    variant.device.as_deref().unwrap_or("other").bright_black(),
+                    variant.source_text
+                );
+            }
+        }
         if !context.existing_translations.is_empty() {
             println!("  {}: ", "Other languages".bright_black());
             for (lang, trans) in &context.existing_translations {
@@ -244,14 +281,51 @@ impl UI {
         }
         println!();
 
-        let result = translator.translate_with_context(&context, target_language).await;
+        let source_language = xcstrings.data.source_language.clone();
+        if let Some(source) = Self::memory_source(&context) {
+            // An exact match needs no API call
+            if let Some(entry) = memory.lookup(&source_language, target_language, source) {
+                println!("Translation memory match:");
+                println!("  {}", entry.target.bright_white());
+                println!();
+
+                let translation = TranslationValue::Text(entry.target.clone());
+                let confirm = Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Use translation memory match?")
+                    .default(true)
+                    .interact()?;
+                if confirm {
+                    xcstrings.add_translation_value(key, target_language, &translation, ai_state)?;
+                    return Ok(true);
+                }
+            }
+        }
+
+        context.memory_matches = memory.fuzzy_matches(&source_language, target_language, &context.source_text, 3);
+        if !context.memory_matches.is_empty() {
+            println!("Translation memory:");
+            for memory_match in &context.memory_matches {
+                println!(
+                    "  {} {}",
+                    format!("{}%", memory_match.score).bright_black(),
+                    memory_match.source
+                );
+                println!("       {}", memory_match.target.cyan());
+            }
+            println!();
+        }
+
+        let result = translator.translate_value_with_context(&context, target_language).await;
 
         match result {
             Ok(translation) => {
                 println!();
                 println!("Translation:");
-                println!("  {}", translation.bright_white());
+                Self::print_translation_value(&translation, 1);
                 println!();
+                for issue in translator.glossary().check(&context.source_text, &translation.to_string(), target_language) {
+                    Self::print_warning(&format!("Glossary: {}", issue));
+                }
 
                 let confirm = Confirm::with_theme(&ColorfulTheme::default())
                     .with_prompt("Accept translation?")
@@ -259,7 +333,8 @@ impl UI {
                     .interact()?;
 
                 if confirm {
-                    xcstrings.add_translation(key, target_language, &translation)?;
+                    xcstrings.add_translation_value(key, target_language, &translation, ai_state)?;
+                    Self::remember(memory, translator.glossary(), &source_language, target_language, &context, &translation, file_path);
                     return Ok(true);
                 } else {
                     let custom_translation: String = Input::with_theme(&ColorfulTheme::default())
@@ -268,7 +343,10 @@ impl UI {
                         .interact_text()?;
 
                     if !custom_translation.trim().is_empty() {
-                        xcstrings.add_translation(key, target_language, &custom_translation)?;
+                        // Typed by a human, so it needs no further review
+                        xcstrings.add_translation(key, target_language, &custom_translation, UnitState::Translated)?;
+                        let translation = TranslationValue::Text(custom_translation);
+                        Self::remember(memory, translator.glossary(), &source_language, target_language, &context, &translation, file_path);
                         return Ok(true);
                     }
                 }
@@ -282,6 +360,76 @@ impl UI {
         Ok(false)
     }
 
+    /// Source text the translation memory can match: memory entries are whole plain strings,
+    /// so plural, device and substitution keys only get fuzzy references
+    fn memory_source(context: &TranslationContext) -> Option<&str> {
+        let plain = context.plural_sources.is_empty()
+            && context.device_variants.is_empty()
+            && context.substitution_sources.is_empty();
+        Some(context.source_text.as_str()).filter(|_| plain)
+    }
+
+    /// Add an accepted translation of a plain string to the translation memory. Every caller
+    /// runs after a person approved the translation: accepting it, confirming a batch preview
+    /// or approving it in review. Translations that don't use the glossary's terms are still
+    /// saved but never remembered, so the memory only spreads approved terminology.
+    fn remember(
+        memory: &mut TranslationMemory,
+        glossary: &Glossary,
+        source_language: &str,
+        target_language: &str,
+        context: &TranslationContext,
+        translation: &TranslationValue,
+        file_path: &Path,
+    ) {
+        if !glossary.check(&context.source_text, &translation.to_string(), target_language).is_empty() {
+            return;
+        }
+        if let (Some(source), TranslationValue::Text(target)) = (Self::memory_source(context), translation) {
+            memory.add(MemoryEntry {
+                source_language: source_language.to_string(),
+                target_language: target_language.to_string(),
+                source: source.to_string(),
+                target: target.clone(),
+                origin: Some(file_path.display().to_string()),
+            });
+        }
+    }
+
+    fn print_translation_value(value: &TranslationValue, depth: usize) {
+        let indent = "  ".repeat(depth);
+        match value {
+            TranslationValue::Text(text) => println!("{}{}", indent, text.bright_white()),
+            TranslationValue::Plural(forms) => {
+                let mut categories: Vec<&String> = forms.keys().collect();
+                sort_categories(&mut categories);
+                for category in categories {
+                    println!("{}{}: {}", indent, category.bright_black(), forms[category].bright_white());
+                }
+            }
+            TranslationValue::Device(devices) => {
+                let mut device_types: Vec<&String> = devices.keys().collect();
+                sort_devices(&mut device_types);
+                for device in device_types {
+                    println!("{}{}:", indent, device.bright_black());
+                    Self::print_translation_value(&devices[device], depth + 1);
+                }
+            }
+            TranslationValue::Substitutions { text, substitutions } => {
+                println!("{}{}", indent, text.bright_white());
+                let mut names: Vec<&String> = substitutions.keys().collect();
+                names.sort();
+                for name in names {
+                    println!("{}%#@{}@:", indent, name.bright_black());
+                    Self::print_translation_value(
+                        &TranslationValue::Plural(substitutions[name].clone()),
+                        depth + 1,
+                    );
+                }
+            }
+        }
+    }
+
     async fn batch_translate_confirm(keys: &[String], target_language: &str) -> Result<bool> {
         println!();
         println!("Batch translate {} keys to {}", keys.len(), target_language.cyan());
@@ -308,21 +456,27 @@ impl UI {
     pub async fn auto_translate_all(
         xcstrings: &mut XCStringsFile,
         translator: &Translator,
+        memory: &mut TranslationMemory,
         keys: &[String],
         target_language: &str,
-        _file_path: &Path,
+        file_path: &Path,
+        ai_state: UnitState,
     ) -> Result<()> {
-        Self::batch_translate_keys(xcstrings, translator, keys, target_language).await
+        Self::batch_translate_keys(xcstrings, translator, memory, keys, target_language, file_path, ai_state).await
     }
 
     async fn batch_translate_keys(
         xcstrings: &mut XCStringsFile,
         translator: &Translator,
+        memory: &mut TranslationMemory,
         keys: &[String],
         target_language: &str,
+        file_path: &Path,
+        ai_state: UnitState,
     ) -> Result<()> {
         // Get rich translation contexts for all keys
-        let contexts = xcstrings.get_translation_contexts(keys, &xcstrings.data.source_language);
+        let source_language = xcstrings.data.source_language.clone();
+        let mut contexts = xcstrings.get_translation_contexts(keys, &source_language);
         
         let pb = ProgressBar::new(contexts.len() as u64);
         pb.set_style(
@@ -332,7 +486,8 @@ impl UI {
                 .progress_chars("█▉▊▋▌▍▎▏  "),
         );
 
-        let mut results: Vec<(String, Result<String, String>)> = Vec::new();
+        // Translation of each context, and whether it came from the translation memory
+        let mut results: Vec<Option<(Result<TranslationValue, String>, bool)>> = vec![None; contexts.len()];
 
         // Unicode-safe helper to truncate long keys without splitting multibyte characters.
         fn ellipsize_utf8(s: &str, max_chars: usize) -> String {
@@ -354,24 +509,43 @@ impl UI {
             format!("{}...", &s[..boundary])
         }
 
-        for (_i, context) in contexts.iter().enumerate() {
-            let display_key = ellipsize_utf8(&context.key, 40);
-            pb.set_message(display_key);
-
-            let result = match translator.translate_with_context(context, target_language).await {
-                Ok(t) => Ok(t),
-                Err(e) => Err(e.to_string()),
-            };
-
-            results.push((context.key.clone(), result));
+        let mut pending = Vec::new();
+        for (i, context) in contexts.iter_mut().enumerate() {
+            // Exact translation memory matches are applied without an API call
+            let exact = Self::memory_source(context)
+                .and_then(|source| memory.lookup(&source_language, target_language, source));
+            if let Some(entry) = exact {
+                results[i] = Some((Ok(TranslationValue::Text(entry.target.clone())), true));
+                pb.inc(1);
+                continue;
+            }
 
-            pb.inc(1);
+            context.memory_matches = memory.fuzzy_matches(&source_language, target_language, &context.source_text, 3);
+            pending.push(i);
+        }
 
-            // Rate limiting to avoid hitting API limits  
-            tokio::time::sleep(Duration::from_millis(200)).await;
+        // Plain strings are packed several to a request and the requests run concurrently; the
+        // translator's rate limiter bounds and paces them, and each result goes back to its key's
+        // slot so the preview keeps key order
+        let groups = translator.request_groups(pending.into_iter().map(|i| (i, &contexts[i])).collect());
+        let mut translations = stream::iter(groups)
+            .map(|group| async move {
+                let (indices, group): (Vec<usize>, Vec<&TranslationContext>) = group.into_iter().unzip();
+                let results = translator.translate_keys_with_context(&group, target_language).await;
+                indices.into_iter().zip(results).collect::<Vec<_>>()
+            })
+            .buffer_unordered(translator.concurrency());
+
+        while let Some(group) = translations.next().await {
+            for (i, result) in group {
+                pb.set_message(ellipsize_utf8(&contexts[i].key, 40));
+                pb.inc(1);
+                results[i] = Some((result.map_err(|e| e.to_string()), false));
+            }
         }
 
         pb.finish_and_clear();
+        let results: Vec<(Result<TranslationValue, String>, bool)> = results.into_iter().flatten().collect();
 
         // Build preview table
         let mut table = Table::new();
@@ -381,16 +555,31 @@ impl UI {
             .set_header(vec!["Key", "Translation (preview)", "Status"]);
 
         let mut success_count = 0;
+        let mut memory_count = 0;
         let mut failed_count = 0;
+        let mut glossary_count = 0;
 
-        for (k, res) in &results {
+        for (context, (res, from_memory)) in contexts.iter().zip(&results) {
+            let k = &context.key;
             match res {
                 Ok(t) => {
                     success_count += 1;
+                    let issues = translator.glossary().check(&context.source_text, &t.to_string(), target_language);
+                    let status = if !issues.is_empty() {
+                        glossary_count += 1;
+                        format!("Glossary: {}", issues.join("; ")).yellow().to_string()
+                    } else if *from_memory {
+                        "Memory".blue().to_string()
+                    } else {
+                        "Success".green().to_string()
+                    };
+                    if *from_memory {
+                        memory_count += 1;
+                    }
                     table.add_row(vec![
                         ellipsize_utf8(k, 40),
-                        ellipsize_utf8(t, 60),
-                        "Success".green().to_string(),
+                        ellipsize_utf8(&t.to_string(), 60),
+                        status,
                     ]);
                 }
                 Err(err_msg) => {
@@ -406,7 +595,16 @@ impl UI {
 
         println!("\n{}", table);
 
-        println!("\nSummary: {} successes, {} failures", success_count, failed_count);
+        println!(
+            "\nSummary: {} successes ({} from translation memory), {} failures",
+            success_count, memory_count, failed_count
+        );
+        if glossary_count > 0 {
+            Self::print_warning(&format!(
+                "{} translations don't use the glossary's terms; check them before saving",
+                glossary_count
+            ));
+        }
 
         let proceed = Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
             .with_prompt("Save successful translations to .xcstrings?")
@@ -414,14 +612,15 @@ impl UI {
             .interact()?;
 
         if proceed {
-            for (k, res) in results {
-                if let Ok(trans) = res {
-                    // Ignore individual save errors, collect later if needed
-                    let _ = xcstrings.add_translation(&k, target_language, &trans);
-                }
-            }
+            let translations: Vec<(&TranslationContext, TranslationValue)> = contexts
+                .iter()
+                .zip(results)
+                .filter_map(|(context, (res, _))| res.ok().map(|trans| (context, trans)))
+                .collect();
+            Self::save_batch(xcstrings, memory, translator.glossary(), &translations, target_language, file_path, ai_state);
 
             xcstrings.save()?;
+            memory.save()?;
             Self::print_success("Translations saved.");
         } else {
             Self::print_warning("Translations were not saved.");
@@ -430,6 +629,150 @@ impl UI {
         Ok(())
     }
 
+    /// Store the translations of a confirmed batch preview, remembering the accepted ones
+    fn save_batch(
+        xcstrings: &mut XCStringsFile,
+        memory: &mut TranslationMemory,
+        glossary: &Glossary,
+        translations: &[(&TranslationContext, TranslationValue)],
+        target_language: &str,
+        file_path: &Path,
+        ai_state: UnitState,
+    ) {
+        let source_language = xcstrings.data.source_language.clone();
+        for (context, trans) in translations {
+            if let Err(e) = xcstrings.add_translation_value(&context.key, target_language, trans, ai_state) {
+                Self::print_error(&format!("Failed to save '{}': {}", context.key, e));
+                continue;
+            }
+            Self::remember(memory, glossary, &source_language, target_language, context, trans, file_path);
+        }
+    }
+
+    /// Add a key's translation, just approved in review, to the translation memory
+    pub fn remember_reviewed(
+        xcstrings: &XCStringsFile,
+        memory: &mut TranslationMemory,
+        glossary: &Glossary,
+        key: &str,
+        language: &str,
+        file_path: &Path,
+    ) {
+        let source_language = &xcstrings.data.source_language;
+        let Some(context) = xcstrings.get_translation_context(key, source_language) else {
+            return;
+        };
+        let value = xcstrings
+            .data
+            .strings
+            .get(key)
+            .and_then(|entry| entry.localizations.get(language))
+            .and_then(|loc| loc.value());
+        if let Some(value) = value {
+            Self::remember(memory, glossary, source_language, language, &context, &value, file_path);
+        }
+    }
+
+    /// Walk through units marked `needs_review` and promote the approved ones to `translated`
+    pub async fn interactive_review(
+        xcstrings: &mut XCStringsFile,
+        memory: &mut TranslationMemory,
+        glossary: &Glossary,
+        keys: &[String],
+        language: &str,
+        file_path: &Path,
+    ) -> Result<()> {
+        let total = keys.len();
+        let mut approved = 0;
+
+        for (index, key) in keys.iter().enumerate() {
+            let term = Term::stdout();
+            if console::user_attended() {
+                let _ = term.clear_screen();
+            }
+
+            println!();
+            println!("Review Progress");
+            Self::print_info("Status", &format!("{}/{} keys", index + 1, total));
+            Self::print_info("Approved", &approved.to_string());
+            println!();
+
+            println!("Key:");
+            println!("  {}", key.bright_white());
+            println!();
+
+            if let Some(source) = xcstrings.get_existing_translation(key, &xcstrings.data.source_language) {
+                println!("Source:");
+                println!("  {}", source.bright_black());
+                println!();
+            }
+
+            let Some(value) = xcstrings
+                .data
+                .strings
+                .get(key)
+                .and_then(|entry| entry.localizations.get(language))
+                .and_then(|loc| loc.value())
+            else {
+                continue;
+            };
+
+            println!("Translation:");
+            Self::print_translation_value(&value, 1);
+            println!();
+
+            let mut choices = vec!["Approve", "Skip", "Approve all remaining", "Save and exit"];
+            // Only plain strings can be edited inline
+            let editable = matches!(value, TranslationValue::Text(_));
+            if editable {
+                choices.insert(1, "Edit");
+            }
+
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Action")
+                .items(&choices)
+                .default(0)
+                .interact()?;
+
+            match choices[selection] {
+                "Approve" => {
+                    xcstrings.mark_reviewed(key, language)?;
+                    Self::remember_reviewed(xcstrings, memory, glossary, key, language, file_path);
+                    xcstrings.save()?;
+                    memory.save()?;
+                    approved += 1;
+                }
+                "Edit" => {
+                    let initial = value.to_string();
+                    let edited: String = Input::with_theme(&ColorfulTheme::default())
+                        .with_prompt("Corrected translation")
+                        .with_initial_text(initial)
+                        .interact_text()?;
+                    xcstrings.add_translation(key, language, &edited, UnitState::Translated)?;
+                    Self::remember_reviewed(xcstrings, memory, glossary, key, language, file_path);
+                    xcstrings.save()?;
+                    memory.save()?;
+                    approved += 1;
+                }
+                "Approve all remaining" => {
+                    for remaining_key in &keys[index..] {
+                        xcstrings.mark_reviewed(remaining_key, language)?;
+                        Self::remember_reviewed(xcstrings, memory, glossary, remaining_key, language, file_path);
+                        approved += 1;
+                    }
+                    xcstrings.save()?;
+                    memory.save()?;
+                    break;
+                }
+                "Skip" => {}
+                _ => break,
+            }
+        }
+
+        Self::print_success(&format!("Approved {} of {} keys", approved, total));
+        Ok(())
+    }
+
     pub fn display_provider_info(&self) {
         match &self.provider {
             AIProvider::OpenAI { api_key, model } => {
@@ -447,6 +790,11 @@ impl UI {
                 println!("API Key:           {}", mask_api_key(&api_key));
                 println!("Model:             {}", model);
             }
+            AIProvider::OpenRouter { api_key, model } => {
+                println!("AI Provider:       {}", "OpenRouter".bright_cyan());
+                println!("API Key:           {}", mask_api_key(api_key));
+                println!("Model:             {}", model);
+            }
         }
     }
 
@@ -455,6 +803,7 @@ impl UI {
             AIProvider::OpenAI { .. } => "OpenAI",
             AIProvider::Claude { .. } => "Claude",
             AIProvider::Gemini { .. } => "Google Gemini",
+            AIProvider::OpenRouter { .. } => "OpenRouter",
         }
     }
 
@@ -463,6 +812,7 @@ impl UI {
             AIProvider::OpenAI { model, .. } => model.clone(),
             AIProvider::Claude { model, .. } => model.clone(),
             AIProvider::Gemini { model, .. } => model.clone(),
+            AIProvider::OpenRouter { model, .. } => model.clone(),
         }
     }
 
@@ -471,6 +821,7 @@ impl UI {
             AIProvider::OpenAI { api_key, .. } => api_key.clone(),
             AIProvider::Claude { api_key, .. } => api_key.clone(),
             AIProvider::Gemini { api_key, .. } => api_key.clone(),
+            AIProvider::OpenRouter { api_key, .. } => api_key.clone(),
         }
     }
 
@@ -545,6 +896,11 @@ pub fn display_config(config: &Config) {
             println!("API Key:           {}", mask_api_key(api_key));
             println!("Model:             {}", model);
         }
+        AIProvider::OpenRouter { api_key, model } => {
+            println!("AI Provider:       {}", "OpenRouter".bright_cyan());
+            println!("API Key:           {}", mask_api_key(api_key));
+            println!("Model:             {}", model);
+        }
     }
 }
 
@@ -556,4 +912,47 @@ fn mask_api_key(api_key: &str) -> String {
         &api_key[..4],
         "*".repeat(api_key.len() - 4)
     )
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xcstrings::tests::load_catalog;
+    use std::collections::BTreeMap;
+
+    const CATALOG: &str = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "Save" : {},
+    "Open Focus Mode" : {}
+  },
+  "version" : "1.0"
+}"#;
+
+    #[test]
+    fn test_batch_fills_memory() {
+        let (dir, mut file) = load_catalog(CATALOG);
+        let mut memory = TranslationMemory::load_from(dir.path().join("memory.json")).unwrap();
+        let glossary = Glossary {
+            terms: BTreeMap::from([(
+                "Focus Mode".to_string(),
+                BTreeMap::from([("ja".to_string(), "集中モード".to_string())]),
+            )]),
+            do_not_translate: Vec::new(),
+        };
+        let save = file.get_translation_context("Save", "en").unwrap();
+        let focus = file.get_translation_context("Open Focus Mode", "en").unwrap();
+        let translations = [
+            (&save, TranslationValue::Text("保存".to_string())),
+            (&focus, TranslationValue::Text("フォーカスモードを開く".to_string())),
+        ];
+
+        // The default state leaves the translations for review, but confirming the preview
+        // accepts them; the one ignoring the glossary is saved without being remembered
+        let path = dir.path().join("Localizable.xcstrings");
+        UI::save_batch(&mut file, &mut memory, &glossary, &translations, "ja", &path, UnitState::NeedsReview);
+
+        assert_eq!(memory.len(), 1);
+        assert_eq!(memory.lookup("en", "ja", "Save").unwrap().target, "保存");
+        assert_eq!(file.get_existing_translation("Open Focus Mode", "ja").as_deref(), Some("フォーカスモードを開く"));
+    }
+}
@@ -0,0 +1,127 @@
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+
+/// Directories holding build output or third-party code, never our own catalogs
+const SKIPPED_DIRS: &[&str] = &[
+    "DerivedData",
+    "build",
+    ".build",
+    "Pods",
+    "Carthage",
+    "SourcePackages",
+    "node_modules",
+];
+
+/// Find the root of the project containing `start`: the nearest ancestor with a `.git`
+/// directory, or `start` itself when there is none.
+pub fn find_workspace_root(start: &Path) -> PathBuf {
+    let mut dir = start.to_path_buf();
+    loop {
+        if dir.join(".git").exists() {
+            return dir;
+        }
+        if !dir.pop() {
+            return start.to_path_buf();
+        }
+    }
+}
+
+/// Find every `.xcstrings` catalog under `root`, honoring `.gitignore` and skipping
+/// hidden and build directories. Paths are returned sorted.
+pub fn discover_catalogs(root: &Path) -> Vec<PathBuf> {
+    let walker = WalkBuilder::new(root)
+        .hidden(true)
+        .git_ignore(true)
+        .git_exclude(true)
+        .require_git(false)
+        .filter_entry(|entry| {
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            !(is_dir
+                && entry
+                    .file_name()
+                    .to_str()
+                    .map(|name| SKIPPED_DIRS.contains(&name))
+                    .unwrap_or(false))
+        })
+        .build();
+
+    let mut catalogs: Vec<PathBuf> = walker
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .map(|entry| entry.into_path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("xcstrings"))
+        .collect();
+
+    catalogs.sort();
+    catalogs
+}
+
+/// Keep the catalogs matching any selector, by file name without extension
+/// (`InfoPlist`) or by a fragment of the path (`Widget/`). No selectors keeps everything.
+pub fn filter_catalogs(catalogs: &[PathBuf], selectors: &[String]) -> Vec<PathBuf> {
+    if selectors.is_empty() {
+        return catalogs.to_vec();
+    }
+
+    catalogs
+        .iter()
+        .filter(|path| {
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            let full = path.to_string_lossy();
+            selectors
+                .iter()
+                .any(|selector| stem == selector || full.contains(selector.as_str()))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Path of a catalog relative to the workspace root, for display
+pub fn display_name(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_discover_catalogs() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for path in [
+            "App/Localizable.xcstrings",
+            "App/InfoPlist.xcstrings",
+            "Widget/Widget.xcstrings",
+            "Packages/Core/Sources/Core/Resources/Localizable.xcstrings",
+            "DerivedData/App/Localizable.xcstrings",
+            "Generated/Localizable.xcstrings",
+            "App/Localizable.xcstrings.backup_20250101_000000",
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "{}").unwrap();
+        }
+        fs::write(root.join(".gitignore"), "Generated/\n").unwrap();
+
+        let catalogs = discover_catalogs(root);
+        let names: Vec<String> = catalogs.iter().map(|p| display_name(p, root)).collect();
+        assert_eq!(
+            names,
+            vec![
+                "App/InfoPlist.xcstrings",
+                "App/Localizable.xcstrings",
+                "Packages/Core/Sources/Core/Resources/Localizable.xcstrings",
+                "Widget/Widget.xcstrings",
+            ]
+        );
+
+        let selected = filter_catalogs(&catalogs, &["InfoPlist".to_string(), "Widget/".to_string()]);
+        assert_eq!(selected.len(), 2);
+    }
+}
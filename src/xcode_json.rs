@@ -0,0 +1,169 @@
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// Serialize a JSON value exactly the way Xcode writes `.xcstrings` files.
+///
+/// Xcode uses Foundation's pretty printer with sorted keys: two-space indentation,
+/// `"key" : value` separators, escaped forward slashes, empty containers written as
+/// `{\n\n}` and a trailing newline.
+pub fn to_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, 0);
+    out.push('\n');
+    out
+}
+
+/// Compare two keys the way Foundation's sorted-keys option does: case-insensitive,
+/// numeric-aware (`"item 2"` < `"item 10"`), with a literal comparison as the final tie-breaker.
+pub fn compare_keys(a: &str, b: &str) -> Ordering {
+    let mut left = a.chars().peekable();
+    let mut right = b.chars().peekable();
+
+    loop {
+        match (left.peek().copied(), right.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(l), Some(r)) if l.is_ascii_digit() && r.is_ascii_digit() => {
+                let left_digits = take_digits(&mut left);
+                let right_digits = take_digits(&mut right);
+                let ordering = compare_numeric(&left_digits, &right_digits);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(l), Some(r)) => {
+                let ordering = l.to_lowercase().cmp(r.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                left.next();
+                right.next();
+            }
+        }
+    }
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.peek().copied().filter(char::is_ascii_digit) {
+        digits.push(c);
+        chars.next();
+    }
+    digits
+}
+
+fn compare_numeric(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+fn write_value(out: &mut String, value: &Value, depth: usize) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&n.to_string()),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push_str("[\n");
+            if items.is_empty() {
+                out.push('\n');
+            }
+            for (i, item) in items.iter().enumerate() {
+                write_indent(out, depth + 1);
+                write_value(out, item, depth + 1);
+                if i + 1 < items.len() {
+                    out.push(',');
+                }
+                out.push('\n');
+            }
+            write_indent(out, depth);
+            out.push(']');
+        }
+        Value::Object(map) => {
+            out.push_str("{\n");
+            if map.is_empty() {
+                out.push('\n');
+            }
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort_by(|a, b| compare_keys(a, b));
+            for (i, key) in keys.iter().enumerate() {
+                write_indent(out, depth + 1);
+                write_string(out, key);
+                out.push_str(" : ");
+                write_value(out, &map[*key], depth + 1);
+                if i + 1 < keys.len() {
+                    out.push(',');
+                }
+                out.push('\n');
+            }
+            write_indent(out, depth);
+            out.push('}');
+        }
+    }
+}
+
+fn write_indent(out: &mut String, depth: usize) {
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '/' => out.push_str("\\/"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_keys() {
+        let mut keys = vec!["b", "item 10", "A", "item 2", "_x", "a"];
+        keys.sort_by(|a, b| compare_keys(a, b));
+        assert_eq!(keys, vec!["_x", "A", "a", "b", "item 2", "item 10"]);
+    }
+
+    #[test]
+    fn test_xcode_formatting() {
+        let catalog = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "" : {
+
+    },
+    "Open https:\/\/example.com" : {
+      "comment" : "Line one\nline two \"quoted\"",
+      "localizations" : {
+        "de" : {
+          "stringUnit" : {
+            "state" : "translated",
+            "value" : "Öffne https:\/\/example.com"
+          }
+        }
+      }
+    }
+  },
+  "version" : "1.0"
+}
+"#;
+        let value: Value = serde_json::from_str(catalog).unwrap();
+        assert_eq!(to_string(&value), catalog);
+    }
+}
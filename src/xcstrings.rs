@@ -1,12 +1,36 @@
+//! Reading and writing Xcode string catalogs (`.xcstrings`).
+//!
+//! Every catalog struct keeps the fields it does not model in its `extra` map, so saving a
+//! catalog written by a newer Xcode does not drop them.
+
 use crate::error::{Result, TranslatorError};
 use crate::key_mappings::{infer_key_meaning, categorize_usage};
+use crate::plural_rules::{plural_categories, sort_categories, PLURAL_CATEGORIES};
+use crate::strings_file::StringsTable;
+use crate::translation_memory::MemoryMatch;
+use crate::xcode_json;
 use crate::TranslationMode;
 use chrono::Utc;
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Device identifiers Xcode uses under `variations.device`, in the order Xcode lists them
+pub const DEVICE_TYPES: [&str; 8] = [
+    "iphone",
+    "ipad",
+    "ipod",
+    "mac",
+    "applewatch",
+    "appletv",
+    "applevision",
+    "other",
+];
+
 // New structure for rich translation context
 #[derive(Debug, Clone)]
 pub struct TranslationContext {
@@ -16,30 +40,771 @@ pub struct TranslationContext {
     pub source_text: String,
     pub existing_translations: HashMap<String, String>,
     pub usage_category: Option<String>,
+    /// Source text of each plural form, keyed by CLDR category (empty for plain strings)
+    pub plural_sources: HashMap<String, String>,
+    /// Plural category currently being translated, if any
+    pub plural_category: Option<String>,
+    /// Device this context targets when the string varies by device
+    pub device: Option<String>,
+    /// One context per device variant of the source (empty when the string does not vary by device)
+    pub device_variants: Vec<TranslationContext>,
+    /// Plural forms of each substitution in the source, keyed by substitution name then category
+    pub substitution_sources: HashMap<String, HashMap<String, String>>,
+    /// Substitution whose plural branch is currently being translated, if any
+    pub substitution: Option<String>,
+    /// Approved translations of similar source strings from the translation memory
+    pub memory_matches: Vec<MemoryMatch>,
+}
+
+/// A translation produced for one key
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranslationValue {
+    /// Plain `stringUnit` value
+    Text(String),
+    /// Plural forms keyed by CLDR category
+    Plural(HashMap<String, String>),
+    /// Per-device values keyed by device type
+    Device(HashMap<String, TranslationValue>),
+    /// Outer string with `%#@name@` tokens plus the plural forms of each substitution
+    Substitutions {
+        text: String,
+        substitutions: HashMap<String, HashMap<String, String>>,
+    },
+}
+
+impl fmt::Display for TranslationValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranslationValue::Text(text) => write!(f, "{}", text),
+            TranslationValue::Plural(forms) => {
+                let mut categories: Vec<&String> = forms.keys().collect();
+                sort_categories(&mut categories);
+                let parts: Vec<String> = categories
+                    .iter()
+                    .map(|category| format!("{}: {}", category, forms[*category]))
+                    .collect();
+                write!(f, "{}", parts.join(" | "))
+            }
+            TranslationValue::Device(devices) => {
+                let mut device_types: Vec<&String> = devices.keys().collect();
+                sort_devices(&mut device_types);
+                let parts: Vec<String> = device_types
+                    .iter()
+                    .map(|device| format!("[{}] {}", device, devices[*device]))
+                    .collect();
+                write!(f, "{}", parts.join("; "))
+            }
+            TranslationValue::Substitutions { text, substitutions } => {
+                let mut names: Vec<&String> = substitutions.keys().collect();
+                names.sort();
+                let parts: Vec<String> = names
+                    .iter()
+                    .map(|name| {
+                        let forms = TranslationValue::Plural(substitutions[*name].clone());
+                        format!("[{}] {}", name, forms)
+                    })
+                    .collect();
+                write!(f, "{}; {}", text, parts.join("; "))
+            }
+        }
+    }
+}
+
+/// Separates a key from the path of one of its variations in flattened ids,
+/// e.g. `%lld items|==|plural.one` or `Start|==|device.applewatch`
+pub const VARIATION_SEPARATOR: &str = "|==|";
+
+/// Id of one variation of a key; the key itself when the path is empty
+pub fn variation_id(key: &str, path: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}{}{}", key, VARIATION_SEPARATOR, path)
+    }
+}
+
+/// Split an id made by `variation_id` into key and variation path
+pub fn split_variation_id(id: &str) -> (&str, &str) {
+    id.split_once(VARIATION_SEPARATOR).unwrap_or((id, ""))
+}
+
+fn join_path(path: &str, segment: &str) -> String {
+    if path.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{}.{}", path, segment)
+    }
+}
+
+impl TranslationValue {
+    /// The value of a single device, for formats without per-device variations:
+    /// the `other` device, or else the first one in Xcode's order
+    pub fn collapse_devices(self) -> TranslationValue {
+        let TranslationValue::Device(mut devices) = self else {
+            return self;
+        };
+        let mut names: Vec<String> = devices.keys().cloned().collect();
+        sort_devices(&mut names);
+        let device = names.iter().find(|d| *d == "other").or(names.first()).cloned();
+        match device.and_then(|d| devices.remove(&d)) {
+            Some(value) => value,
+            None => TranslationValue::Device(devices),
+        }
+    }
+
+    /// Flatten into `(variation path, text)` pairs such as `("plural.one", "%lld item")`,
+    /// for formats that hold one string per row. With `categories`, plurals are expanded
+    /// to exactly those categories, falling back to the `other` form.
+    pub fn flatten(&self, categories: Option<&[&str]>) -> Vec<(String, String)> {
+        let mut out = Vec::new();
+        self.flatten_into(categories, String::new(), &mut out);
+        out
+    }
+
+    fn flatten_into(&self, categories: Option<&[&str]>, path: String, out: &mut Vec<(String, String)>) {
+        match self {
+            TranslationValue::Text(text) => out.push((path, text.clone())),
+            TranslationValue::Plural(forms) => flatten_plural(forms, categories, &path, out),
+            TranslationValue::Device(devices) => {
+                let mut names: Vec<&String> = devices.keys().collect();
+                sort_devices(&mut names);
+                for device in names {
+                    let device_path = join_path(&path, &format!("device.{}", device));
+                    devices[device].flatten_into(categories, device_path, out);
+                }
+            }
+            TranslationValue::Substitutions { text, substitutions } => {
+                out.push((path.clone(), text.clone()));
+                let mut names: Vec<&String> = substitutions.keys().collect();
+                names.sort();
+                for name in names {
+                    let substitution_path = join_path(&path, &format!("substitutions.{}", name));
+                    flatten_plural(&substitutions[name], categories, &substitution_path, out);
+                }
+            }
+        }
+    }
+}
+
+fn flatten_plural(
+    forms: &HashMap<String, String>,
+    categories: Option<&[&str]>,
+    path: &str,
+    out: &mut Vec<(String, String)>,
+) {
+    let expand = categories.is_some();
+    for category in categories.unwrap_or(&PLURAL_CATEGORIES) {
+        let form = forms
+            .get(*category)
+            .or_else(|| forms.get("other").filter(|_| expand));
+        if let Some(form) = form {
+            out.push((join_path(path, &format!("plural.{}", category)), form.clone()));
+        }
+    }
+}
+
+/// Rebuilds a translation value from the pairs `TranslationValue::flatten` produces
+#[derive(Debug, Default)]
+pub struct ValueBuilder {
+    text: Option<String>,
+    plural: HashMap<String, String>,
+    substitutions: HashMap<String, HashMap<String, String>>,
+    devices: BTreeMap<String, ValueBuilder>,
+}
+
+impl ValueBuilder {
+    /// Store `text` at a variation path, returning false for paths `flatten` never produces
+    pub fn insert(&mut self, path: &str, text: String) -> bool {
+        let segments: Vec<&str> = path.split('.').filter(|s| !s.is_empty()).collect();
+        self.insert_segments(&segments, text)
+    }
+
+    fn insert_segments(&mut self, segments: &[&str], text: String) -> bool {
+        match segments {
+            [] => self.text = Some(text),
+            ["plural", category] => {
+                self.plural.insert(category.to_string(), text);
+            }
+            ["substitutions", name, "plural", category] => {
+                self.substitutions
+                    .entry(name.to_string())
+                    .or_default()
+                    .insert(category.to_string(), text);
+            }
+            ["device", device, rest @ ..] => {
+                return self
+                    .devices
+                    .entry(device.to_string())
+                    .or_default()
+                    .insert_segments(rest, text);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn build(self) -> Option<TranslationValue> {
+        if !self.devices.is_empty() {
+            let devices: HashMap<String, TranslationValue> = self
+                .devices
+                .into_iter()
+                .filter_map(|(device, builder)| builder.build().map(|value| (device, value)))
+                .collect();
+            return Some(TranslationValue::Device(devices));
+        }
+        if !self.substitutions.is_empty() {
+            return Some(TranslationValue::Substitutions {
+                text: self.text?,
+                substitutions: self.substitutions,
+            });
+        }
+        if !self.plural.is_empty() {
+            return Some(TranslationValue::Plural(self.plural));
+        }
+        self.text.map(TranslationValue::Text)
+    }
+}
+
+/// Names of the `%#@name@` substitution tokens in a string, in order of appearance.
+///
+/// Positional tokens such as `%1$#@name@` are recognised as well.
+pub fn substitution_names(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('%') {
+        let after = &rest[start + 1..];
+        let digits = after.chars().take_while(|c| c.is_ascii_digit()).count();
+        let after_position = if digits > 0 && after[digits..].starts_with('$') {
+            &after[digits + 1..]
+        } else {
+            after
+        };
+
+        if let Some(token) = after_position.strip_prefix("#@") {
+            if let Some(end) = token.find('@') {
+                names.push(token[..end].to_string());
+                rest = &token[end + 1..];
+                continue;
+            }
+        }
+        rest = after;
+    }
+
+    names
+}
+
+/// Sort device types into Xcode's order, leaving unknown devices at the end
+pub fn sort_devices<S: AsRef<str>>(devices: &mut [S]) {
+    devices.sort_by_key(|d| {
+        DEVICE_TYPES
+            .iter()
+            .position(|t| *t == d.as_ref())
+            .unwrap_or(DEVICE_TYPES.len())
+    });
+}
+
+/// Review state of a `stringUnit`, as used by Xcode's localization workflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitState {
+    /// Added but never translated
+    New,
+    /// Translated, waiting for a human to check it
+    #[value(name = "needs_review")]
+    NeedsReview,
+    /// Translated and reviewed
+    Translated,
+    /// Source changed since the unit was translated
+    Stale,
+}
+
+impl UnitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitState::New => "new",
+            UnitState::NeedsReview => "needs_review",
+            UnitState::Translated => "translated",
+            UnitState::Stale => "stale",
+        }
+    }
+
+    /// Position from least to most finished, for picking the weakest state of several units
+    pub fn rank(&self) -> u8 {
+        match self {
+            UnitState::New | UnitState::Stale => 0,
+            UnitState::NeedsReview => 1,
+            UnitState::Translated => 2,
+        }
+    }
+
+    pub fn parse(state: &str) -> Option<Self> {
+        match state {
+            "new" => Some(UnitState::New),
+            "needs_review" => Some(UnitState::NeedsReview),
+            "translated" => Some(UnitState::Translated),
+            "stale" => Some(UnitState::Stale),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for UnitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Options controlling which keys `get_keys_needing_translation` selects
+#[derive(Debug, Clone)]
+pub struct TranslationFilter {
+    /// Unit states that count as untranslated in `Supplement` mode
+    pub retranslate_states: Vec<UnitState>,
+    /// Also translate keys Xcode marked as stale (no longer found in source code)
+    pub include_stale_keys: bool,
+}
+
+impl Default for TranslationFilter {
+    fn default() -> Self {
+        Self {
+            retranslate_states: vec![UnitState::New, UnitState::Stale],
+            include_stale_keys: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StringUnit {
     pub state: String,
     pub value: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl StringUnit {
+    /// The unit's state, if it is one of the states Xcode defines
+    pub fn unit_state(&self) -> Option<UnitState> {
+        UnitState::parse(&self.state)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Variation {
+    #[serde(rename = "stringUnit", skip_serializing_if = "Option::is_none")]
+    pub string_unit: Option<StringUnit>,
+    /// Nested variations, e.g. plural forms inside a device variant
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variations: Option<Variations>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Variations {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plural: Option<HashMap<String, Variation>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<HashMap<String, Variation>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Variations {
+    /// Non-empty plural forms, keyed by CLDR category
+    pub fn plural_forms(&self) -> HashMap<String, String> {
+        self.plural
+            .as_ref()
+            .map(|plural| {
+                plural
+                    .iter()
+                    .filter_map(|(category, variation)| {
+                        variation
+                            .string_unit
+                            .as_ref()
+                            .filter(|unit| !unit.value.trim().is_empty())
+                            .map(|unit| (category.clone(), unit.value.clone()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// States of every string unit in these variations
+    pub fn unit_states(&self) -> Vec<UnitState> {
+        self.plural
+            .iter()
+            .chain(self.device.iter())
+            .flat_map(|variations| variations.values())
+            .flat_map(|variation| variation.unit_states())
+            .collect()
+    }
+
+    /// Change every unit in state `from` to `to`, returning how many changed
+    pub fn replace_unit_states(&mut self, from: UnitState, to: UnitState) -> usize {
+        self.plural
+            .iter_mut()
+            .chain(self.device.iter_mut())
+            .flat_map(|variations| variations.values_mut())
+            .map(|variation| variation.replace_unit_states(from, to))
+            .sum()
+    }
+
+    /// Plural variations holding `forms`, each with the given state
+    pub fn from_plural_forms(forms: &HashMap<String, String>, state: UnitState) -> Self {
+        let mut variations = Variations::default();
+        variations.set_plural_forms(forms, state);
+        variations
+    }
+
+    /// Replace the plural forms with `forms`. Forms that remain are updated in place, keeping
+    /// fields not modelled here.
+    pub fn set_plural_forms(&mut self, forms: &HashMap<String, String>, state: UnitState) {
+        let mut existing = self.plural.take().unwrap_or_default();
+        let plural = forms
+            .iter()
+            .map(|(category, translation)| {
+                let mut variation = existing.remove(category).unwrap_or_default();
+                variation.set_value(&TranslationValue::Text(translation.clone()), state);
+                (category.clone(), variation)
+            })
+            .collect();
+        self.plural = Some(plural);
+    }
+}
+
+/// One argument of a multi-argument string, referenced as `%#@name@` from the outer string
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Substitution {
+    #[serde(rename = "argNum", skip_serializing_if = "Option::is_none")]
+    pub arg_num: Option<u32>,
+    #[serde(rename = "formatSpecifier", skip_serializing_if = "Option::is_none")]
+    pub format_specifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variations: Option<Variations>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Substitution {
+    /// Non-empty plural forms of this substitution, keyed by CLDR category
+    pub fn plural_forms(&self) -> HashMap<String, String> {
+        self.variations
+            .as_ref()
+            .map(Variations::plural_forms)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Localization {
     #[serde(rename = "stringUnit", skip_serializing_if = "Option::is_none")]
     pub string_unit: Option<StringUnit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variations: Option<Variations>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub substitutions: Option<HashMap<String, Substitution>>,
     #[serde(rename = "shouldTranslate", skip_serializing_if = "Option::is_none")]
     pub should_translate: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Shared shape of a localization and a variation: a string unit or nested variations
+pub trait UnitNode {
+    fn unit(&self) -> Option<&StringUnit>;
+    fn nested(&self) -> Option<&Variations>;
+    fn parts_mut(&mut self) -> (&mut Option<StringUnit>, &mut Option<Variations>);
+
+    /// Substitutions attached to this node (only localizations carry them)
+    fn substitutions(&self) -> Option<&HashMap<String, Substitution>> {
+        None
+    }
+
+    fn substitutions_mut(&mut self) -> Option<&mut HashMap<String, Substitution>> {
+        None
+    }
+
+    /// States of every string unit in this node, including nested variations and substitutions
+    fn unit_states(&self) -> Vec<UnitState> {
+        let mut states: Vec<UnitState> = self.unit().and_then(StringUnit::unit_state).into_iter().collect();
+        if let Some(variations) = self.nested() {
+            states.extend(variations.unit_states());
+        }
+        if let Some(substitutions) = self.substitutions() {
+            for substitution in substitutions.values() {
+                if let Some(variations) = &substitution.variations {
+                    states.extend(variations.unit_states());
+                }
+            }
+        }
+        states
+    }
+
+    /// Change every unit in state `from` to `to`, returning how many changed
+    fn replace_unit_states(&mut self, from: UnitState, to: UnitState) -> usize {
+        let mut count = 0;
+        let (string_unit, variations) = self.parts_mut();
+        if let Some(unit) = string_unit.as_mut().filter(|unit| unit.unit_state() == Some(from)) {
+            unit.state = to.as_str().to_string();
+            count += 1;
+        }
+        if let Some(variations) = variations {
+            count += variations.replace_unit_states(from, to);
+        }
+        if let Some(substitutions) = self.substitutions_mut() {
+            for substitution in substitutions.values_mut() {
+                if let Some(variations) = &mut substitution.variations {
+                    count += variations.replace_unit_states(from, to);
+                }
+            }
+        }
+        count
+    }
+
+    /// Non-empty plural forms of this node, keyed by CLDR category
+    fn plural_forms(&self) -> HashMap<String, String> {
+        self.nested()
+            .map(Variations::plural_forms)
+            .unwrap_or_default()
+    }
+
+    /// Device variants of this node, keyed by device type
+    fn device_variants(&self) -> Option<&HashMap<String, Variation>> {
+        self.nested()
+            .and_then(|variations| variations.device.as_ref())
+            .filter(|device| !device.is_empty())
+    }
+
+    /// Whether this node holds a complete translation for `language`.
+    ///
+    /// When the source varies by device, every source device must be translated; when it has
+    /// substitutions, every substitution needs all plural categories of `language`.
+    fn is_translated(&self, language: &str, source: Option<&dyn UnitNode>) -> bool {
+        if let Some(source_substitutions) = source
+            .and_then(|s| s.substitutions())
+            .filter(|subs| !subs.is_empty())
+        {
+            let complete = source_substitutions.keys().all(|name| {
+                let forms = self
+                    .substitutions()
+                    .and_then(|subs| subs.get(name))
+                    .map(Substitution::plural_forms)
+                    .unwrap_or_default();
+                plural_categories(language)
+                    .iter()
+                    .all(|category| forms.contains_key(*category))
+            });
+            if !complete {
+                return false;
+            }
+        }
+
+        if let Some(source_devices) = source.and_then(|s| s.device_variants()) {
+            let Some(devices) = self.device_variants() else {
+                return false;
+            };
+            return source_devices.iter().all(|(device, source_variant)| {
+                devices
+                    .get(device)
+                    .map(|variant| variant.is_translated(language, Some(source_variant)))
+                    .unwrap_or(false)
+            });
+        }
+
+        if self
+            .unit()
+            .map(|unit| !unit.value.is_empty())
+            .unwrap_or(false)
+        {
+            return true;
+        }
+
+        let forms = self.plural_forms();
+        !forms.is_empty()
+            && plural_categories(language)
+                .iter()
+                .all(|category| forms.contains_key(*category))
+    }
+
+    /// The translation held by this node, if any
+    fn value(&self) -> Option<TranslationValue> {
+        if let Some(devices) = self.device_variants() {
+            let values: HashMap<String, TranslationValue> = devices
+                .iter()
+                .filter_map(|(device, variant)| variant.value().map(|v| (device.clone(), v)))
+                .collect();
+            return Some(TranslationValue::Device(values)).filter(|_| !devices.is_empty());
+        }
+
+        if let Some(unit) = self.unit() {
+            if let Some(substitutions) = self.substitutions().filter(|subs| !subs.is_empty()) {
+                return Some(TranslationValue::Substitutions {
+                    text: unit.value.clone(),
+                    substitutions: substitutions
+                        .iter()
+                        .map(|(name, substitution)| (name.clone(), substitution.plural_forms()))
+                        .collect(),
+                });
+            }
+            return Some(TranslationValue::Text(unit.value.clone()));
+        }
+
+        let forms = self.plural_forms();
+        if forms.is_empty() {
+            None
+        } else {
+            Some(TranslationValue::Plural(forms))
+        }
+    }
+
+    /// Text representing this node in prompts: the plain value, the `other` plural form,
+    /// or the `other` device variant
+    fn representative_text(&self) -> Option<String> {
+        if let Some(devices) = self.device_variants() {
+            return devices
+                .get("other")
+                .and_then(|variant| variant.representative_text())
+                .or_else(|| devices.values().find_map(|variant| variant.representative_text()));
+        }
+
+        if let Some(unit) = self.unit() {
+            return Some(unit.value.clone()).filter(|v| !v.trim().is_empty());
+        }
+
+        let forms = self.plural_forms();
+        forms
+            .get("other")
+            .or_else(|| forms.values().next())
+            .cloned()
+    }
+
+    /// Replace this node's content with `value`, using Xcode's variation structure. Existing
+    /// variation nodes are updated in place, so fields not modelled here survive.
+    ///
+    /// Substitutions live on the localization itself, so only their outer text is stored here;
+    /// see [`Localization::set_substitutions`].
+    fn set_value(&mut self, value: &TranslationValue, state: UnitState) {
+        let (string_unit, variations) = self.parts_mut();
+        match value {
+            TranslationValue::Text(translation)
+            | TranslationValue::Substitutions { text: translation, .. } => {
+                let extra = string_unit.take().map(|unit| unit.extra).unwrap_or_default();
+                *string_unit = Some(StringUnit {
+                    state: state.as_str().to_string(),
+                    value: translation.clone(),
+                    extra,
+                });
+                *variations = None;
+            }
+            TranslationValue::Plural(forms) => {
+                // Xcode stores plural strings only under `variations`
+                *string_unit = None;
+                let mut nested = variations.take().unwrap_or_default();
+                nested.device = None;
+                nested.set_plural_forms(forms, state);
+                *variations = Some(nested);
+            }
+            TranslationValue::Device(devices) => {
+                *string_unit = None;
+                let mut nested = variations.take().unwrap_or_default();
+                let mut existing = nested.device.take().unwrap_or_default();
+                let device = devices
+                    .iter()
+                    .map(|(device, device_value)| {
+                        let mut variation = existing.remove(device).unwrap_or_default();
+                        variation.set_value(device_value, state);
+                        (device.clone(), variation)
+                    })
+                    .collect();
+                nested.plural = None;
+                nested.device = Some(device);
+                *variations = Some(nested);
+            }
+        }
+    }
+}
+
+impl UnitNode for Localization {
+    fn unit(&self) -> Option<&StringUnit> {
+        self.string_unit.as_ref()
+    }
+
+    fn nested(&self) -> Option<&Variations> {
+        self.variations.as_ref()
+    }
+
+    fn parts_mut(&mut self) -> (&mut Option<StringUnit>, &mut Option<Variations>) {
+        (&mut self.string_unit, &mut self.variations)
+    }
+
+    fn substitutions(&self) -> Option<&HashMap<String, Substitution>> {
+        self.substitutions.as_ref()
+    }
+
+    fn substitutions_mut(&mut self) -> Option<&mut HashMap<String, Substitution>> {
+        self.substitutions.as_mut()
+    }
+}
+
+impl Localization {
+    /// Store the plural branches of each substitution, taking `argNum` and `formatSpecifier`
+    /// from the matching source substitution. Existing substitutions are updated in place, and
+    /// those missing from `substitutions` are kept.
+    pub fn set_substitutions(
+        &mut self,
+        substitutions: &HashMap<String, HashMap<String, String>>,
+        source: Option<&HashMap<String, Substitution>>,
+        state: UnitState,
+    ) {
+        let existing = self.substitutions.get_or_insert_with(HashMap::new);
+        for (name, forms) in substitutions {
+            let substitution = existing.entry(name.clone()).or_default();
+            if let Some(source_substitution) = source.and_then(|s| s.get(name)) {
+                substitution.arg_num = source_substitution.arg_num;
+                substitution.format_specifier = source_substitution.format_specifier.clone();
+            }
+            substitution
+                .variations
+                .get_or_insert_with(Variations::default)
+                .set_plural_forms(forms, state);
+        }
+    }
+}
+
+impl UnitNode for Variation {
+    fn unit(&self) -> Option<&StringUnit> {
+        self.string_unit.as_ref()
+    }
+
+    fn nested(&self) -> Option<&Variations> {
+        self.variations.as_ref()
+    }
+
+    fn parts_mut(&mut self) -> (&mut Option<StringUnit>, &mut Option<Variations>) {
+        (&mut self.string_unit, &mut self.variations)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LocalizationEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comment: Option<String>,
+    /// How Xcode found the key: `manual`, `extracted_with_value`, `migrated` or `stale`
+    #[serde(rename = "extractionState", skip_serializing_if = "Option::is_none")]
+    pub extraction_state: Option<String>,
     #[serde(rename = "shouldTranslate", skip_serializing_if = "Option::is_none")]
     pub should_translate: Option<bool>,
     #[serde(skip_serializing_if = "HashMap::is_empty", default)]
     pub localizations: HashMap<String, Localization>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl LocalizationEntry {
+    /// Whether Xcode marked this key as no longer used in source code
+    pub fn is_stale(&self) -> bool {
+        self.extraction_state.as_deref() == Some("stale")
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -48,40 +813,82 @@ pub struct XCStringsData {
     pub source_language: String,
     pub version: String,
     pub strings: HashMap<String, LocalizationEntry>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 pub struct XCStringsFile {
     path: PathBuf,
     pub data: XCStringsData,
+    /// Set when the file is a legacy `.strings`/`.stringsdict` table rather than a String Catalog
+    legacy: Option<StringsTable>,
 }
 
 impl XCStringsFile {
+    /// Load a String Catalog, or a legacy `.strings`/`.stringsdict` table of the source language
+    /// together with its translations in the sibling `xx.lproj` directories
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref().to_path_buf();
+
+        if matches!(path.extension().and_then(|e| e.to_str()), Some("strings" | "stringsdict")) {
+            let (table, data) = StringsTable::load(&path)?;
+            return Ok(Self { path, data, legacy: Some(table) });
+        }
+
         let content = fs::read_to_string(&path)?;
         let data: XCStringsData = serde_json::from_str(&content)
             .map_err(|e| TranslatorError::FileFormatError(format!("Invalid JSON: {}", e)))?;
 
-        Ok(Self { path, data })
+        Ok(Self { path, data, legacy: None })
+    }
+
+    /// A catalog that does not exist on disk yet; `save` creates it
+    pub fn new<P: AsRef<Path>>(path: P, data: XCStringsData) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            data,
+            legacy: None,
+        }
     }
 
     pub fn save(&self) -> Result<()> {
-        let content = serde_json::to_string_pretty(&self.data)?;
+        if let Some(table) = &self.legacy {
+            return table.save(&self.data);
+        }
+
+        // Write in Xcode's own format so saving only changes the units that were edited
+        let content = xcode_json::to_string(&serde_json::to_value(&self.data)?);
         fs::write(&self.path, content)?;
         Ok(())
     }
 
+    /// Back up the file (every language's file for a legacy table), returning the
+    /// backup of the loaded file
     pub fn create_backup(&self) -> Result<PathBuf> {
         let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-        let backup_path = self.path.with_extension(format!("xcstrings.backup_{}", timestamp));
-        fs::copy(&self.path, &backup_path)?;
-        Ok(backup_path)
+        let backup = |path: &Path| -> Result<PathBuf> {
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+            let backup_path = path.with_extension(format!("{}.backup_{}", extension, timestamp));
+            fs::copy(path, &backup_path)?;
+            Ok(backup_path)
+        };
+
+        if let Some(table) = &self.legacy {
+            for path in table.existing_files()? {
+                if path != self.path {
+                    backup(&path)?;
+                }
+            }
+        }
+
+        backup(&self.path)
     }
 
     pub fn get_keys_needing_translation(
         &self,
         target_language: &str,
         mode: &TranslationMode,
+        filter: &TranslationFilter,
     ) -> Vec<String> {
         let mut keys = Vec::new();
 
@@ -101,17 +908,31 @@ impl XCStringsFile {
                 continue;
             }
 
+            // Skip keys that vanished from source code unless explicitly requested
+            if entry.is_stale() && !filter.include_stale_keys {
+                continue;
+            }
+
             // Check based on translation mode
             match mode {
                 TranslationMode::Supplement => {
-                    // Only include if target language doesn't exist or has no translation
-                    if !entry.localizations.contains_key(target_language)
-                        || entry
-                            .localizations
-                            .get(target_language)
-                            .and_then(|loc| loc.string_unit.as_ref())
-                            .map(|unit| unit.value.is_empty())
-                            .unwrap_or(true)
+                    // Only include if target language doesn't exist, lacks a complete translation,
+                    // or has units in a state the filter asks to retranslate
+                    let source = entry
+                        .localizations
+                        .get(&self.data.source_language)
+                        .map(|loc| loc as &dyn UnitNode);
+                    if !entry
+                        .localizations
+                        .get(target_language)
+                        .map(|loc| {
+                            loc.is_translated(target_language, source)
+                                && !loc
+                                    .unit_states()
+                                    .iter()
+                                    .any(|state| filter.retranslate_states.contains(state))
+                        })
+                        .unwrap_or(false)
                     {
                         keys.push(key.clone());
                     }
@@ -142,26 +963,67 @@ impl XCStringsFile {
         false
     }
 
-    pub fn add_translation(&mut self, key: &str, target_language: &str, translation: &str) -> Result<()> {
+    pub fn add_translation(
+        &mut self,
+        key: &str,
+        target_language: &str,
+        translation: &str,
+        state: UnitState,
+    ) -> Result<()> {
+        self.add_translation_value(
+            key,
+            target_language,
+            &TranslationValue::Text(translation.to_string()),
+            state,
+        )
+    }
+
+    /// Store a plain, plural, per-device or substituted translation under the target language
+    pub fn add_translation_value(
+        &mut self,
+        key: &str,
+        target_language: &str,
+        value: &TranslationValue,
+        state: UnitState,
+    ) -> Result<()> {
         let entry = self.data.strings.get_mut(key)
             .ok_or_else(|| TranslatorError::TranslationFailed(format!("Key not found: {}", key)))?;
 
-        if !entry.localizations.contains_key(target_language) {
-            entry.localizations.insert(target_language.to_string(), Localization {
-                string_unit: None,
-                should_translate: None,
-            });
-        }
+        let source_substitutions = entry
+            .localizations
+            .get(&self.data.source_language)
+            .and_then(|loc| loc.substitutions.clone());
 
-        let localization = entry.localizations.get_mut(target_language).unwrap();
-        localization.string_unit = Some(StringUnit {
-            state: "translated".to_string(),
-            value: translation.to_string(),
-        });
+        let localization = entry
+            .localizations
+            .entry(target_language.to_string())
+            .or_default();
+
+        localization.set_value(value, state);
+        if let TranslationValue::Substitutions { substitutions, .. } = value {
+            localization.set_substitutions(substitutions, source_substitutions.as_ref(), state);
+        }
 
         Ok(())
     }
 
+    /// Add a key Xcode cannot extract from source code, so it is marked `manual` and never
+    /// reported as stale. Returns false when the key already exists.
+    pub fn add_manual_key(&mut self, key: &str, comment: Option<String>) -> bool {
+        if self.data.strings.contains_key(key) {
+            return false;
+        }
+        self.data.strings.insert(
+            key.to_string(),
+            LocalizationEntry {
+                comment,
+                extraction_state: Some("manual".to_string()),
+                ..Default::default()
+            },
+        );
+        true
+    }
+
     pub fn mark_as_no_translate(&mut self, key: &str) -> Result<()> {
         let entry = self.data.strings.get_mut(key)
             .ok_or_else(|| TranslatorError::TranslationFailed(format!("Key not found: {}", key)))?;
@@ -170,14 +1032,81 @@ impl XCStringsFile {
         Ok(())
     }
 
+    /// Keys Xcode marked as stale, in Xcode's key order
+    pub fn get_stale_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .data
+            .strings
+            .iter()
+            .filter(|(_, entry)| entry.is_stale())
+            .map(|(key, _)| key.clone())
+            .collect();
+        keys.sort_by(|a, b| xcode_json::compare_keys(a, b));
+        keys
+    }
+
+    /// Delete the given keys from the catalog, returning how many were removed
+    pub fn remove_keys(&mut self, keys: &[String]) -> usize {
+        keys.iter()
+            .filter(|key| self.data.strings.remove(key.as_str()).is_some())
+            .count()
+    }
+
+    /// Keys with at least one unit in `state` for the given language, in Xcode's key order
+    pub fn get_keys_in_state(&self, language: &str, state: UnitState) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .data
+            .strings
+            .iter()
+            .filter(|(_, entry)| {
+                entry
+                    .localizations
+                    .get(language)
+                    .map(|loc| loc.unit_states().contains(&state))
+                    .unwrap_or(false)
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        keys.sort_by(|a, b| xcode_json::compare_keys(a, b));
+        keys
+    }
+
+    /// Promote every `needs_review` unit of a key's localization to `translated`.
+    ///
+    /// Returns the number of units that changed.
+    pub fn mark_reviewed(&mut self, key: &str, language: &str) -> Result<usize> {
+        let entry = self.data.strings.get_mut(key)
+            .ok_or_else(|| TranslatorError::TranslationFailed(format!("Key not found: {}", key)))?;
+
+        Ok(entry
+            .localizations
+            .get_mut(language)
+            .map(|loc| loc.replace_unit_states(UnitState::NeedsReview, UnitState::Translated))
+            .unwrap_or(0))
+    }
+
     pub fn get_existing_translation(&self, key: &str, target_language: &str) -> Option<String> {
         self.data.strings
             .get(key)?
             .localizations
             .get(target_language)?
-            .string_unit
-            .as_ref()
-            .map(|unit| unit.value.clone())
+            .value()
+            .map(|value| value.to_string())
+    }
+
+    /// Keys that take part in translation, in Xcode's order: no empty, do-not-translate or stale keys
+    pub fn get_translatable_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .data
+            .strings
+            .iter()
+            .filter(|(key, entry)| {
+                !key.trim().is_empty() && !self.is_marked_no_translate_globally(entry) && !entry.is_stale()
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        keys.sort_by(|a, b| xcode_json::compare_keys(a, b));
+        keys
     }
 
     pub fn get_keys(&self) -> Vec<String> {
@@ -188,23 +1117,34 @@ impl XCStringsFile {
     pub fn get_translation_context(&self, key: &str, source_language: &str) -> Option<TranslationContext> {
         let entry = self.data.strings.get(key)?;
         
+        let source_localization = entry.localizations.get(source_language);
+
         // Get source text from source language; if missing or empty, fall back to key itself
-        let source_text = entry
-            .localizations
-            .get(source_language)
-            .and_then(|loc| loc.string_unit.as_ref())
-            .map(|unit| unit.value.clone())
-            .filter(|v| !v.trim().is_empty())
+        let source_text = source_localization
+            .and_then(|loc| loc.representative_text())
             .unwrap_or_else(|| key.to_string());
 
+        // Each plural form of the source becomes its own source text
+        let plural_sources = source_localization
+            .map(|loc| loc.plural_forms())
+            .unwrap_or_default();
+
+        // Substitutions contribute their plural branches as additional sources
+        let substitution_sources = source_localization
+            .and_then(|loc| loc.substitutions.as_ref())
+            .map(|subs| {
+                subs.iter()
+                    .map(|(name, substitution)| (name.clone(), substitution.plural_forms()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
         // Get existing translations from other languages
         let mut existing_translations = HashMap::new();
         for (lang, localization) in &entry.localizations {
             if lang != source_language {
-                if let Some(string_unit) = &localization.string_unit {
-                    if !string_unit.value.trim().is_empty() {
-                        existing_translations.insert(lang.clone(), string_unit.value.clone());
-                    }
+                if let Some(text) = localization.representative_text() {
+                    existing_translations.insert(lang.clone(), text);
                 }
             }
         }
@@ -215,14 +1155,59 @@ impl XCStringsFile {
         // Categorize usage
         let usage_category = categorize_usage(key, key_meaning.as_deref());
 
-        Some(TranslationContext {
+        let mut context = TranslationContext {
             key: key.to_string(),
             key_meaning,
             comment: entry.comment.clone(),
             source_text,
             existing_translations,
             usage_category,
-        })
+            plural_sources,
+            plural_category: None,
+            device: None,
+            device_variants: Vec::new(),
+            substitution_sources,
+            substitution: None,
+            memory_matches: Vec::new(),
+        };
+
+        // Each device variant of the source is translated on its own, with the device type attached
+        if let Some(source_devices) = source_localization.and_then(|loc| loc.device_variants()) {
+            let mut devices: Vec<&String> = source_devices.keys().collect();
+            sort_devices(&mut devices);
+
+            context.device_variants = devices
+                .into_iter()
+                .map(|device| {
+                    let variant = &source_devices[device];
+                    let existing_translations = entry
+                        .localizations
+                        .iter()
+                        .filter(|(lang, _)| lang.as_str() != source_language)
+                        .filter_map(|(lang, localization)| {
+                            localization
+                                .device_variants()
+                                .and_then(|variants| variants.get(device))
+                                .and_then(|v| v.representative_text())
+                                .map(|text| (lang.clone(), text))
+                        })
+                        .collect();
+
+                    TranslationContext {
+                        source_text: variant
+                            .representative_text()
+                            .unwrap_or_else(|| context.source_text.clone()),
+                        existing_translations,
+                        plural_sources: variant.plural_forms(),
+                        device: Some(device.clone()),
+                        substitution_sources: HashMap::new(),
+                        ..context.clone()
+                    }
+                })
+                .collect();
+        }
+
+        Some(context)
     }
 
     /// Get translation contexts for multiple keys
@@ -241,8 +1226,508 @@ impl XCStringsFile {
         &self,
         target_language: &str,
         mode: &TranslationMode,
+        filter: &TranslationFilter,
     ) -> Vec<TranslationContext> {
-        let keys = self.get_keys_needing_translation(target_language, mode);
+        let keys = self.get_keys_needing_translation(target_language, mode, filter);
         self.get_translation_contexts(&keys, &self.data.source_language)
     }
-}
\ No newline at end of file
+}
+
+/// Keys written by an import, and the ids that could not be applied
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub keys: usize,
+    pub skipped: Vec<String>,
+}
+
+/// One language's file written by a `CatalogFormat`, and the keys it could not represent
+#[derive(Debug, Default)]
+pub struct FormatExport {
+    pub content: String,
+    pub skipped: Vec<String>,
+}
+
+/// A file format holding one language of a catalog, which catalogs can be exported to
+/// and imported from
+pub trait CatalogFormat {
+    /// Name shown in messages, e.g. `Flutter ARB`
+    fn name(&self) -> &'static str;
+
+    /// Directory exports are written to unless another one is given
+    fn default_directory(&self) -> &'static str;
+
+    /// Path of a language's file, relative to the export directory
+    fn file_path(&self, language: &str) -> PathBuf;
+
+    /// Language of an imported file, from its content or its path
+    fn detect_language(&self, path: &Path, content: &str) -> Option<String>;
+
+    /// Write the keys of `language`; the source language gets every key
+    fn export(&self, file: &XCStringsFile, language: &str) -> Result<FormatExport>;
+
+    /// Apply a file's translations to the catalog's existing keys
+    fn import(&self, file: &mut XCStringsFile, content: &str, language: &str) -> Result<ImportSummary>;
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const PLURAL_CATALOG: &str = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "%lld items" : {
+      "localizations" : {
+        "en" : {
+          "variations" : {
+            "plural" : {
+              "one" : { "stringUnit" : { "state" : "translated", "value" : "%lld item" } },
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld items" } }
+            }
+          }
+        },
+        "ja" : {
+          "variations" : {
+            "plural" : {
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld 個" } }
+            }
+          }
+        }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#;
+
+    fn supplement_keys(file: &XCStringsFile, language: &str) -> Vec<String> {
+        file.get_keys_needing_translation(language, &TranslationMode::Supplement, &TranslationFilter::default())
+    }
+
+    /// Write `content` to a catalog file in a temporary directory and load it
+    pub(crate) fn load_catalog(content: &str) -> (tempfile::TempDir, XCStringsFile) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("Localizable.xcstrings");
+        fs::write(&path, content).unwrap();
+        let file = XCStringsFile::load(&path).unwrap();
+        (dir, file)
+    }
+
+    #[test]
+    fn test_plural_keys_needing_translation() {
+        let (_dir, file) = load_catalog(PLURAL_CATALOG);
+
+        // Japanese only needs `other`, which is present
+        assert!(supplement_keys(&file, "ja").is_empty());
+        // Russian has no localization at all
+        assert_eq!(
+            supplement_keys(&file, "ru"),
+            vec!["%lld items".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_plural_translation_context() {
+        let (_dir, file) = load_catalog(PLURAL_CATALOG);
+        let context = file.get_translation_context("%lld items", "en").unwrap();
+
+        assert_eq!(context.source_text, "%lld items");
+        assert_eq!(context.plural_sources.get("one").map(String::as_str), Some("%lld item"));
+        assert_eq!(context.plural_sources.get("other").map(String::as_str), Some("%lld items"));
+        assert_eq!(context.existing_translations.get("ja").map(String::as_str), Some("%lld 個"));
+    }
+
+    #[test]
+    fn test_plural_translation_round_trip() {
+        let (_dir, mut file) = load_catalog(PLURAL_CATALOG);
+
+        let forms: HashMap<String, String> = [
+            ("one", "%lld элемент"),
+            ("few", "%lld элемента"),
+            ("many", "%lld элементов"),
+            ("other", "%lld элемента"),
+        ]
+        .iter()
+        .map(|(c, v)| (c.to_string(), v.to_string()))
+        .collect();
+        file.add_translation_value(
+            "%lld items",
+            "ru",
+            &TranslationValue::Plural(forms.clone()),
+            UnitState::Translated,
+        )
+        .unwrap();
+        file.save().unwrap();
+
+        let reloaded = XCStringsFile::load(&file.path).unwrap();
+        let ru = &reloaded.data.strings["%lld items"].localizations["ru"];
+        assert!(ru.string_unit.is_none());
+        assert_eq!(ru.plural_forms(), forms);
+        assert!(supplement_keys(&reloaded, "ru").is_empty());
+    }
+
+    const DEVICE_CATALOG: &str = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "Start Workout" : {
+      "localizations" : {
+        "en" : {
+          "variations" : {
+            "device" : {
+              "applewatch" : { "stringUnit" : { "state" : "translated", "value" : "Start" } },
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "Start Workout" } }
+            }
+          }
+        },
+        "de" : {
+          "variations" : {
+            "device" : {
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "Training starten" } }
+            }
+          }
+        }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#;
+
+    #[test]
+    fn test_device_variants_needing_translation() {
+        let (_dir, file) = load_catalog(DEVICE_CATALOG);
+
+        // German is missing the watch variant
+        assert_eq!(
+            supplement_keys(&file, "de"),
+            vec!["Start Workout".to_string()]
+        );
+
+        let context = file.get_translation_context("Start Workout", "en").unwrap();
+        assert_eq!(context.device_variants.len(), 2);
+        assert_eq!(context.device_variants[0].device.as_deref(), Some("applewatch"));
+        assert_eq!(context.device_variants[0].source_text, "Start");
+        assert_eq!(
+            context.device_variants[1].existing_translations.get("de").map(String::as_str),
+            Some("Training starten")
+        );
+    }
+
+    #[test]
+    fn test_device_translation_round_trip() {
+        let (_dir, mut file) = load_catalog(DEVICE_CATALOG);
+
+        let devices: HashMap<String, TranslationValue> = [
+            ("applewatch", "Starten"),
+            ("other", "Training starten"),
+        ]
+        .iter()
+        .map(|(d, v)| (d.to_string(), TranslationValue::Text(v.to_string())))
+        .collect();
+        file.add_translation_value(
+            "Start Workout",
+            "de",
+            &TranslationValue::Device(devices.clone()),
+            UnitState::Translated,
+        )
+        .unwrap();
+        file.save().unwrap();
+
+        let reloaded = XCStringsFile::load(&file.path).unwrap();
+        let de = &reloaded.data.strings["Start Workout"].localizations["de"];
+        assert_eq!(de.value(), Some(TranslationValue::Device(devices)));
+        assert!(supplement_keys(&reloaded, "de").is_empty());
+    }
+
+    const SUBSTITUTION_CATALOG: &str = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "%#@photos@ in %#@albums@" : {
+      "localizations" : {
+        "en" : {
+          "stringUnit" : { "state" : "translated", "value" : "%#@photos@ in %#@albums@" },
+          "substitutions" : {
+            "albums" : {
+              "argNum" : 2,
+              "formatSpecifier" : "lld",
+              "variations" : {
+                "plural" : {
+                  "one" : { "stringUnit" : { "state" : "translated", "value" : "%arg album" } },
+                  "other" : { "stringUnit" : { "state" : "translated", "value" : "%arg albums" } }
+                }
+              }
+            },
+            "photos" : {
+              "argNum" : 1,
+              "formatSpecifier" : "lld",
+              "variations" : {
+                "plural" : {
+                  "one" : { "stringUnit" : { "state" : "translated", "value" : "%arg photo" } },
+                  "other" : { "stringUnit" : { "state" : "translated", "value" : "%arg photos" } }
+                }
+              }
+            }
+          }
+        }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#;
+
+    #[test]
+    fn test_substitution_names() {
+        assert_eq!(substitution_names("%#@photos@ in %#@albums@"), vec!["photos", "albums"]);
+        assert_eq!(substitution_names("%2$#@albums@ / %1$#@photos@"), vec!["albums", "photos"]);
+        assert!(substitution_names("100%% of %@").is_empty());
+    }
+
+    #[test]
+    fn test_substitution_round_trip() {
+        let (_dir, mut file) = load_catalog(SUBSTITUTION_CATALOG);
+        let key = "%#@photos@ in %#@albums@";
+
+        let context = file.get_translation_context(key, "en").unwrap();
+        assert!(context.plural_sources.is_empty());
+        assert_eq!(
+            context.substitution_sources["photos"].get("one").map(String::as_str),
+            Some("%arg photo")
+        );
+        assert_eq!(supplement_keys(&file, "ja").len(), 1);
+
+        let substitutions: HashMap<String, HashMap<String, String>> = [
+            ("photos", "%arg 枚の写真"),
+            ("albums", "%arg 個のアルバム"),
+        ]
+        .iter()
+        .map(|(name, value)| {
+            let forms = [("other".to_string(), value.to_string())].into_iter().collect();
+            (name.to_string(), forms)
+        })
+        .collect();
+        let value = TranslationValue::Substitutions {
+            text: "%#@albums@内の%#@photos@".to_string(),
+            substitutions,
+        };
+        file.add_translation_value(key, "ja", &value, UnitState::Translated).unwrap();
+        file.save().unwrap();
+
+        let reloaded = XCStringsFile::load(&file.path).unwrap();
+        let ja = &reloaded.data.strings[key].localizations["ja"];
+        assert_eq!(ja.value(), Some(value));
+        let photos = &ja.substitutions.as_ref().unwrap()["photos"];
+        assert_eq!(photos.arg_num, Some(1));
+        assert_eq!(photos.format_specifier.as_deref(), Some("lld"));
+        assert!(supplement_keys(&reloaded, "ja").is_empty());
+    }
+
+    #[test]
+    fn test_substitution_rewrite_keeps_unknown_fields() {
+        let (_dir, mut file) = load_catalog(SUBSTITUTION_CATALOG);
+        let key = "%#@photos@ in %#@albums@";
+        let ja: Localization = serde_json::from_str(
+            r#"{
+  "stringUnit" : { "state" : "needs_review", "value" : "%#@albums@内の%#@photos@" },
+  "substitutions" : {
+    "albums" : { "variations" : { "plural" : { "other" : { "stringUnit" : { "state" : "translated", "value" : "%arg 個のアルバム" } } } } },
+    "photos" : {
+      "futureSubstitutionField" : "kept",
+      "variations" : { "plural" : { "other" : { "stringUnit" : { "state" : "needs_review", "value" : "%arg 枚" }, "futureVariationField" : 1 } } }
+    }
+  }
+}"#,
+        )
+        .unwrap();
+        file.data.strings.get_mut(key).unwrap().localizations.insert("ja".to_string(), ja);
+
+        let forms = [("other".to_string(), "%arg 枚の写真".to_string())].into_iter().collect();
+        let value = TranslationValue::Substitutions {
+            text: "%#@albums@内の%#@photos@".to_string(),
+            substitutions: [("photos".to_string(), forms)].into_iter().collect(),
+        };
+        file.add_translation_value(key, "ja", &value, UnitState::Translated).unwrap();
+        file.save().unwrap();
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&file.path).unwrap()).unwrap();
+        let substitutions = &saved["strings"][key]["localizations"]["ja"]["substitutions"];
+        let photos = &substitutions["photos"];
+        assert_eq!(photos["futureSubstitutionField"], "kept");
+        assert_eq!(photos["argNum"], 1);
+        let other = &photos["variations"]["plural"]["other"];
+        assert_eq!(other["futureVariationField"], 1);
+        assert_eq!(other["stringUnit"]["value"], "%arg 枚の写真");
+        // A substitution the new value leaves out is kept
+        assert_eq!(substitutions["albums"]["variations"]["plural"]["other"]["stringUnit"]["value"], "%arg 個のアルバム");
+    }
+
+    #[test]
+    fn test_unknown_fields_round_trip() {
+        let original = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "Done" : {
+      "comment" : "Button title",
+      "extractionState" : "manual",
+      "isCommentAutoGenerated" : true,
+      "localizations" : {
+        "en" : {
+          "stringUnit" : { "state" : "translated", "value" : "Done", "futureUnitField" : 1 },
+          "futureLocalizationField" : { "nested" : [1, 2, 3] }
+        }
+      }
+    }
+  },
+  "version" : "1.0",
+  "futureCatalogField" : "kept"
+}"#;
+        let (_dir, file) = load_catalog(original);
+        file.save().unwrap();
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&file.path).unwrap()).unwrap();
+        let expected: Value = serde_json::from_str(original).unwrap();
+        assert_eq!(saved, expected);
+    }
+
+    #[test]
+    fn test_set_plural_keeps_unknown_fields() {
+        let mut localization: Localization = serde_json::from_str(
+            r#"{
+  "variations" : {
+    "plural" : {
+      "one" : { "stringUnit" : { "state" : "translated", "value" : "%lld Tag", "futureUnitField" : 1 }, "futureVariationField" : true },
+      "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld Tage" } }
+    },
+    "futureVariationsField" : "kept"
+  }
+}"#,
+        )
+        .unwrap();
+
+        let forms: HashMap<String, String> = [("one", "%lld día"), ("other", "%lld días")]
+            .iter()
+            .map(|(c, v)| (c.to_string(), v.to_string()))
+            .collect();
+        localization.set_value(&TranslationValue::Plural(forms), UnitState::NeedsReview);
+
+        let saved = serde_json::to_value(&localization).unwrap();
+        let one = &saved["variations"]["plural"]["one"];
+        assert_eq!(saved["variations"]["futureVariationsField"], "kept");
+        assert_eq!(one["futureVariationField"], true);
+        assert_eq!(one["stringUnit"]["futureUnitField"], 1);
+        assert_eq!(one["stringUnit"]["value"], "%lld día");
+        assert_eq!(one["stringUnit"]["state"], "needs_review");
+    }
+
+    #[test]
+    fn test_save_without_changes_is_byte_identical() {
+        let original = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "%lld items" : {
+      "extractionState" : "manual",
+      "localizations" : {
+        "en" : {
+          "variations" : {
+            "plural" : {
+              "one" : {
+                "stringUnit" : {
+                  "state" : "translated",
+                  "value" : "%lld item"
+                }
+              },
+              "other" : {
+                "stringUnit" : {
+                  "state" : "translated",
+                  "value" : "%lld items"
+                }
+              }
+            }
+          }
+        }
+      }
+    },
+    "Done" : {
+      "comment" : "Button title",
+      "isCommentAutoGenerated" : true
+    }
+  },
+  "version" : "1.0"
+}
+"#;
+        let (_dir, file) = load_catalog(original);
+        file.save().unwrap();
+        assert_eq!(fs::read_to_string(&file.path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_unit_states() {
+        let catalog = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "Cancel" : {
+      "localizations" : {
+        "fr" : { "stringUnit" : { "state" : "needs_review", "value" : "Annuler" } }
+      }
+    },
+    "Save" : {
+      "localizations" : {
+        "fr" : { "stringUnit" : { "state" : "stale", "value" : "Sauver" } }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#;
+        let (_dir, mut file) = load_catalog(catalog);
+
+        // Stale units are retranslated by default, units awaiting review are not
+        assert_eq!(supplement_keys(&file, "fr"), vec!["Save".to_string()]);
+        let filter = TranslationFilter {
+            retranslate_states: vec![UnitState::NeedsReview],
+            ..Default::default()
+        };
+        assert_eq!(
+            file.get_keys_needing_translation("fr", &TranslationMode::Supplement, &filter),
+            vec!["Cancel".to_string()]
+        );
+
+        file.add_translation("Save", "fr", "Enregistrer", UnitState::NeedsReview).unwrap();
+        assert_eq!(
+            file.get_keys_in_state("fr", UnitState::NeedsReview),
+            vec!["Cancel".to_string(), "Save".to_string()]
+        );
+
+        assert_eq!(file.mark_reviewed("Cancel", "fr").unwrap(), 1);
+        assert_eq!(file.get_keys_in_state("fr", UnitState::NeedsReview), vec!["Save".to_string()]);
+        assert_eq!(
+            file.data.strings["Cancel"].localizations["fr"].string_unit.as_ref().unwrap().state,
+            "translated"
+        );
+    }
+
+    #[test]
+    fn test_stale_keys() {
+        let catalog = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "Old title" : { "extractionState" : "stale" },
+    "Title" : { "extractionState" : "extracted_with_value" }
+  },
+  "version" : "1.0"
+}"#;
+        let (_dir, mut file) = load_catalog(catalog);
+
+        assert_eq!(supplement_keys(&file, "de"), vec!["Title".to_string()]);
+        let filter = TranslationFilter {
+            include_stale_keys: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            file.get_keys_needing_translation("de", &TranslationMode::Supplement, &filter).len(),
+            2
+        );
+
+        let stale = file.get_stale_keys();
+        assert_eq!(stale, vec!["Old title".to_string()]);
+        assert_eq!(file.remove_keys(&stale), 1);
+        assert!(!file.data.strings.contains_key("Old title"));
+    }
+}
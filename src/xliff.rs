@@ -0,0 +1,436 @@
+use crate::error::{Result, TranslatorError};
+use crate::plural_rules::plural_categories;
+use crate::xcode_json;
+use crate::xcstrings::{
+    split_variation_id, variation_id, ImportSummary, TranslationValue, UnitNode, UnitState,
+    ValueBuilder, XCStringsFile,
+};
+use crate::xml::{attribute, escape, escape_attribute};
+use quick_xml::escape::unescape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Folder of an `.xcloc` bundle holding the XLIFF
+const LOCALIZED_CONTENTS: &str = "Localized Contents";
+
+/// One `<file>` element: the units of one catalog
+#[derive(Debug, Clone, PartialEq)]
+pub struct XliffFile {
+    /// Path of the catalog, relative to the workspace root
+    pub original: String,
+    pub source_language: String,
+    pub target_language: Option<String>,
+    pub units: Vec<XliffUnit>,
+}
+
+/// One `<trans-unit>`: a key, or one variation of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct XliffUnit {
+    pub id: String,
+    pub source: String,
+    pub target: Option<String>,
+    pub state: Option<String>,
+    pub note: Option<String>,
+}
+
+/// XLIFF `state` attribute for a unit state
+pub fn xliff_state(state: UnitState) -> &'static str {
+    match state {
+        UnitState::New => "new",
+        UnitState::NeedsReview | UnitState::Stale => "needs-review-translation",
+        UnitState::Translated => "translated",
+    }
+}
+
+/// Unit state for an XLIFF `state` attribute; a target without one counts as translated
+pub fn unit_state(state: Option<&str>) -> UnitState {
+    match state {
+        Some("new") | Some("needs-translation") => UnitState::New,
+        Some(state) if state.starts_with("needs-") => UnitState::NeedsReview,
+        _ => UnitState::Translated,
+    }
+}
+
+/// Units of every translatable key, with plural, device and substitution variations as
+/// separate units. Plurals get one unit per category the target language needs.
+pub fn export_units(file: &XCStringsFile, language: &str) -> Vec<XliffUnit> {
+    let data = &file.data;
+    let categories = plural_categories(language);
+    let mut units = Vec::new();
+
+    for key in file.get_translatable_keys() {
+        let entry = &data.strings[&key];
+        let source = entry
+            .localizations
+            .get(&data.source_language)
+            .and_then(|loc| loc.value())
+            .unwrap_or_else(|| TranslationValue::Text(key.clone()));
+        let target_localization = entry.localizations.get(language);
+
+        let targets: HashMap<String, String> = target_localization
+            .and_then(|loc| loc.value())
+            .map(|value| value.flatten(None).into_iter().collect())
+            .unwrap_or_default();
+
+        // A key's units share the weakest state of its translation
+        let state = target_localization
+            .and_then(|loc| loc.unit_states().into_iter().min_by_key(|s| s.rank()))
+            .map(|s| xliff_state(s).to_string());
+
+        for (path, source_text) in source.flatten(Some(categories)) {
+            let target = targets.get(&path).cloned();
+            units.push(XliffUnit {
+                id: variation_id(&key, &path),
+                source: source_text,
+                state: target.as_ref().and(state.clone()),
+                target,
+                note: entry.comment.clone(),
+            });
+        }
+    }
+
+    units
+}
+
+/// Apply translated units to a catalog. Units of one key are combined into a single value,
+/// stored with the weakest state among them.
+pub fn import_units(file: &mut XCStringsFile, units: &[XliffUnit], language: &str) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    let mut keys: BTreeMap<String, (ValueBuilder, UnitState)> = BTreeMap::new();
+
+    for unit in units {
+        let Some(target) = unit.target.as_ref().filter(|t| !t.is_empty()) else {
+            continue;
+        };
+        let (key, path) = split_variation_id(&unit.id);
+        if !file.data.strings.contains_key(key) {
+            summary.skipped.push(unit.id.clone());
+            continue;
+        }
+
+        let state = unit_state(unit.state.as_deref());
+        let (builder, key_state) = keys
+            .entry(key.to_string())
+            .or_insert_with(|| (ValueBuilder::default(), state));
+        if state.rank() < key_state.rank() {
+            *key_state = state;
+        }
+
+        if !builder.insert(path, target.clone()) {
+            summary.skipped.push(unit.id.clone());
+        }
+    }
+
+    for (key, (builder, state)) in keys {
+        if let Some(value) = builder.build() {
+            file.add_translation_value(&key, language, &value, state)?;
+            summary.keys += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Write an XLIFF 1.2 document in the layout Xcode exports
+pub fn to_string(files: &[XliffFile]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<xliff xmlns=\"urn:oasis:names:tc:xliff:document:1.2\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" version=\"1.2\" xsi:schemaLocation=\"urn:oasis:names:tc:xliff:document:1.2 http://docs.oasis-open.org/xliff/v1.2/os/xliff-core-1.2-strict.xsd\">\n");
+
+    for file in files {
+        out.push_str(&format!(
+            "  <file original=\"{}\" source-language=\"{}\"",
+            escape_attribute(&file.original),
+            escape_attribute(&file.source_language)
+        ));
+        if let Some(target) = &file.target_language {
+            out.push_str(&format!(" target-language=\"{}\"", escape_attribute(target)));
+        }
+        out.push_str(" datatype=\"plaintext\">\n");
+        out.push_str("    <header>\n");
+        out.push_str(&format!(
+            "      <tool tool-id=\"rosetta\" tool-name=\"Rosetta\" tool-version=\"{}\"/>\n",
+            env!("CARGO_PKG_VERSION")
+        ));
+        out.push_str("    </header>\n");
+        out.push_str("    <body>\n");
+        for unit in &file.units {
+            out.push_str(&format!(
+                "      <trans-unit id=\"{}\" xml:space=\"preserve\">\n",
+                escape_attribute(&unit.id)
+            ));
+            out.push_str(&format!("        <source>{}</source>\n", escape(&unit.source)));
+            if let Some(target) = &unit.target {
+                match &unit.state {
+                    Some(state) => out.push_str(&format!(
+                        "        <target state=\"{}\">{}</target>\n",
+                        escape(state),
+                        escape(target)
+                    )),
+                    None => out.push_str(&format!("        <target>{}</target>\n", escape(target))),
+                }
+            }
+            if let Some(note) = &unit.note {
+                out.push_str(&format!("        <note>{}</note>\n", escape(note)));
+            }
+            out.push_str("      </trans-unit>\n");
+        }
+        out.push_str("    </body>\n");
+        out.push_str("  </file>\n");
+    }
+
+    out.push_str("</xliff>\n");
+    out
+}
+
+fn xml_error(e: impl std::fmt::Display) -> TranslatorError {
+    TranslatorError::FileFormatError(format!("Invalid XLIFF: {}", e))
+}
+
+/// Parse an XLIFF 1.2 document
+pub fn parse(content: &str) -> Result<Vec<XliffFile>> {
+    let mut reader = Reader::from_str(content);
+    let mut files: Vec<XliffFile> = Vec::new();
+    let mut unit: Option<XliffUnit> = None;
+
+    loop {
+        let (element, is_empty) = match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) => (element, false),
+            Event::Empty(element) => (element, true),
+            Event::End(element) => {
+                if element.local_name().as_ref() == "trans-unit" {
+                    let finished = unit.take().ok_or_else(|| xml_error("unbalanced trans-unit"))?;
+                    files
+                        .last_mut()
+                        .ok_or_else(|| xml_error("trans-unit outside of a file"))?
+                        .units
+                        .push(finished);
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        let text = |reader: &mut Reader<&[u8]>| -> Result<String> {
+            if is_empty {
+                return Ok(String::new());
+            }
+            let end = element.to_end().into_owned();
+            let raw = reader.read_text(end.name()).map_err(xml_error)?;
+            Ok(unescape(&raw.xml10_content()).map_err(xml_error)?.into_owned())
+        };
+
+        match element.local_name().as_ref() {
+            "file" => files.push(XliffFile {
+                original: attribute(&element, "original")?.unwrap_or_default(),
+                source_language: attribute(&element, "source-language")?.unwrap_or_default(),
+                target_language: attribute(&element, "target-language")?,
+                units: Vec::new(),
+            }),
+            "trans-unit" => {
+                let id = attribute(&element, "id")?
+                    .ok_or_else(|| xml_error("trans-unit without an id"))?;
+                let new_unit = XliffUnit {
+                    id,
+                    source: String::new(),
+                    target: None,
+                    state: None,
+                    note: None,
+                };
+                if is_empty {
+                    if let Some(file) = files.last_mut() {
+                        file.units.push(new_unit);
+                    }
+                } else {
+                    unit = Some(new_unit);
+                }
+            }
+            "source" => {
+                let source = text(&mut reader)?;
+                if let Some(unit) = unit.as_mut() {
+                    unit.source = source;
+                }
+            }
+            "target" => {
+                let state = attribute(&element, "state")?;
+                let target = text(&mut reader)?;
+                if let Some(unit) = unit.as_mut() {
+                    unit.target = Some(target);
+                    unit.state = state;
+                }
+            }
+            "note" => {
+                let note = text(&mut reader)?;
+                if let Some(unit) = unit.as_mut() {
+                    unit.note = Some(note).filter(|n| !n.is_empty());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(files)
+}
+
+/// Write an Xcode localization bundle (`ja.xcloc`) holding the XLIFF for one language
+pub fn write_xcloc(bundle: &Path, files: &[XliffFile], source_language: &str, language: &str) -> Result<()> {
+    let localized = bundle.join(LOCALIZED_CONTENTS);
+    fs::create_dir_all(&localized)?;
+    fs::create_dir_all(bundle.join("Source Contents"))?;
+    fs::create_dir_all(bundle.join("Notes"))?;
+
+    fs::write(localized.join(format!("{}.xliff", language)), to_string(files))?;
+
+    let contents = json!({
+        "developmentRegion": source_language,
+        "targetLocale": language,
+        "toolInfo": {
+            "toolID": "rosetta",
+            "toolName": "Rosetta",
+            "toolVersion": env!("CARGO_PKG_VERSION"),
+        },
+        "version": "1.0",
+    });
+    fs::write(bundle.join("contents.json"), xcode_json::to_string(&contents))?;
+    Ok(())
+}
+
+/// Read the XLIFF of an Xcode localization bundle. A bundle holding several `.xliff` files
+/// gives the `<file>` elements of all of them, in file name order.
+pub fn read_xcloc(bundle: &Path) -> Result<Vec<XliffFile>> {
+    let localized = bundle.join(LOCALIZED_CONTENTS);
+    let mut xliffs: Vec<_> = fs::read_dir(&localized)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("xliff"))
+        .collect();
+    xliffs.sort();
+
+    if xliffs.is_empty() {
+        return Err(TranslatorError::FileFormatError(format!(
+            "No .xliff file in {}",
+            localized.display()
+        )));
+    }
+
+    let mut files = Vec::new();
+    for xliff in &xliffs {
+        files.extend(parse(&fs::read_to_string(xliff)?)?);
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xcstrings::tests::load_catalog;
+
+    const CATALOG: &str = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "%lld items" : {
+      "comment" : "Items in the <cart>",
+      "localizations" : {
+        "en" : {
+          "variations" : {
+            "plural" : {
+              "one" : { "stringUnit" : { "state" : "translated", "value" : "%lld item" } },
+              "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld items" } }
+            }
+          }
+        }
+      }
+    },
+    "Hello & welcome" : {
+      "localizations" : {
+        "ru" : { "stringUnit" : { "state" : "needs_review", "value" : "Привет" } }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#;
+
+    #[test]
+    fn test_export_units() {
+        let (_dir, file) = load_catalog(CATALOG);
+        let units = export_units(&file, "ru");
+
+        let ids: Vec<&str> = units.iter().map(|u| u.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "%lld items|==|plural.one",
+                "%lld items|==|plural.few",
+                "%lld items|==|plural.many",
+                "%lld items|==|plural.other",
+                "Hello & welcome",
+            ]
+        );
+        assert_eq!(units[1].source, "%lld items");
+        assert_eq!(units[0].note.as_deref(), Some("Items in the <cart>"));
+        assert_eq!(units[4].target.as_deref(), Some("Привет"));
+        assert_eq!(units[4].state.as_deref(), Some("needs-review-translation"));
+    }
+
+    #[test]
+    fn test_xliff_round_trip() {
+        let (_dir, mut file) = load_catalog(CATALOG);
+        let mut units = export_units(&file, "ru");
+        for (unit, target) in units.iter_mut().zip(["%lld товар", "%lld товара", "%lld товаров", "%lld товара"]) {
+            unit.target = Some(target.to_string());
+            unit.state = Some("translated".to_string());
+        }
+        let document = to_string(&[XliffFile {
+            original: "Localizable.xcstrings".to_string(),
+            source_language: "en".to_string(),
+            target_language: Some("ru".to_string()),
+            units: units.clone(),
+        }]);
+
+        let parsed = parse(&document).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].target_language.as_deref(), Some("ru"));
+        assert_eq!(parsed[0].units, units);
+
+        let summary = import_units(&mut file, &parsed[0].units, "ru").unwrap();
+        assert_eq!(summary.keys, 2);
+        assert!(summary.skipped.is_empty());
+
+        let ru = &file.data.strings["%lld items"].localizations["ru"];
+        assert_eq!(ru.plural_forms()["many"], "%lld товаров");
+        assert_eq!(ru.unit_states(), vec![UnitState::Translated; 4]);
+        // The unchanged target keeps the state the vendor gave it
+        let hello = &file.data.strings["Hello & welcome"].localizations["ru"];
+        assert_eq!(hello.string_unit.as_ref().unwrap().state, "needs_review");
+    }
+
+    #[test]
+    fn test_xcloc_bundle() {
+        let (dir, file) = load_catalog(CATALOG);
+        let files = vec![XliffFile {
+            original: "Localizable.xcstrings".to_string(),
+            source_language: "en".to_string(),
+            target_language: Some("ru".to_string()),
+            units: export_units(&file, "ru"),
+        }];
+        let bundle = dir.path().join("ru.xcloc");
+        write_xcloc(&bundle, &files, "en", "ru").unwrap();
+
+        assert!(bundle.join("contents.json").is_file());
+        assert_eq!(read_xcloc(&bundle).unwrap(), files);
+
+        // A second XLIFF in the bundle is read too
+        let other = vec![XliffFile {
+            original: "InfoPlist.xcstrings".to_string(),
+            ..files[0].clone()
+        }];
+        fs::write(bundle.join(LOCALIZED_CONTENTS).join("ru-InfoPlist.xliff"), to_string(&other)).unwrap();
+        let read = read_xcloc(&bundle).unwrap();
+        let originals: Vec<&str> = read.iter().map(|f| f.original.as_str()).collect();
+        assert_eq!(originals, vec!["InfoPlist.xcstrings", "Localizable.xcstrings"]);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::error::{Result, TranslatorError};
+use quick_xml::events::BytesStart;
+use quick_xml::XmlVersion;
+
+/// Escape text for element content or a quoted attribute value
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape an attribute value; whitespace is written as character references so
+/// attribute normalization does not turn it into spaces
+pub fn escape_attribute(s: &str) -> String {
+    escape(s)
+        .replace('\n', "&#10;")
+        .replace('\r', "&#13;")
+        .replace('\t', "&#9;")
+}
+
+/// Value of an attribute with its references resolved, if the element has it
+pub fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        TranslatorError::FileFormatError(format!("Invalid attribute '{}': {}", name, e))
+    };
+    match element.try_get_attribute(name).map_err(|e| invalid(&e))? {
+        Some(attr) => Ok(Some(
+            attr.normalized_value(XmlVersion::Implicit1_0)
+                .map_err(|e| invalid(&e))?
+                .into_owned(),
+        )),
+        None => Ok(None),
+    }
+}